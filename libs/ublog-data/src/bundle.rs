@@ -0,0 +1,473 @@
+//! Self-contained bundles of a single post and its resources, for sharing or archiving one post.
+//!
+//! A bundle is a tar archive holding a `post.json` manifest, which contains the post and the metadata of its resources,
+//! and one file under `resources/` for each resource, named after the resource with an extension derived from its MIME
+//! type. The metadata of a resource includes whether the resource is public within the post.
+
+use std::collections::HashSet;
+use std::error::Error;
+use std::fmt::{Debug, Display, Formatter};
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::models::{Post, Resource};
+
+const MANIFEST_PATH: &str = "post.json";
+const RESOURCES_DIR: &str = "resources";
+
+const BLOCK_SIZE: usize = 512;
+const MAX_PATH_LEN: usize = 100;
+const MAX_NAME_STEM_LEN: usize = 64;
+
+/// The largest file size that fits in the 11 octal digits of the size field of a ustar header.
+const MAX_FILE_SIZE: u64 = 0o777_7777_7777;
+
+#[derive(Debug, Deserialize, Serialize)]
+struct Manifest {
+    post: Post,
+    resources: Vec<ResourceEntry>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+struct ResourceEntry {
+    id: Uuid,
+    name: String,
+    ty: String,
+    path: String,
+
+    /// Bundles that predate this field only hold public resources.
+    #[serde(default = "default_is_public")]
+    is_public: bool,
+}
+
+fn default_is_public() -> bool {
+    true
+}
+
+/// Encode the given post and its resources into a bundle.
+///
+/// The resources whose IDs are in `private_resource_ids` are recorded as private to the post.
+pub fn encode_bundle(
+    post: &Post,
+    resources: &[Resource],
+    private_resource_ids: &[Uuid],
+) -> Result<Vec<u8>, BundleError> {
+    let mut used_paths = HashSet::new();
+    let mut entries = Vec::with_capacity(resources.len());
+    for res in resources {
+        let path = get_resource_path(res, &mut used_paths);
+        entries.push(ResourceEntry {
+            id: res.id,
+            name: res.name.clone(),
+            ty: res.ty.clone(),
+            path,
+            is_public: !private_resource_ids.contains(&res.id),
+        });
+    }
+
+    let manifest = Manifest {
+        post: post.clone(),
+        resources: entries,
+    };
+    let manifest_data = serde_json::to_vec_pretty(&manifest).unwrap();
+
+    let mut archive = Vec::new();
+    append_tar_entry(&mut archive, MANIFEST_PATH, &manifest_data)?;
+    for (entry, res) in manifest.resources.iter().zip(resources) {
+        append_tar_entry(&mut archive, &entry.path, &res.data)?;
+    }
+
+    // A tar archive ends with two zero blocks.
+    archive.resize(archive.len() + 2 * BLOCK_SIZE, 0);
+
+    Ok(archive)
+}
+
+/// Decode a bundle into the post and the resources within it, together with the IDs of the resources that are private
+/// to the post.
+pub fn decode_bundle(data: &[u8]) -> Result<(Post, Vec<Resource>, Vec<Uuid>), BundleError> {
+    let files = read_tar_entries(data)?;
+    let find_file = |path: &str| {
+        files
+            .iter()
+            .find(|(file_path, _)| file_path == path)
+            .map(|(_, data)| *data)
+    };
+
+    let manifest_data = find_file(MANIFEST_PATH)
+        .ok_or_else(|| BundleError::MissingFile(String::from(MANIFEST_PATH)))?;
+    let manifest: Manifest = serde_json::from_slice(manifest_data)?;
+
+    let mut resources = Vec::with_capacity(manifest.resources.len());
+    let mut private_resource_ids = Vec::new();
+    for entry in manifest.resources {
+        if !entry.is_public {
+            private_resource_ids.push(entry.id);
+        }
+
+        let data = find_file(&entry.path).ok_or(BundleError::MissingFile(entry.path))?;
+        resources.push(Resource {
+            id: entry.id,
+            name: entry.name,
+            ty: entry.ty,
+            data: data.to_vec(),
+        });
+    }
+
+    Ok((manifest.post, resources, private_resource_ids))
+}
+
+/// Errors that occur when encoding or decoding bundles.
+#[derive(Debug)]
+pub enum BundleError {
+    Json(serde_json::Error),
+    Malformed(&'static str),
+    MissingFile(String),
+    PathTooLong(String),
+    FileTooLarge(String),
+}
+
+impl Display for BundleError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Json(err) => write!(f, "manifest error: {}", err),
+            Self::Malformed(reason) => write!(f, "malformed bundle: {}", reason),
+            Self::MissingFile(path) => write!(f, "missing file in bundle: {}", path),
+            Self::PathTooLong(path) => write!(
+                f,
+                "file path too long for bundle: {} (at most {} bytes)",
+                path, MAX_PATH_LEN
+            ),
+            Self::FileTooLarge(path) => write!(
+                f,
+                "file too large for bundle: {} (at most {} bytes)",
+                path, MAX_FILE_SIZE
+            ),
+        }
+    }
+}
+
+impl Error for BundleError {}
+
+impl From<serde_json::Error> for BundleError {
+    fn from(err: serde_json::Error) -> Self {
+        Self::Json(err)
+    }
+}
+
+/// Errors that occur when exporting or importing bundles through a database.
+#[derive(Debug)]
+pub enum DatabaseBundleError<E> {
+    Storage(E),
+    Bundle(BundleError),
+}
+
+impl<E> Display for DatabaseBundleError<E>
+where
+    E: Display,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Storage(err) => write!(f, "storage error: {}", err),
+            Self::Bundle(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl<E> Error for DatabaseBundleError<E> where E: Debug + Display {}
+
+impl<E> From<BundleError> for DatabaseBundleError<E> {
+    fn from(err: BundleError) -> Self {
+        Self::Bundle(err)
+    }
+}
+
+/// Get a unique path within the bundle for the data of the given resource.
+fn get_resource_path(res: &Resource, used_paths: &mut HashSet<String>) -> String {
+    let ext = get_mime_extension(&res.ty);
+
+    let mut stem: String = res
+        .name
+        .chars()
+        .map(|ch| {
+            if ch.is_ascii_alphanumeric() || ch == '-' || ch == '_' || ch == '.' {
+                ch
+            } else {
+                '_'
+            }
+        })
+        .take(MAX_NAME_STEM_LEN)
+        .collect();
+    if let Some(stripped) = stem.strip_suffix(&format!(".{}", ext)) {
+        stem = String::from(stripped);
+    }
+    if stem.is_empty() {
+        stem = format!("{}", res.id.as_hyphenated());
+    }
+
+    let mut path = format!("{}/{}.{}", RESOURCES_DIR, stem, ext);
+    let mut suffix = 1;
+    while used_paths.contains(&path) {
+        suffix += 1;
+        path = format!("{}/{}-{}.{}", RESOURCES_DIR, stem, suffix, ext);
+    }
+    used_paths.insert(path.clone());
+
+    path
+}
+
+/// Get the file extension commonly used for the given MIME type.
+fn get_mime_extension(ty: &str) -> &'static str {
+    match ty {
+        "image/png" => "png",
+        "image/jpeg" => "jpg",
+        "image/gif" => "gif",
+        "image/webp" => "webp",
+        "image/svg+xml" => "svg",
+        "image/bmp" => "bmp",
+        "application/pdf" => "pdf",
+        "text/plain" => "txt",
+        _ => "bin",
+    }
+}
+
+/// Append a regular file entry in the ustar format to the given archive.
+fn append_tar_entry(archive: &mut Vec<u8>, path: &str, data: &[u8]) -> Result<(), BundleError> {
+    let header = create_tar_header(path, data.len() as u64)?;
+
+    archive.extend_from_slice(&header);
+    archive.extend_from_slice(data);
+    let padding = (BLOCK_SIZE - data.len() % BLOCK_SIZE) % BLOCK_SIZE;
+    archive.resize(archive.len() + padding, 0);
+
+    Ok(())
+}
+
+/// Create the ustar header of a regular file with the given path and size.
+fn create_tar_header(path: &str, size: u64) -> Result<[u8; BLOCK_SIZE], BundleError> {
+    if path.len() > MAX_PATH_LEN {
+        return Err(BundleError::PathTooLong(String::from(path)));
+    }
+    if size > MAX_FILE_SIZE {
+        return Err(BundleError::FileTooLarge(String::from(path)));
+    }
+
+    let mut header = [0u8; BLOCK_SIZE];
+    header[..path.len()].copy_from_slice(path.as_bytes());
+    write_octal(&mut header[100..108], 0o644);
+    write_octal(&mut header[108..116], 0);
+    write_octal(&mut header[116..124], 0);
+    write_octal(&mut header[124..136], size);
+    write_octal(&mut header[136..148], 0);
+    header[156] = b'0';
+    header[257..263].copy_from_slice(b"ustar\0");
+    header[263..265].copy_from_slice(b"00");
+
+    // The checksum is computed with the checksum field itself filled with spaces.
+    header[148..156].fill(b' ');
+    let checksum: u64 = header.iter().map(|b| *b as u64).sum();
+    write_octal(&mut header[148..155], checksum);
+
+    Ok(header)
+}
+
+/// Read the paths and the data of the regular files within the given ustar archive.
+fn read_tar_entries(archive: &[u8]) -> Result<Vec<(String, &[u8])>, BundleError> {
+    let mut entries = Vec::new();
+    let mut offset = 0;
+    while offset + BLOCK_SIZE <= archive.len() {
+        let header = &archive[offset..offset + BLOCK_SIZE];
+        if header.iter().all(|b| *b == 0) {
+            return Ok(entries);
+        }
+
+        let path_len = header[..MAX_PATH_LEN]
+            .iter()
+            .position(|b| *b == 0)
+            .unwrap_or(MAX_PATH_LEN);
+        let path = std::str::from_utf8(&header[..path_len])
+            .map_err(|_| BundleError::Malformed("file path is not UTF-8"))?;
+        let size = read_octal(&header[124..136])
+            .ok_or(BundleError::Malformed("invalid file size"))? as usize;
+
+        let data_start = offset + BLOCK_SIZE;
+        let data_end = data_start
+            .checked_add(size)
+            .filter(|end| *end <= archive.len())
+            .ok_or(BundleError::Malformed("truncated file data"))?;
+        if header[156] == b'0' || header[156] == 0 {
+            entries.push((String::from(path), &archive[data_start..data_end]));
+        }
+
+        let padding = (BLOCK_SIZE - size % BLOCK_SIZE) % BLOCK_SIZE;
+        offset = data_end + padding;
+    }
+
+    Err(BundleError::Malformed("missing end of archive"))
+}
+
+/// Write the given value as zero-padded octal digits followed by a NUL into the given field.
+///
+/// The value must fit in one digit less than the length of the field.
+fn write_octal(field: &mut [u8], value: u64) {
+    let digits = format!("{:0width$o}", value, width = field.len() - 1);
+    field[..digits.len()].copy_from_slice(digits.as_bytes());
+    field[digits.len()] = 0;
+}
+
+fn read_octal(field: &[u8]) -> Option<u64> {
+    let digits = std::str::from_utf8(field).ok()?;
+    let digits = digits.trim_matches(|ch: char| ch == '\0' || ch == ' ');
+    u64::from_str_radix(digits, 8).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::db::Database;
+    use crate::fixtures::{PostBuilder, ResourceBuilder};
+    use crate::storage::sqlite::SqliteStorage;
+    use crate::storage::Storage;
+
+    use super::*;
+
+    #[test]
+    fn test_bundle_round_trip() {
+        let post = PostBuilder::new("slug")
+            .title("title")
+            .timestamp(100)
+            .tags(["tag1", "tag2"])
+            .build();
+        let resources = [
+            ResourceBuilder::new("cover.png").data(vec![1; 700]).build(),
+            ResourceBuilder::new("photo")
+                .ty("image/jpeg")
+                .data(vec![2; 10])
+                .build(),
+        ];
+
+        let bundle = encode_bundle(&post, &resources, &[]).unwrap();
+        assert_eq!(bundle.len() % BLOCK_SIZE, 0);
+
+        let (decoded_post, decoded_resources, private_resource_ids) =
+            decode_bundle(&bundle).unwrap();
+        assert_eq!(
+            serde_json::to_value(&decoded_post).unwrap(),
+            serde_json::to_value(&post).unwrap()
+        );
+        assert!(private_resource_ids.is_empty());
+        assert_eq!(decoded_resources.len(), 2);
+        for (decoded, res) in decoded_resources.iter().zip(&resources) {
+            assert_eq!(decoded.id, res.id);
+            assert_eq!(decoded.name, res.name);
+            assert_eq!(decoded.ty, res.ty);
+            assert_eq!(decoded.data, res.data);
+        }
+    }
+
+    #[test]
+    fn test_bundle_resource_paths() {
+        let post = PostBuilder::new("slug").build();
+        let resources = [
+            ResourceBuilder::new("cover.png").build(),
+            ResourceBuilder::new("cover").build(),
+            ResourceBuilder::new("my photo").ty("image/jpeg").build(),
+        ];
+
+        let bundle = encode_bundle(&post, &resources, &[]).unwrap();
+        let paths: Vec<_> = read_tar_entries(&bundle)
+            .unwrap()
+            .into_iter()
+            .map(|(path, _)| path)
+            .collect();
+        assert_eq!(
+            paths,
+            [
+                "post.json",
+                "resources/cover.png",
+                "resources/cover-2.png",
+                "resources/my_photo.jpg"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_bundle_truncated() {
+        let post = PostBuilder::new("slug").build();
+        let resources = [ResourceBuilder::new("cover.png").build()];
+        let bundle = encode_bundle(&post, &resources, &[]).unwrap();
+
+        assert!(matches!(
+            decode_bundle(&bundle[..BLOCK_SIZE + 10]),
+            Err(BundleError::Malformed(_))
+        ));
+    }
+
+    #[test]
+    fn test_bundle_path_too_long() {
+        let path = "a".repeat(MAX_PATH_LEN + 1);
+        assert!(matches!(
+            create_tar_header(&path, 0),
+            Err(BundleError::PathTooLong(p)) if p == path
+        ));
+        assert!(create_tar_header(&path[1..], 0).is_ok());
+    }
+
+    #[test]
+    fn test_bundle_file_too_large() {
+        // 8 GiB no longer fits in the size field.
+        assert!(matches!(
+            create_tar_header("resources/large.bin", 8 << 30),
+            Err(BundleError::FileTooLarge(_))
+        ));
+
+        let header = create_tar_header("resources/large.bin", MAX_FILE_SIZE).unwrap();
+        assert_eq!(read_octal(&header[124..136]), Some(MAX_FILE_SIZE));
+    }
+
+    #[tokio::test]
+    async fn test_bundle_database_round_trip() {
+        let db = Database::new(SqliteStorage::new_memory().unwrap());
+        let post = PostBuilder::new("slug")
+            .timestamp(100)
+            .tags(["tag1", "tag2"])
+            .build();
+        let public_resource = ResourceBuilder::new("cover.png").build();
+        let private_resource = ResourceBuilder::new("draft.png").build();
+        db.insert_post(&post, &[public_resource.clone(), private_resource.clone()])
+            .await
+            .unwrap();
+        db.set_resource_public("slug", &private_resource.id, false)
+            .await
+            .unwrap();
+
+        let bundle = db.export_post_bundle("slug").await.unwrap().unwrap();
+
+        let imported_db = Database::new(SqliteStorage::new_memory().unwrap());
+        let imported_post = imported_db.import_post_bundle(&bundle).await.unwrap();
+        assert_eq!(
+            serde_json::to_value(&imported_post).unwrap(),
+            serde_json::to_value(&post).unwrap()
+        );
+
+        let (stored_post, stored_resources) = imported_db
+            .storage()
+            .get_post_with_resources("slug")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            serde_json::to_value(&stored_post).unwrap(),
+            serde_json::to_value(&post).unwrap()
+        );
+        let mut stored_ids: Vec<_> = stored_resources.iter().map(|res| res.id).collect();
+        let mut expected_ids = vec![public_resource.id, private_resource.id];
+        stored_ids.sort();
+        expected_ids.sort();
+        assert_eq!(stored_ids, expected_ids);
+        assert_eq!(
+            imported_db.get_private_resource_ids("slug").await.unwrap(),
+            [private_resource.id]
+        );
+    }
+}
@@ -0,0 +1,144 @@
+use std::fmt::Debug;
+#[cfg(any(test, feature = "test-util"))]
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::Arc;
+
+use time::OffsetDateTime;
+
+/// Provide the current time to storages.
+pub trait Clock: Debug + Send + Sync {
+    /// Get the Unix timestamp of the current time, in UTC time zone.
+    fn now_unix_timestamp(&self) -> i64;
+}
+
+impl<C> Clock for Arc<C>
+where
+    C: Clock + ?Sized,
+{
+    fn now_unix_timestamp(&self) -> i64 {
+        (**self).now_unix_timestamp()
+    }
+}
+
+/// A [`Clock`] that reads the system clock.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now_unix_timestamp(&self) -> i64 {
+        OffsetDateTime::now_utc().unix_timestamp()
+    }
+}
+
+/// A [`Clock`] that always reports the same time.
+///
+/// This clock is mainly useful in tests where timestamps written by the storage need to be deterministic.
+#[derive(Clone, Copy, Debug)]
+pub struct FixedClock {
+    timestamp: i64,
+}
+
+impl FixedClock {
+    /// Create a new `FixedClock` that always reports the given Unix timestamp.
+    pub fn new(timestamp: i64) -> Self {
+        Self { timestamp }
+    }
+}
+
+impl Clock for FixedClock {
+    fn now_unix_timestamp(&self) -> i64 {
+        self.timestamp
+    }
+}
+
+/// A [`Clock`] whose time only changes when it is set or advanced manually.
+///
+/// Clones of a `MockClock` share the same time, so a test can keep a clone of the clock it hands to a storage and
+/// advance the storage's time from the outside.
+#[cfg(any(test, feature = "test-util"))]
+#[derive(Clone, Debug, Default)]
+pub struct MockClock {
+    timestamp: Arc<AtomicI64>,
+}
+
+#[cfg(any(test, feature = "test-util"))]
+impl MockClock {
+    /// Create a new `MockClock` that reports the given Unix timestamp until it is changed.
+    pub fn new(timestamp: i64) -> Self {
+        Self {
+            timestamp: Arc::new(AtomicI64::new(timestamp)),
+        }
+    }
+
+    /// Set the Unix timestamp reported by the clock.
+    pub fn set(&self, timestamp: i64) {
+        self.timestamp.store(timestamp, Ordering::SeqCst);
+    }
+
+    /// Move the clock forward by the given number of seconds.
+    pub fn advance(&self, secs: i64) {
+        self.timestamp.fetch_add(secs, Ordering::SeqCst);
+    }
+}
+
+#[cfg(any(test, feature = "test-util"))]
+impl Clock for MockClock {
+    fn now_unix_timestamp(&self) -> i64 {
+        self.timestamp.load(Ordering::SeqCst)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::*;
+
+    #[test]
+    fn test_mock_clock_shared() {
+        let clock = MockClock::new(100);
+        let storage_clock = clock.clone();
+        assert_eq!(storage_clock.now_unix_timestamp(), 100);
+
+        clock.advance(15);
+        assert_eq!(storage_clock.now_unix_timestamp(), 115);
+
+        clock.set(42);
+        assert_eq!(storage_clock.now_unix_timestamp(), 42);
+    }
+
+    fn find_direct_time_reads(dir: &Path, found: &mut Vec<String>) {
+        for entry in std::fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+            if path.is_dir() {
+                find_direct_time_reads(&path, found);
+            } else if path.extension().is_some_and(|ext| ext == "rs") && !path.ends_with("clock.rs")
+            {
+                let source = std::fs::read_to_string(&path).unwrap();
+                for pattern in ["now_utc()", "now_local()", "SystemTime::now()"] {
+                    if source.contains(pattern) {
+                        found.push(format!("{}: {}", path.display(), pattern));
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_no_direct_time_reads() {
+        let workspace_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("../..");
+        let mut found = Vec::new();
+        find_direct_time_reads(&workspace_dir.join("src"), &mut found);
+        for entry in std::fs::read_dir(workspace_dir.join("libs")).unwrap() {
+            let src_dir = entry.unwrap().path().join("src");
+            if src_dir.is_dir() {
+                find_direct_time_reads(&src_dir, &mut found);
+            }
+        }
+        assert!(
+            found.is_empty(),
+            "the current time must be read through a Clock: {:?}",
+            found
+        );
+    }
+}
@@ -1,7 +1,13 @@
+use std::collections::BTreeMap;
+
 use uuid::Uuid;
 
-use crate::models::{Post, Resource};
-use crate::storage::{PaginatedList, Pagination, Storage};
+use crate::bundle::DatabaseBundleError;
+use crate::models::{Post, PostKind, Resource, ValidationError};
+use crate::storage::{
+    ArchiveEntry, PaginatedList, Pagination, PostsBySlugs, SlugSuggestion, Storage, TagMatch,
+};
+use crate::time_zone::TimeZone;
 
 /// A database instance that loads data from an underlying storage.
 #[derive(Debug)]
@@ -14,6 +20,11 @@ impl<S> Database<S> {
     pub fn new(storage: S) -> Self {
         Self { storage }
     }
+
+    /// Get the underlying storage of the database.
+    pub fn storage(&self) -> &S {
+        &self.storage
+    }
 }
 
 impl<S> Database<S>
@@ -40,18 +51,253 @@ where
         self.storage.get_posts(special, pagination).await
     }
 
+    /// Get a list of posts of the given kind within the specified page, from the latest post to the earliest one.
+    ///
+    /// The `special` parameter indicates whether the target posts is special posts.
+    pub async fn get_posts_of_kind(
+        &self,
+        special: bool,
+        kind: PostKind,
+        pagination: &Pagination,
+    ) -> Result<PaginatedList<Post>, S::Error> {
+        self.storage
+            .get_posts_of_kind(special, kind, pagination)
+            .await
+    }
+
+    /// Get a list of posts without a category within the specified page, from the latest post to the earliest one.
+    ///
+    /// Both normal and special posts are included.
+    pub async fn get_uncategorized_posts(
+        &self,
+        pagination: &Pagination,
+    ) -> Result<PaginatedList<Post>, S::Error> {
+        self.storage.get_uncategorized_posts(pagination).await
+    }
+
+    /// Get a list of posts whose content has at least `min_words` words within the specified page, from the latest post
+    /// to the earliest one.
+    ///
+    /// Word counts are maintained when posts are written, so the selection does not read the posts' content. Both normal
+    /// and special posts are included.
+    pub async fn get_longform_posts(
+        &self,
+        min_words: usize,
+        pagination: &Pagination,
+    ) -> Result<PaginatedList<Post>, S::Error> {
+        self.storage.get_longform_posts(min_words, pagination).await
+    }
+
+    /// Get a list of posts matching the given tags within the specified page, from the latest post to the earliest one.
+    ///
+    /// With `TagMatch::All`, only posts that have every one of the tags are selected; with `TagMatch::Any`, posts that
+    /// have at least one of the tags are selected. Both normal and special posts are included.
+    pub async fn get_posts_by_tags(
+        &self,
+        tags: &[String],
+        mode: TagMatch,
+        pagination: &Pagination,
+    ) -> Result<PaginatedList<Post>, S::Error> {
+        self.storage.get_posts_by_tags(tags, mode, pagination).await
+    }
+
+    /// Get at most `limit` tags that most often appear on the same posts as the given selected tags, together with how
+    /// many times each of them appears alongside a selected tag.
+    ///
+    /// This is meant for suggesting tags while tagging a new post. The selected tags themselves are never suggested.
+    pub async fn suggest_tags(
+        &self,
+        selected: &[String],
+        limit: usize,
+    ) -> Result<Vec<(String, u64)>, S::Error> {
+        self.storage.suggest_tags(selected, limit).await
+    }
+
+    /// Get the number of posts created within each month, from the latest month to the earliest one.
+    ///
+    /// Months are computed in the time zone given by `time_zone`. Months without any posts are not included. The
+    /// `special` parameter indicates whether the target posts is special posts.
+    pub async fn get_archive_index(
+        &self,
+        special: bool,
+        time_zone: TimeZone,
+    ) -> Result<Vec<ArchiveEntry>, S::Error> {
+        self.storage.get_archive_index(special, time_zone).await
+    }
+
+    /// Get a view of posts created within the specified month, within the specified page.
+    ///
+    /// Months start from 1 and are interpreted in the time zone given by `time_zone`. The `special` parameter
+    /// indicates whether the target posts is special posts.
+    pub async fn get_archive_posts(
+        &self,
+        special: bool,
+        year: i32,
+        month: u8,
+        time_zone: TimeZone,
+        pagination: &Pagination,
+    ) -> Result<PaginatedList<Post>, S::Error> {
+        self.storage
+            .get_archive_posts(special, year, month, time_zone, pagination)
+            .await
+    }
+
+    /// Get the slugs of all posts, including special posts, in ascending order.
+    pub async fn get_post_slugs(&self) -> Result<Vec<String>, S::Error> {
+        self.storage.get_post_slugs().await
+    }
+
+    /// Get the posts, including special posts, whose titles are at most `max_distance` edits away from the given title.
+    ///
+    /// Titles are compared case-insensitively. The returned posts are ordered from the most similar one to the least
+    /// similar one and do not include their content.
+    pub async fn get_posts_with_similar_title<T>(
+        &self,
+        title: T,
+        max_distance: usize,
+    ) -> Result<Vec<Post>, S::Error>
+    where
+        T: AsRef<str>,
+    {
+        let title = title.as_ref();
+        self.storage
+            .get_posts_with_similar_title(title, max_distance)
+            .await
+    }
+
+    /// Get at most `limit` posts, including special posts, whose slugs are similar to the given slug, from the most
+    /// similar one to the least similar one.
+    ///
+    /// This is meant for suggesting posts to readers who request a slug that does not correspond to any post. Slugs are
+    /// compared case-insensitively by their edit distance, and only slugs within a distance of a quarter of the length
+    /// of the given slug (but at least 1) are considered similar.
+    pub async fn get_similar_slugs(
+        &self,
+        slug: &str,
+        limit: usize,
+    ) -> Result<Vec<SlugSuggestion>, S::Error> {
+        self.storage.get_similar_slugs(slug, limit).await
+    }
+
+    /// Get the most recently created post.
+    ///
+    /// The `special` parameter indicates whether the target post is a special post. Returns `None` if there is no such
+    /// post.
+    pub async fn get_latest_post(&self, special: bool) -> Result<Option<Post>, S::Error> {
+        self.storage.get_latest_post(special).await
+    }
+
+    /// Get the earliest created post.
+    ///
+    /// The `special` parameter indicates whether the target post is a special post. Returns `None` if there is no such
+    /// post.
+    pub async fn get_oldest_post(&self, special: bool) -> Result<Option<Post>, S::Error> {
+        self.storage.get_oldest_post(special).await
+    }
+
+    /// Get the posts with the given slugs.
+    ///
+    /// The returned posts are ordered as their slugs appear in `slugs`; duplicate slugs are selected only once. Slugs
+    /// that do not correspond to any post are reported in the returned object. The `with_content` parameter indicates
+    /// whether the content of the posts should be loaded.
+    pub async fn get_posts_by_slugs(
+        &self,
+        slugs: &[String],
+        with_content: bool,
+    ) -> Result<PostsBySlugs, S::Error> {
+        self.storage.get_posts_by_slugs(slugs, with_content).await
+    }
+
+    /// Check whether the given post can be inserted into the database, without inserting it.
+    ///
+    /// Every validation rule is checked, including that no other post has the same slug, and all violations are
+    /// returned. The post is valid if the returned list is empty.
+    pub async fn validate_post(&self, post: &Post) -> Result<Vec<ValidationError>, S::Error> {
+        self.storage.validate_post(post).await
+    }
+
+    /// Export the post with the given slug and its resources as a bundle.
+    ///
+    /// Returns `None` if there is no post with the given slug. See the [`bundle`](crate::bundle) module for the format
+    /// of bundles.
+    pub async fn export_post_bundle(
+        &self,
+        slug: &str,
+    ) -> Result<Option<Vec<u8>>, DatabaseBundleError<S::Error>> {
+        let (post, resources) = match self
+            .storage
+            .get_post_with_resources(slug)
+            .await
+            .map_err(DatabaseBundleError::Storage)?
+        {
+            Some(post_with_resources) => post_with_resources,
+            None => return Ok(None),
+        };
+        let private_resource_ids = self
+            .storage
+            .get_private_resource_ids(slug)
+            .await
+            .map_err(DatabaseBundleError::Storage)?;
+
+        let bundle = crate::bundle::encode_bundle(&post, &resources, &private_resource_ids)?;
+        Ok(Some(bundle))
+    }
+
+    /// Import the post and its resources from the given bundle, and return the imported post.
+    ///
+    /// The import fails if a post with the same slug already exists.
+    pub async fn import_post_bundle(
+        &self,
+        bundle: &[u8],
+    ) -> Result<Post, DatabaseBundleError<S::Error>> {
+        let (post, resources, private_resource_ids) = crate::bundle::decode_bundle(bundle)?;
+        self.storage
+            .insert_post(&post, &resources)
+            .await
+            .map_err(DatabaseBundleError::Storage)?;
+        for id in &private_resource_ids {
+            self.storage
+                .set_resource_public(&post.slug, id, false)
+                .await
+                .map_err(DatabaseBundleError::Storage)?;
+        }
+        Ok(post)
+    }
+
     /// Insert the given post into the database.
+    ///
+    /// The post is checked against every validation rule first and is not inserted if it violates any of them.
     pub async fn insert_post(&self, post: &Post, resources: &[Resource]) -> Result<(), S::Error> {
         self.storage.insert_post(post, resources).await
     }
 
     /// Update the given post into the database.
+    ///
+    /// Like [`Database::insert_post`], nothing is written if the post violates any validation rule.
     pub async fn update_post(&self, post: &Post, resources: &[Resource]) -> Result<(), S::Error> {
         self.storage.update_post(post, resources).await
     }
 
+    /// Set the last update timestamp of the post with the given slug to the current time, without changing anything else.
+    ///
+    /// Returns the new update timestamp, or `None` if there is no post with the given slug.
+    pub async fn touch_post(&self, slug: &str) -> Result<Option<i64>, S::Error> {
+        self.storage.touch_post(slug).await
+    }
+
+    /// Insert the given post into the database, or update the existing post with the same slug in place.
+    ///
+    /// An existing post keeps its creation timestamp and the visibility of the resources that stay attached to it,
+    /// while its other fields, tags and resources are replaced. Returns whether the post is newly inserted. Like
+    /// [`Database::insert_post`], nothing is written if the post violates any validation rule.
+    pub async fn upsert_post(&self, post: &Post, resources: &[Resource]) -> Result<bool, S::Error> {
+        self.storage.upsert_post(post, resources).await
+    }
+
     /// Delete the post object with the given slug.
-    pub async fn delete_post<T>(&self, slug: T) -> Result<(), S::Error>
+    ///
+    /// Returns the deleted post as it was before the deletion, or `None` if there is no post with the given slug.
+    pub async fn delete_post<T>(&self, slug: T) -> Result<Option<Post>, S::Error>
     where
         T: AsRef<str>,
     {
@@ -59,9 +305,78 @@ where
         self.storage.delete_post(slug).await
     }
 
+    /// Delete all static resources attached to the post with the given slug. The post itself is left in place.
+    ///
+    /// Returns the number of deleted resources.
+    pub async fn delete_post_resources<T>(&self, slug: T) -> Result<usize, S::Error>
+    where
+        T: AsRef<str>,
+    {
+        let slug = slug.as_ref();
+        self.storage.delete_post_resources(slug).await
+    }
+
+    /// Set the metadata value of the given key on the post with the given slug, replacing any previous value.
+    ///
+    /// Returns `false` without setting anything if there is no post with the given slug. Post metadata is kept when
+    /// the post is updated and removed when the post is deleted. Changes to it are recorded in commits, so it is
+    /// synchronized to other databases together with the post.
+    pub async fn set_post_meta(
+        &self,
+        slug: &str,
+        key: &str,
+        value: &str,
+    ) -> Result<bool, S::Error> {
+        self.storage.set_post_meta(slug, key, value).await
+    }
+
+    /// Get the metadata value of the given key on the post with the given slug.
+    pub async fn get_post_meta(&self, slug: &str, key: &str) -> Result<Option<String>, S::Error> {
+        self.storage.get_post_meta(slug, key).await
+    }
+
+    /// Get all metadata of the post with the given slug, ordered by key.
+    pub async fn get_all_post_meta(
+        &self,
+        slug: &str,
+    ) -> Result<BTreeMap<String, String>, S::Error> {
+        self.storage.get_all_post_meta(slug).await
+    }
+
     /// Get the static resource object with the given ID.
-    pub async fn get_resource(&self, id: &Uuid) -> Result<Option<Resource>, S::Error> {
-        self.storage.get_resource(id).await
+    ///
+    /// If `public_only` is `true`, resources that are private to any post they are attached to are not returned.
+    pub async fn get_resource(
+        &self,
+        id: &Uuid,
+        public_only: bool,
+    ) -> Result<Option<Resource>, S::Error> {
+        self.storage.get_resource(id, public_only).await
+    }
+
+    /// Get the number of resources attached to the post with the given slug, including private ones.
+    ///
+    /// The resources themselves are not loaded. Returns 0 if there is no such post.
+    pub async fn count_post_resources(&self, slug: &str) -> Result<u64, S::Error> {
+        self.storage.count_post_resources(slug).await
+    }
+
+    /// Get the IDs of the resources that are private to the post with the given slug.
+    pub async fn get_private_resource_ids(&self, slug: &str) -> Result<Vec<Uuid>, S::Error> {
+        self.storage.get_private_resource_ids(slug).await
+    }
+
+    /// Set whether the resource with the given ID is public within the post with the given slug.
+    ///
+    /// Resources are public by default. Private resources are attached to their posts as usual but are not served to
+    /// readers. Returns `false` if the resource is not attached to the post.
+    pub async fn set_resource_public(
+        &self,
+        slug: &str,
+        id: &Uuid,
+        is_public: bool,
+    ) -> Result<bool, S::Error> {
+        self.storage.set_resource_public(slug, id, is_public).await
     }
 
     /// Get a list of resources within the specified page.
@@ -78,4 +393,12 @@ where
     pub async fn delete_resource(&self, id: &Uuid) -> Result<(), S::Error> {
         self.storage.delete_resource(id).await
     }
+
+    /// Get the versions of the schema migrations applied to the database, together with the Unix timestamps at which
+    /// they are applied, from the earliest version to the latest one.
+    ///
+    /// This is a trivial query, which makes it suitable for checking that the database is reachable.
+    pub async fn schema_info(&self) -> Result<Vec<(u32, i64)>, S::Error> {
+        self.storage.get_schema_info().await
+    }
 }
@@ -0,0 +1,408 @@
+//! Builders of posts and resources for tests, a helper that seeds a database with a reproducible blog, and temporary
+//! database files.
+
+use std::path::{Path, PathBuf};
+
+use ublog_doc::{DocumentNode, DocumentNodeTag, DocumentResourceLink};
+use uuid::Uuid;
+
+use crate::db::Database;
+use crate::models::{Post, PostKind, Resource};
+use crate::storage::Storage;
+
+/// Builder of `Post` objects.
+///
+/// Fields that are not set take the following default values: the title `"title"`, the author `"msr"`, the category
+/// `"category"`, no tags, zero timestamps, not special, an article without a link URL or a canonical URL, not synced
+/// from any source, and empty content.
+#[derive(Clone, Debug)]
+pub struct PostBuilder {
+    post: Post,
+}
+
+impl PostBuilder {
+    /// Create a new `PostBuilder` that builds a post with the given slug.
+    pub fn new<T>(slug: T) -> Self
+    where
+        T: Into<String>,
+    {
+        Self {
+            post: Post {
+                title: String::from("title"),
+                slug: slug.into(),
+                author: String::from("msr"),
+                create_timestamp: 0,
+                update_timestamp: 0,
+                category: String::from("category"),
+                tags: Vec::new(),
+                is_special: false,
+                kind: PostKind::Article,
+                link_url: None,
+                canonical_url: None,
+                source: None,
+                source_version: None,
+                word_count: 0,
+                content: DocumentNode::new_empty(),
+            },
+        }
+    }
+
+    /// Set the title of the post.
+    pub fn title<T>(mut self, title: T) -> Self
+    where
+        T: Into<String>,
+    {
+        self.post.title = title.into();
+        self
+    }
+
+    /// Set the author of the post.
+    pub fn author<T>(mut self, author: T) -> Self
+    where
+        T: Into<String>,
+    {
+        self.post.author = author.into();
+        self
+    }
+
+    /// Set both the creation and the last update timestamp of the post.
+    pub fn timestamp(self, timestamp: i64) -> Self {
+        self.create_timestamp(timestamp).update_timestamp(timestamp)
+    }
+
+    /// Set the creation timestamp of the post.
+    pub fn create_timestamp(mut self, timestamp: i64) -> Self {
+        self.post.create_timestamp = timestamp;
+        self
+    }
+
+    /// Set the last update timestamp of the post.
+    pub fn update_timestamp(mut self, timestamp: i64) -> Self {
+        self.post.update_timestamp = timestamp;
+        self
+    }
+
+    /// Set the category of the post.
+    pub fn category<T>(mut self, category: T) -> Self
+    where
+        T: Into<String>,
+    {
+        self.post.category = category.into();
+        self
+    }
+
+    /// Set the tags of the post.
+    pub fn tags<I, T>(mut self, tags: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<String>,
+    {
+        self.post.tags = tags.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Set whether the post is a special post.
+    pub fn special(mut self, is_special: bool) -> Self {
+        self.post.is_special = is_special;
+        self
+    }
+
+    /// Set the kind of the post.
+    pub fn kind(mut self, kind: PostKind) -> Self {
+        self.post.kind = kind;
+        self
+    }
+
+    /// Set the external URL the post links to.
+    pub fn link_url<T>(mut self, link_url: T) -> Self
+    where
+        T: Into<String>,
+    {
+        self.post.link_url = Some(link_url.into());
+        self
+    }
+
+    /// Set the canonical URL of the post.
+    pub fn canonical_url<T>(mut self, canonical_url: T) -> Self
+    where
+        T: Into<String>,
+    {
+        self.post.canonical_url = Some(canonical_url.into());
+        self
+    }
+
+    /// Set the source the post is synced from, and the source's version of the post that was last synced.
+    pub fn source<T>(mut self, source: T, source_version: i64) -> Self
+    where
+        T: Into<String>,
+    {
+        self.post.source = Some(source.into());
+        self.post.source_version = Some(source_version);
+        self
+    }
+
+    /// Set the content of the post.
+    pub fn content(mut self, content: DocumentNode) -> Self {
+        self.post.word_count = content.word_count();
+        self.post.content = content;
+        self
+    }
+
+    /// Build the post.
+    pub fn build(self) -> Post {
+        self.post
+    }
+}
+
+/// Builder of `Resource` objects.
+///
+/// Resources are built with a random ID unless one is set, the type `"image/png"` and 4 bytes of data by default.
+#[derive(Clone, Debug)]
+pub struct ResourceBuilder {
+    resource: Resource,
+}
+
+impl ResourceBuilder {
+    /// Create a new `ResourceBuilder` that builds a resource with the given name.
+    pub fn new<T>(name: T) -> Self
+    where
+        T: Into<String>,
+    {
+        Self {
+            resource: Resource {
+                id: Uuid::new_v4(),
+                name: name.into(),
+                ty: String::from("image/png"),
+                data: vec![0, 1, 2, 3],
+            },
+        }
+    }
+
+    /// Set the ID of the resource.
+    pub fn id(mut self, id: Uuid) -> Self {
+        self.resource.id = id;
+        self
+    }
+
+    /// Set the MIME type of the resource.
+    pub fn ty<T>(mut self, ty: T) -> Self
+    where
+        T: Into<String>,
+    {
+        self.resource.ty = ty.into();
+        self
+    }
+
+    /// Set the data of the resource.
+    pub fn data(mut self, data: Vec<u8>) -> Self {
+        self.resource.data = data;
+        self
+    }
+
+    /// Build the resource.
+    pub fn build(self) -> Resource {
+        self.resource
+    }
+}
+
+/// Insert `posts_count` posts into the given database, together with their resources.
+///
+/// The posts are pseudo-random, spanning several categories, tags and special posts, some of which have resources
+/// embedded in their content. The same `seed` always produces the same posts and resources. Returns the inserted
+/// posts.
+pub async fn seed_blog<S>(
+    db: &Database<S>,
+    posts_count: usize,
+    seed: u64,
+) -> Result<Vec<Post>, S::Error>
+where
+    S: Storage,
+{
+    const CATEGORIES: [&str; 4] = ["rust", "cpp", "notes", ""];
+    const TAGS: [&str; 6] = ["async", "sqlite", "web", "compiler", "linux", "math"];
+    const DAY_SECS: i64 = 86400;
+
+    let mut rng = Lcg::new(seed);
+    let mut posts = Vec::with_capacity(posts_count);
+
+    for i in 0..posts_count {
+        let category = CATEGORIES[rng.next_below(CATEGORIES.len())];
+        let tags: Vec<_> = TAGS
+            .iter()
+            .filter(|_| rng.next_below(3) == 0)
+            .copied()
+            .collect();
+        let create_timestamp = 1_600_000_000 + (i as i64) * DAY_SECS + rng.next_below(86400) as i64;
+        let update_timestamp = create_timestamp + rng.next_below(30) as i64 * DAY_SECS;
+
+        let mut content = DocumentNode::new_empty();
+        let mut resources = Vec::new();
+        for j in 0..rng.next_below(3) {
+            let resource = ResourceBuilder::new(format!("post{}-res{}.png", i, j))
+                .id(rng.next_uuid())
+                .data(vec![i as u8; 1 + rng.next_below(256)])
+                .build();
+            content
+                .children
+                .push(DocumentNode::new(DocumentNodeTag::Image {
+                    link: DocumentResourceLink::Embedded {
+                        uuid: format!("{}", resource.id.as_hyphenated()),
+                    },
+                    caption: None,
+                }));
+            resources.push(resource);
+        }
+
+        let post = PostBuilder::new(format!("post-{}", i))
+            .title(format!("Post {}", i))
+            .create_timestamp(create_timestamp)
+            .update_timestamp(update_timestamp)
+            .category(category)
+            .tags(tags)
+            .special(rng.next_below(10) == 0)
+            .content(content)
+            .build();
+
+        db.insert_post(&post, &resources).await?;
+        posts.push(post);
+    }
+
+    Ok(posts)
+}
+
+/// A database file in the temporary directory with a unique name.
+///
+/// The file is not created until a database is opened on it. When dropped, the file is removed together with the
+/// `-wal` and `-shm` files sqlite keeps next to it.
+#[derive(Debug)]
+pub struct TempDatabaseFile {
+    path: PathBuf,
+}
+
+impl TempDatabaseFile {
+    /// Create a new `TempDatabaseFile` with a unique path.
+    pub fn new() -> Self {
+        let file_name = format!("ublog-test-{}.db", Uuid::new_v4());
+        Self {
+            path: std::env::temp_dir().join(file_name),
+        }
+    }
+
+    /// Get the path of the database file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Default for TempDatabaseFile {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for TempDatabaseFile {
+    fn drop(&mut self) {
+        for suffix in ["", "-wal", "-shm"] {
+            let mut path = self.path.clone().into_os_string();
+            path.push(suffix);
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
+
+/// A linear congruential generator, which is good enough for generating reproducible fixtures.
+#[derive(Clone, Debug)]
+struct Lcg {
+    state: u64,
+}
+
+impl Lcg {
+    fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    fn next_u32(&mut self) -> u32 {
+        self.state = self
+            .state
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        (self.state >> 32) as u32
+    }
+
+    fn next_below(&mut self, bound: usize) -> usize {
+        self.next_u32() as usize % bound
+    }
+
+    fn next_uuid(&mut self) -> Uuid {
+        let mut bytes = [0u8; 16];
+        for chunk in bytes.chunks_mut(4) {
+            chunk.copy_from_slice(&self.next_u32().to_le_bytes());
+        }
+        uuid::Builder::from_random_bytes(bytes).into_uuid()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::storage::sqlite::SqliteStorage;
+
+    use super::*;
+
+    async fn dump_blog<S>(db: &Database<S>) -> Vec<serde_json::Value>
+    where
+        S: Storage,
+    {
+        let mut dump = Vec::new();
+        for slug in db.get_post_slugs().await.unwrap() {
+            let (post, resources) = db
+                .storage()
+                .get_post_with_resources(&slug)
+                .await
+                .unwrap()
+                .unwrap();
+            dump.push(serde_json::to_value(&post).unwrap());
+            for res in resources {
+                dump.push(serde_json::to_value(&res).unwrap());
+            }
+        }
+        dump
+    }
+
+    #[tokio::test]
+    async fn test_seed_blog_reproducible() {
+        let db1 = Database::new(SqliteStorage::new_memory().unwrap());
+        let db2 = Database::new(SqliteStorage::new_memory().unwrap());
+        let posts1 = seed_blog(&db1, 20, 42).await.unwrap();
+        let posts2 = seed_blog(&db2, 20, 42).await.unwrap();
+
+        assert_eq!(
+            serde_json::to_value(&posts1).unwrap(),
+            serde_json::to_value(&posts2).unwrap()
+        );
+
+        let dump1 = dump_blog(&db1).await;
+        assert_eq!(
+            dump1.len(),
+            posts1.len() + db1.get_resources().await.unwrap().len()
+        );
+        assert_eq!(dump1, dump_blog(&db2).await);
+
+        let db3 = Database::new(SqliteStorage::new_memory().unwrap());
+        seed_blog(&db3, 20, 43).await.unwrap();
+        assert_ne!(dump1, dump_blog(&db3).await);
+    }
+
+    #[test]
+    fn test_lcg_reproducible() {
+        let mut rng1 = Lcg::new(42);
+        let mut rng2 = Lcg::new(42);
+        let seq1: Vec<_> = (0..16).map(|_| rng1.next_below(100)).collect();
+        let seq2: Vec<_> = (0..16).map(|_| rng2.next_below(100)).collect();
+        assert_eq!(seq1, seq2);
+        assert_eq!(rng1.next_uuid(), rng2.next_uuid());
+
+        let mut rng3 = Lcg::new(43);
+        let seq3: Vec<_> = (0..16).map(|_| rng3.next_below(100)).collect();
+        assert_ne!(seq1, seq3);
+    }
+}
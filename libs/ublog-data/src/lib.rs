@@ -1,3 +1,8 @@
+pub mod bundle;
+pub mod clock;
 pub mod db;
+#[cfg(any(test, feature = "test-util"))]
+pub mod fixtures;
 pub mod models;
 pub mod storage;
+pub mod time_zone;
@@ -1,9 +1,14 @@
+use std::collections::{BTreeMap, HashSet};
+use std::fmt::{Display, Formatter};
+
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
-use time::OffsetDateTime;
+use time::{OffsetDateTime, UtcOffset};
 use ublog_doc::DocumentNode;
 use uuid::Uuid;
 
+use crate::clock::Clock;
+
 /// A blog post.
 #[derive(Clone, Debug, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
@@ -33,6 +38,35 @@ pub struct Post {
     #[serde(rename = "isSpecial")]
     pub is_special: bool,
 
+    /// The post's kind.
+    #[serde(default)]
+    pub kind: PostKind,
+
+    /// The external URL a link post points to.
+    #[serde(default)]
+    pub link_url: Option<String>,
+
+    /// The URL of the original of a post syndicated from elsewhere.
+    #[serde(default)]
+    pub canonical_url: Option<String>,
+
+    /// The external source the post is synced from, such as `notion`.
+    #[serde(default)]
+    pub source: Option<String>,
+
+    /// The update timestamp of the source's version of the post that was last synced into the post.
+    ///
+    /// The post has been edited locally since the last sync if its update timestamp is newer than this.
+    #[serde(default)]
+    pub source_version: Option<i64>,
+
+    /// Number of words in the post's content.
+    ///
+    /// The words are counted by [`DocumentNode::word_count`] whenever the post is stored, so the value is ignored when
+    /// storing a post.
+    #[serde(default)]
+    pub word_count: usize,
+
     /// Content of the post.
     pub content: DocumentNode,
 }
@@ -47,6 +81,223 @@ impl Post {
     pub fn update_time(&self) -> OffsetDateTime {
         OffsetDateTime::from_unix_timestamp(self.update_timestamp).unwrap()
     }
+
+    /// Get the URL of the post under the given base URL, built by the given strategy.
+    pub fn permalink(&self, base_url: &str, strategy: PermalinkStrategy) -> String {
+        let base_url = base_url.trim_end_matches('/');
+        match strategy {
+            PermalinkStrategy::Slug => format!("{}/{}", base_url, self.slug),
+            PermalinkStrategy::DatePrefixedSlug(utc_offset) => {
+                let date = self.create_time().to_offset(utc_offset).date();
+                format!(
+                    "{}/{:04}/{:02}/{:02}/{}",
+                    base_url,
+                    date.year(),
+                    u8::from(date.month()),
+                    date.day(),
+                    self.slug
+                )
+            }
+        }
+    }
+
+    /// Get the title to display for the post.
+    ///
+    /// This is the post's title, unless the post is a note without a title, in which case the first words of the
+    /// post's content are used instead.
+    pub fn display_title(&self) -> String {
+        if !self.title.trim().is_empty() || self.kind != PostKind::Note {
+            return self.title.clone();
+        }
+
+        let text = self.content.to_plain_text();
+        let mut words = text.split_whitespace();
+        let mut title = words
+            .by_ref()
+            .take(DISPLAY_TITLE_WORDS)
+            .collect::<Vec<_>>()
+            .join(" ");
+        if words.next().is_some() {
+            title.push('…');
+        }
+
+        title
+    }
+
+    /// Check the post's fields against every validation rule and collect all the violations.
+    ///
+    /// The uniqueness of the post's slug cannot be checked without a storage and is not checked here.
+    pub fn validate(&self) -> Vec<ValidationError> {
+        let mut errors = Vec::new();
+
+        // Notes are title-less microposts.
+        if self.kind != PostKind::Note && self.title.trim().is_empty() {
+            errors.push(ValidationError::EmptyTitle);
+        }
+
+        match (&self.link_url, self.kind) {
+            (None, PostKind::Link) => errors.push(ValidationError::MissingLinkUrl),
+            (Some(link_url), PostKind::Link) => {
+                if !is_absolute_http_url(link_url) {
+                    errors.push(ValidationError::InvalidLinkUrl(link_url.clone()));
+                }
+            }
+            (Some(_), _) => errors.push(ValidationError::UnexpectedLinkUrl),
+            (None, _) => {}
+        }
+
+        if let Some(canonical_url) = &self.canonical_url {
+            if !is_absolute_http_url(canonical_url) {
+                errors.push(ValidationError::InvalidCanonicalUrl(canonical_url.clone()));
+            }
+        }
+
+        if self.slug.is_empty() {
+            errors.push(ValidationError::EmptySlug);
+        } else if self
+            .slug
+            .chars()
+            .any(|ch| ch.is_whitespace() || ch.is_control() || "/?#%".contains(ch))
+        {
+            errors.push(ValidationError::InvalidSlug(self.slug.clone()));
+        }
+
+        let mut normalized_tags = HashSet::new();
+        for tag in &self.tags {
+            let normalized_tag = normalize_tag(tag);
+            if normalized_tag.is_empty() {
+                errors.push(ValidationError::EmptyTag);
+            } else if !normalized_tags.insert(normalized_tag) {
+                errors.push(ValidationError::DuplicateTag(tag.clone()));
+            }
+        }
+
+        errors
+    }
+}
+
+/// The number of words of a note's content that make up its display title.
+const DISPLAY_TITLE_WORDS: usize = 8;
+
+/// Normalize the given tag for comparison with other tags: surrounding whitespace is trimmed and the tag is case folded.
+///
+/// Tags that are equal after normalization are the same tag.
+pub fn normalize_tag(tag: &str) -> String {
+    tag.trim().to_lowercase()
+}
+
+/// Determine whether the given string is an absolute `http` or `https` URL with a host.
+fn is_absolute_http_url(url: &str) -> bool {
+    let rest = match url
+        .strip_prefix("https://")
+        .or_else(|| url.strip_prefix("http://"))
+    {
+        Some(rest) => rest,
+        None => return false,
+    };
+
+    let host = rest.split(&['/', '?', '#'][..]).next().unwrap_or_default();
+    !host.is_empty() && !url.chars().any(|ch| ch.is_whitespace() || ch.is_control())
+}
+
+/// Kinds of posts.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PostKind {
+    /// A regular article.
+    #[default]
+    Article,
+
+    /// A link to an external page with a short comment. Link posts carry the URL of the page they point to.
+    Link,
+
+    /// A short micropost, which may have no title.
+    Note,
+}
+
+impl PostKind {
+    /// Get the value that represents the kind in storages.
+    pub fn to_code(self) -> i32 {
+        match self {
+            Self::Article => 0,
+            Self::Link => 1,
+            Self::Note => 2,
+        }
+    }
+
+    /// Get the kind represented by the given value in storages.
+    ///
+    /// Returns `None` if the value does not represent any kind.
+    pub fn from_code(code: i32) -> Option<Self> {
+        match code {
+            0 => Some(Self::Article),
+            1 => Some(Self::Link),
+            2 => Some(Self::Note),
+            _ => None,
+        }
+    }
+}
+
+/// A violation of a validation rule on a post.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum ValidationError {
+    /// The title is empty or consists of whitespace only.
+    EmptyTitle,
+
+    /// The slug is empty.
+    EmptySlug,
+
+    /// The slug contains whitespace, control characters, or characters that are not allowed in a URL path segment.
+    InvalidSlug(String),
+
+    /// A tag is empty or consists of whitespace only.
+    EmptyTag,
+
+    /// A tag equals another tag of the same post after trimming and case folding.
+    DuplicateTag(String),
+
+    /// Another post with the same slug already exists.
+    DuplicateSlug(String),
+
+    /// The post is a link post without a link URL.
+    MissingLinkUrl,
+
+    /// The link URL is not an absolute `http` or `https` URL.
+    InvalidLinkUrl(String),
+
+    /// The post has a link URL but is not a link post.
+    UnexpectedLinkUrl,
+
+    /// The canonical URL is not an absolute `http` or `https` URL.
+    InvalidCanonicalUrl(String),
+}
+
+impl Display for ValidationError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::EmptyTitle => write!(f, "title is empty"),
+            Self::EmptySlug => write!(f, "slug is empty"),
+            Self::InvalidSlug(slug) => write!(f, "invalid slug: {}", slug),
+            Self::EmptyTag => write!(f, "tag is empty"),
+            Self::DuplicateTag(tag) => write!(f, "duplicate tag: {}", tag),
+            Self::DuplicateSlug(slug) => write!(f, "duplicate slug: {}", slug),
+            Self::MissingLinkUrl => write!(f, "link post has no link URL"),
+            Self::InvalidLinkUrl(url) => write!(f, "invalid link URL: {}", url),
+            Self::UnexpectedLinkUrl => write!(f, "only link posts have a link URL"),
+            Self::InvalidCanonicalUrl(url) => write!(f, "invalid canonical URL: {}", url),
+        }
+    }
+}
+
+/// Strategies of building the URL of a post.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum PermalinkStrategy {
+    /// Put the post's slug right under the base URL, as in `https://example.com/posts/hello-world`.
+    Slug,
+
+    /// Prefix the post's slug with the date of its creation in the given time zone, as in
+    /// `https://example.com/posts/2024/02/29/hello-world`.
+    DatePrefixedSlug(UtcOffset),
 }
 
 /// A static resource.
@@ -86,14 +337,22 @@ pub struct Commit {
 }
 
 impl Commit {
-    /// Create a new commit object that contains the specified payload and points the specified commit as its parent
-    /// commit.
-    pub fn new<T>(prev_commit_id: T, payload: CommitPayload) -> Self
+    /// Create a new commit object that contains the specified payload, points the specified commit as its parent
+    /// commit and is created at the current time of the specified clock.
+    pub fn new<T>(prev_commit_id: T, clock: &dyn Clock, payload: CommitPayload) -> Self
+    where
+        T: Into<Vec<u8>>,
+    {
+        Self::with_timestamp(prev_commit_id, clock.now_unix_timestamp(), payload)
+    }
+
+    /// Create a new commit object that contains the specified payload, points the specified commit as its parent
+    /// commit and is created at the specified timestamp.
+    pub fn with_timestamp<T>(prev_commit_id: T, timestamp: i64, payload: CommitPayload) -> Self
     where
         T: Into<Vec<u8>>,
     {
         let prev_commit_id = prev_commit_id.into();
-        let timestamp = OffsetDateTime::now_utc().unix_timestamp();
 
         let mut commit = Self {
             id: Vec::new(),
@@ -120,6 +379,7 @@ pub enum CommitPayload {
     DeletePost(DeletePostCommitPayload),
     CreateResource(CreateResourceCommitPayload),
     DeleteResource(DeleteResourceCommitPayload),
+    UpdatePostMeta(UpdatePostMetaCommitPayload),
 }
 
 impl CommitPayload {
@@ -148,6 +408,14 @@ impl CommitPayload {
     pub fn delete_resource(id: Uuid) -> Self {
         Self::DeleteResource(DeleteResourceCommitPayload { id })
     }
+
+    /// Create a new `UpdatePostMeta` commit payload.
+    pub fn update_post_meta<T>(slug: T) -> Self
+    where
+        T: Into<String>,
+    {
+        Self::UpdatePostMeta(UpdatePostMetaCommitPayload { slug: slug.into() })
+    }
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -170,9 +438,17 @@ pub struct DeleteResourceCommitPayload {
     pub id: Uuid,
 }
 
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct UpdatePostMetaCommitPayload {
+    pub slug: String,
+}
+
 #[derive(Clone, Debug, Default, Deserialize, Serialize)]
 pub struct Delta {
-    pub added_posts: Vec<(Post, Vec<Resource>)>,
+    /// The added posts, each with its resources and the IDs of the resources that are private to it.
+    pub added_posts: Vec<(Post, Vec<Resource>, Vec<Uuid>)>,
+    /// The complete metadata of the added posts and of the posts whose metadata is updated, keyed by post slug.
+    pub post_meta: Vec<(String, BTreeMap<String, String>)>,
     pub deleted_post_slugs: Vec<String>,
     pub added_resources: Vec<Resource>,
     pub deleted_resource_ids: Vec<Uuid>,
@@ -185,3 +461,156 @@ impl Delta {
         Self::default()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use ublog_doc::DocumentNodeTag;
+
+    use super::*;
+
+    fn create_post() -> Post {
+        Post {
+            title: String::from("title"),
+            slug: String::from("hello-world"),
+            author: String::from("msr"),
+            create_timestamp: 1709251199, // 2024-02-29 23:59:59 UTC
+            update_timestamp: 1709251199,
+            category: String::from("category"),
+            tags: Vec::new(),
+            is_special: false,
+            kind: PostKind::Article,
+            link_url: None,
+            canonical_url: None,
+            source: None,
+            source_version: None,
+            word_count: 0,
+            content: DocumentNode::new_empty(),
+        }
+    }
+
+    #[test]
+    fn test_permalink_slug() {
+        let post = create_post();
+        assert_eq!(
+            post.permalink("https://example.com/posts", PermalinkStrategy::Slug),
+            "https://example.com/posts/hello-world"
+        );
+        assert_eq!(
+            post.permalink("https://example.com/posts/", PermalinkStrategy::Slug),
+            "https://example.com/posts/hello-world"
+        );
+    }
+
+    #[test]
+    fn test_permalink_date_prefixed_slug() {
+        let post = create_post();
+
+        let strategy = PermalinkStrategy::DatePrefixedSlug(UtcOffset::UTC);
+        assert_eq!(
+            post.permalink("https://example.com/posts", strategy),
+            "https://example.com/posts/2024/02/29/hello-world"
+        );
+
+        let utc_offset = UtcOffset::from_hms(8, 0, 0).unwrap();
+        let strategy = PermalinkStrategy::DatePrefixedSlug(utc_offset);
+        assert_eq!(
+            post.permalink("https://example.com/posts", strategy),
+            "https://example.com/posts/2024/03/01/hello-world"
+        );
+    }
+
+    #[test]
+    fn test_validate_valid() {
+        let mut post = create_post();
+        post.tags = vec![String::from("rust"), String::from("async")];
+        assert!(post.validate().is_empty());
+    }
+
+    #[test]
+    fn test_validate_collects_all_errors() {
+        let mut post = create_post();
+        post.title = String::from("  ");
+        post.slug = String::from("hello world");
+        post.tags = vec![String::from("Rust"), String::new(), String::from(" rust ")];
+
+        assert_eq!(
+            post.validate(),
+            [
+                ValidationError::EmptyTitle,
+                ValidationError::InvalidSlug(String::from("hello world")),
+                ValidationError::EmptyTag,
+                ValidationError::DuplicateTag(String::from(" rust ")),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_validate_kinds() {
+        let mut post = create_post();
+        post.link_url = Some(String::from("https://example.com"));
+        assert_eq!(post.validate(), [ValidationError::UnexpectedLinkUrl]);
+
+        post.kind = PostKind::Link;
+        assert!(post.validate().is_empty());
+
+        post.link_url = Some(String::from("ftp://example.com/file"));
+        assert_eq!(
+            post.validate(),
+            [ValidationError::InvalidLinkUrl(String::from(
+                "ftp://example.com/file"
+            ))]
+        );
+
+        post.link_url = Some(String::from("https:///path"));
+        assert_eq!(post.validate().len(), 1);
+
+        post.link_url = None;
+        assert_eq!(post.validate(), [ValidationError::MissingLinkUrl]);
+
+        let mut post = create_post();
+        post.title = String::new();
+        assert_eq!(post.validate(), [ValidationError::EmptyTitle]);
+        post.kind = PostKind::Note;
+        assert!(post.validate().is_empty());
+    }
+
+    #[test]
+    fn test_validate_canonical_url() {
+        let mut post = create_post();
+        post.canonical_url = Some(String::from("https://example.com/original"));
+        assert!(post.validate().is_empty());
+
+        for url in [
+            "example.com/original",
+            "ftp://example.com/",
+            "https:///original",
+            "https://exa mple.com/",
+        ] {
+            post.canonical_url = Some(String::from(url));
+            assert_eq!(
+                post.validate(),
+                [ValidationError::InvalidCanonicalUrl(String::from(url))]
+            );
+        }
+    }
+
+    #[test]
+    fn test_display_title() {
+        let mut post = create_post();
+        post.kind = PostKind::Note;
+        assert_eq!(post.display_title(), "title");
+
+        post.title = String::new();
+        let mut paragraph = DocumentNode::new(DocumentNodeTag::Paragraph);
+        paragraph
+            .children
+            .push(DocumentNode::new(DocumentNodeTag::InlineText {
+                text: String::from("one two three four five six seven eight nine"),
+            }));
+        post.content.children.push(paragraph);
+        assert_eq!(
+            post.display_title(),
+            "one two three four five six seven eight…"
+        );
+    }
+}
@@ -3,11 +3,14 @@ pub mod remote;
 pub mod sqlite;
 pub mod sync;
 
+use std::collections::BTreeMap;
+
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-use crate::models::{Commit, Delta, Post, Resource};
+use crate::models::{Commit, Delta, Post, PostKind, Resource, ValidationError};
+use crate::time_zone::TimeZone;
 
 /// Provide storage for databases.
 #[async_trait]
@@ -25,7 +28,14 @@ pub trait Storage: Send + Sync {
         post: &Post,
         post_resources: &[Resource],
     ) -> Result<(), Self::Error>;
-    async fn delete_post(&self, post_slug: &str) -> Result<(), Self::Error>;
+    async fn upsert_post(
+        &self,
+        post: &Post,
+        post_resources: &[Resource],
+    ) -> Result<bool, Self::Error>;
+    async fn touch_post(&self, post_slug: &str) -> Result<Option<i64>, Self::Error>;
+    async fn delete_post(&self, post_slug: &str) -> Result<Option<Post>, Self::Error>;
+    async fn validate_post(&self, post: &Post) -> Result<Vec<ValidationError>, Self::Error>;
     async fn get_post(&self, post_slug: &str) -> Result<Option<Post>, Self::Error>;
     async fn get_post_with_resources(
         &self,
@@ -36,16 +46,104 @@ pub trait Storage: Send + Sync {
         special: bool,
         pagination: &Pagination,
     ) -> Result<PaginatedList<Post>, Self::Error>;
+    async fn get_posts_of_kind(
+        &self,
+        special: bool,
+        kind: PostKind,
+        pagination: &Pagination,
+    ) -> Result<PaginatedList<Post>, Self::Error>;
+    async fn get_uncategorized_posts(
+        &self,
+        pagination: &Pagination,
+    ) -> Result<PaginatedList<Post>, Self::Error>;
+    async fn get_longform_posts(
+        &self,
+        min_words: usize,
+        pagination: &Pagination,
+    ) -> Result<PaginatedList<Post>, Self::Error>;
+    async fn get_posts_by_tags(
+        &self,
+        tags: &[String],
+        mode: TagMatch,
+        pagination: &Pagination,
+    ) -> Result<PaginatedList<Post>, Self::Error>;
+    async fn suggest_tags(
+        &self,
+        selected: &[String],
+        limit: usize,
+    ) -> Result<Vec<(String, u64)>, Self::Error>;
+    async fn get_archive_index(
+        &self,
+        special: bool,
+        time_zone: TimeZone,
+    ) -> Result<Vec<ArchiveEntry>, Self::Error>;
+    async fn get_archive_posts(
+        &self,
+        special: bool,
+        year: i32,
+        month: u8,
+        time_zone: TimeZone,
+        pagination: &Pagination,
+    ) -> Result<PaginatedList<Post>, Self::Error>;
+    async fn get_post_slugs(&self) -> Result<Vec<String>, Self::Error>;
+    async fn get_posts_with_similar_title(
+        &self,
+        title: &str,
+        max_distance: usize,
+    ) -> Result<Vec<Post>, Self::Error>;
+    async fn get_similar_slugs(
+        &self,
+        slug: &str,
+        limit: usize,
+    ) -> Result<Vec<SlugSuggestion>, Self::Error>;
+    async fn get_latest_post(&self, special: bool) -> Result<Option<Post>, Self::Error>;
+    async fn get_oldest_post(&self, special: bool) -> Result<Option<Post>, Self::Error>;
+    async fn get_posts_by_slugs(
+        &self,
+        post_slugs: &[String],
+        with_content: bool,
+    ) -> Result<PostsBySlugs, Self::Error>;
+
+    async fn set_post_meta(
+        &self,
+        post_slug: &str,
+        key: &str,
+        value: &str,
+    ) -> Result<bool, Self::Error>;
+    async fn get_post_meta(
+        &self,
+        post_slug: &str,
+        key: &str,
+    ) -> Result<Option<String>, Self::Error>;
+    async fn get_all_post_meta(
+        &self,
+        post_slug: &str,
+    ) -> Result<BTreeMap<String, String>, Self::Error>;
 
     async fn insert_resource(&self, resource: &Resource) -> Result<(), Self::Error>;
     async fn delete_resource(&self, resource_id: &Uuid) -> Result<(), Self::Error>;
-    async fn get_resource(&self, resource_id: &Uuid) -> Result<Option<Resource>, Self::Error>;
+    async fn delete_post_resources(&self, post_slug: &str) -> Result<usize, Self::Error>;
+    async fn get_resource(
+        &self,
+        resource_id: &Uuid,
+        public_only: bool,
+    ) -> Result<Option<Resource>, Self::Error>;
+    async fn count_post_resources(&self, post_slug: &str) -> Result<u64, Self::Error>;
+    async fn get_private_resource_ids(&self, post_slug: &str) -> Result<Vec<Uuid>, Self::Error>;
+    async fn set_resource_public(
+        &self,
+        post_slug: &str,
+        resource_id: &Uuid,
+        is_public: bool,
+    ) -> Result<bool, Self::Error>;
     async fn get_resources(&self) -> Result<Vec<Resource>, Self::Error>;
 
     async fn get_commits_since(&self, since_timestamp: i64) -> Result<Vec<Commit>, Self::Error>;
     async fn get_latest_commit(&self) -> Result<Option<Commit>, Self::Error>;
 
     async fn apply_delta(&self, delta: &Delta) -> Result<(), Self::Error>;
+
+    async fn get_schema_info(&self) -> Result<Vec<(u32, i64)>, Self::Error>;
 }
 
 /// Pagination parameters.
@@ -62,7 +160,6 @@ impl Pagination {
     pub fn from_page_and_size(page: usize, page_size: usize) -> Self {
         assert!(page > 0);
         assert!(page_size > 0);
-        assert!((page - 1).checked_mul(page_size).is_some());
 
         Self { page, page_size }
     }
@@ -78,8 +175,10 @@ impl Pagination {
     }
 
     /// Get the number of items before the first element of the specified page.
-    pub fn skip_count(&self) -> usize {
-        (self.page - 1) * self.page_size
+    ///
+    /// Returns `None` if the number overflows, in which case the page lies beyond the end of any list.
+    pub fn skip_count(&self) -> Option<usize> {
+        self.page.checked_sub(1)?.checked_mul(self.page_size)
     }
 }
 
@@ -93,3 +192,55 @@ pub struct PaginatedList<T> {
     #[serde(rename = "totalCount")]
     pub total_count: usize,
 }
+
+/// The number of posts created within a month.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArchiveEntry {
+    /// The year.
+    pub year: i32,
+
+    /// The month, starting from 1.
+    pub month: u8,
+
+    /// The number of posts created within the month.
+    pub posts_count: usize,
+}
+
+/// How posts are matched against a set of tags.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum TagMatch {
+    /// Match posts that have every one of the tags.
+    All,
+
+    /// Match posts that have at least one of the tags.
+    Any,
+}
+
+/// A post suggested in place of a slug that does not correspond to any post.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SlugSuggestion {
+    /// Slug of the suggested post.
+    pub slug: String,
+
+    /// Title of the suggested post.
+    pub title: String,
+}
+
+/// Posts selected by a list of slugs.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PostsBySlugs {
+    /// The selected posts, in the order in which their slugs are requested.
+    pub posts: Vec<Post>,
+
+    /// The requested slugs that do not correspond to any post.
+    pub missing_slugs: Vec<String>,
+}
+
+/// The maximum number of slugs that can be requested in one selection by slugs.
+pub const MAX_SELECT_SLUGS: usize = 100;
+
+/// The maximum number of items that can be requested in one page of a paginated selection.
+pub const MAX_PAGE_SIZE: usize = 100;
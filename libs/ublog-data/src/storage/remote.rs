@@ -1,4 +1,5 @@
 use std::borrow::Cow;
+use std::collections::BTreeMap;
 use std::error::Error;
 use std::fmt::{Display, Formatter};
 
@@ -9,8 +10,11 @@ use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
 use tokio::sync::Mutex;
 use uuid::Uuid;
 
-use crate::models::{Commit, Delta, Post, Resource};
-use crate::storage::{PaginatedList, Pagination, Storage};
+use crate::models::{Commit, Delta, Post, PostKind, Resource, ValidationError};
+use crate::storage::{
+    ArchiveEntry, PaginatedList, Pagination, PostsBySlugs, SlugSuggestion, Storage, TagMatch,
+};
+use crate::time_zone::TimeZone;
 
 /// A server that exposes an inner storage object through an underlying channel to a remote storage client.
 #[derive(Debug)]
@@ -64,37 +68,161 @@ where
                 post,
                 post_resources,
             } => {
-                process_request!(self, self.inner.insert_post(&*post, &*post_resources));
+                process_request!(self, self.inner.insert_post(&post, &post_resources));
             }
             Request::UpdatePost {
                 post,
                 post_resources,
             } => {
-                process_request!(self, self.inner.update_post(&*post, &*post_resources));
+                process_request!(self, self.inner.update_post(&post, &post_resources));
+            }
+            Request::UpsertPost {
+                post,
+                post_resources,
+            } => {
+                process_request!(self, self.inner.upsert_post(&post, &post_resources));
+            }
+            Request::TouchPost { post_slug } => {
+                process_request!(self, self.inner.touch_post(&post_slug));
             }
             Request::DeletePost { post_slug } => {
-                process_request!(self, self.inner.delete_post(&*post_slug));
+                process_request!(self, self.inner.delete_post(&post_slug));
+            }
+            Request::ValidatePost { post } => {
+                process_request!(self, self.inner.validate_post(&post));
             }
             Request::GetPost { post_slug } => {
-                process_request!(self, self.inner.get_post(&*post_slug));
+                process_request!(self, self.inner.get_post(&post_slug));
             }
             Request::GetPostWithResources { post_slug } => {
-                process_request!(self, self.inner.get_post_with_resources(&*post_slug));
+                process_request!(self, self.inner.get_post_with_resources(&post_slug));
             }
             Request::GetPosts {
                 special,
                 pagination,
             } => {
-                process_request!(self, self.inner.get_posts(special, &*pagination));
+                process_request!(self, self.inner.get_posts(special, &pagination));
+            }
+            Request::GetPostsOfKind {
+                special,
+                kind,
+                pagination,
+            } => {
+                process_request!(
+                    self,
+                    self.inner.get_posts_of_kind(special, kind, &pagination)
+                );
+            }
+            Request::GetUncategorizedPosts { pagination } => {
+                process_request!(self, self.inner.get_uncategorized_posts(&pagination));
+            }
+            Request::GetLongformPosts {
+                min_words,
+                pagination,
+            } => {
+                process_request!(self, self.inner.get_longform_posts(min_words, &pagination));
+            }
+            Request::GetPostsByTags {
+                tags,
+                mode,
+                pagination,
+            } => {
+                process_request!(self, self.inner.get_posts_by_tags(&tags, mode, &pagination));
+            }
+            Request::GetArchiveIndex { special, time_zone } => {
+                process_request!(self, self.inner.get_archive_index(special, time_zone));
+            }
+            Request::GetArchivePosts {
+                special,
+                year,
+                month,
+                time_zone,
+                pagination,
+            } => {
+                process_request!(
+                    self,
+                    self.inner
+                        .get_archive_posts(special, year, month, time_zone, &pagination)
+                );
+            }
+            Request::GetPostSlugs => {
+                process_request!(self, self.inner.get_post_slugs());
+            }
+            Request::GetPostsWithSimilarTitle {
+                title,
+                max_distance,
+            } => {
+                process_request!(
+                    self,
+                    self.inner
+                        .get_posts_with_similar_title(&title, max_distance)
+                );
+            }
+            Request::SuggestTags { selected, limit } => {
+                process_request!(self, self.inner.suggest_tags(&selected, limit));
+            }
+            Request::GetSimilarSlugs { slug, limit } => {
+                process_request!(self, self.inner.get_similar_slugs(&slug, limit));
+            }
+            Request::GetLatestPost { special } => {
+                process_request!(self, self.inner.get_latest_post(special));
+            }
+            Request::GetOldestPost { special } => {
+                process_request!(self, self.inner.get_oldest_post(special));
+            }
+            Request::GetPostsBySlugs {
+                post_slugs,
+                with_content,
+            } => {
+                process_request!(
+                    self,
+                    self.inner.get_posts_by_slugs(&post_slugs, with_content)
+                );
+            }
+            Request::SetPostMeta {
+                post_slug,
+                key,
+                value,
+            } => {
+                process_request!(self, self.inner.set_post_meta(&post_slug, &key, &value));
+            }
+            Request::GetPostMeta { post_slug, key } => {
+                process_request!(self, self.inner.get_post_meta(&post_slug, &key));
+            }
+            Request::GetAllPostMeta { post_slug } => {
+                process_request!(self, self.inner.get_all_post_meta(&post_slug));
             }
             Request::InsertResource { resource } => {
-                process_request!(self, self.inner.insert_resource(&*resource));
+                process_request!(self, self.inner.insert_resource(&resource));
             }
             Request::DeleteResource { resource_id } => {
                 process_request!(self, self.inner.delete_resource(&resource_id));
             }
-            Request::GetResource { resource_id } => {
-                process_request!(self, self.inner.get_resource(&resource_id));
+            Request::DeletePostResources { post_slug } => {
+                process_request!(self, self.inner.delete_post_resources(&post_slug));
+            }
+            Request::GetResource {
+                resource_id,
+                public_only,
+            } => {
+                process_request!(self, self.inner.get_resource(&resource_id, public_only));
+            }
+            Request::CountPostResources { post_slug } => {
+                process_request!(self, self.inner.count_post_resources(&post_slug));
+            }
+            Request::GetPrivateResourceIds { post_slug } => {
+                process_request!(self, self.inner.get_private_resource_ids(&post_slug));
+            }
+            Request::SetResourcePublic {
+                post_slug,
+                resource_id,
+                is_public,
+            } => {
+                process_request!(
+                    self,
+                    self.inner
+                        .set_resource_public(&post_slug, &resource_id, is_public)
+                );
             }
             Request::GetResources => {
                 process_request!(self, self.inner.get_resources());
@@ -106,7 +234,10 @@ where
                 process_request!(self, self.inner.get_latest_commit());
             }
             Request::ApplyDelta { delta } => {
-                process_request!(self, self.inner.apply_delta(&*delta));
+                process_request!(self, self.inner.apply_delta(&delta));
+            }
+            Request::GetSchemaInfo => {
+                process_request!(self, self.inner.get_schema_info());
             }
         }
 
@@ -188,13 +319,39 @@ where
         .await
     }
 
-    async fn delete_post(&self, post_slug: &str) -> Result<(), Self::Error> {
+    async fn upsert_post(
+        &self,
+        post: &Post,
+        post_resources: &[Resource],
+    ) -> Result<bool, Self::Error> {
+        self.execute_request(&Request::UpsertPost {
+            post: Cow::Borrowed(post),
+            post_resources: Cow::Borrowed(post_resources),
+        })
+        .await
+    }
+
+    async fn touch_post(&self, post_slug: &str) -> Result<Option<i64>, Self::Error> {
+        self.execute_request(&Request::TouchPost {
+            post_slug: Cow::Borrowed(post_slug),
+        })
+        .await
+    }
+
+    async fn delete_post(&self, post_slug: &str) -> Result<Option<Post>, Self::Error> {
         self.execute_request(&Request::DeletePost {
             post_slug: Cow::Borrowed(post_slug),
         })
         .await
     }
 
+    async fn validate_post(&self, post: &Post) -> Result<Vec<ValidationError>, Self::Error> {
+        self.execute_request(&Request::ValidatePost {
+            post: Cow::Borrowed(post),
+        })
+        .await
+    }
+
     async fn get_post(&self, post_slug: &str) -> Result<Option<Post>, Self::Error> {
         self.execute_request(&Request::GetPost {
             post_slug: Cow::Borrowed(post_slug),
@@ -224,6 +381,181 @@ where
         .await
     }
 
+    async fn get_posts_of_kind(
+        &self,
+        special: bool,
+        kind: PostKind,
+        pagination: &Pagination,
+    ) -> Result<PaginatedList<Post>, Self::Error> {
+        self.execute_request(&Request::GetPostsOfKind {
+            special,
+            kind,
+            pagination: Cow::Borrowed(pagination),
+        })
+        .await
+    }
+
+    async fn get_uncategorized_posts(
+        &self,
+        pagination: &Pagination,
+    ) -> Result<PaginatedList<Post>, Self::Error> {
+        self.execute_request(&Request::GetUncategorizedPosts {
+            pagination: Cow::Borrowed(pagination),
+        })
+        .await
+    }
+
+    async fn get_longform_posts(
+        &self,
+        min_words: usize,
+        pagination: &Pagination,
+    ) -> Result<PaginatedList<Post>, Self::Error> {
+        self.execute_request(&Request::GetLongformPosts {
+            min_words,
+            pagination: Cow::Borrowed(pagination),
+        })
+        .await
+    }
+
+    async fn get_posts_by_tags(
+        &self,
+        tags: &[String],
+        mode: TagMatch,
+        pagination: &Pagination,
+    ) -> Result<PaginatedList<Post>, Self::Error> {
+        self.execute_request(&Request::GetPostsByTags {
+            tags: Cow::Borrowed(tags),
+            mode,
+            pagination: Cow::Borrowed(pagination),
+        })
+        .await
+    }
+
+    async fn get_archive_index(
+        &self,
+        special: bool,
+        time_zone: TimeZone,
+    ) -> Result<Vec<ArchiveEntry>, Self::Error> {
+        self.execute_request(&Request::GetArchiveIndex { special, time_zone })
+            .await
+    }
+
+    async fn get_archive_posts(
+        &self,
+        special: bool,
+        year: i32,
+        month: u8,
+        time_zone: TimeZone,
+        pagination: &Pagination,
+    ) -> Result<PaginatedList<Post>, Self::Error> {
+        self.execute_request(&Request::GetArchivePosts {
+            special,
+            year,
+            month,
+            time_zone,
+            pagination: Cow::Borrowed(pagination),
+        })
+        .await
+    }
+
+    async fn get_post_slugs(&self) -> Result<Vec<String>, Self::Error> {
+        self.execute_request(&Request::GetPostSlugs).await
+    }
+
+    async fn get_posts_with_similar_title(
+        &self,
+        title: &str,
+        max_distance: usize,
+    ) -> Result<Vec<Post>, Self::Error> {
+        self.execute_request(&Request::GetPostsWithSimilarTitle {
+            title: Cow::Borrowed(title),
+            max_distance,
+        })
+        .await
+    }
+
+    async fn suggest_tags(
+        &self,
+        selected: &[String],
+        limit: usize,
+    ) -> Result<Vec<(String, u64)>, Self::Error> {
+        self.execute_request(&Request::SuggestTags {
+            selected: Cow::Borrowed(selected),
+            limit,
+        })
+        .await
+    }
+
+    async fn get_similar_slugs(
+        &self,
+        slug: &str,
+        limit: usize,
+    ) -> Result<Vec<SlugSuggestion>, Self::Error> {
+        self.execute_request(&Request::GetSimilarSlugs {
+            slug: Cow::Borrowed(slug),
+            limit,
+        })
+        .await
+    }
+
+    async fn get_latest_post(&self, special: bool) -> Result<Option<Post>, Self::Error> {
+        self.execute_request(&Request::GetLatestPost { special })
+            .await
+    }
+
+    async fn get_oldest_post(&self, special: bool) -> Result<Option<Post>, Self::Error> {
+        self.execute_request(&Request::GetOldestPost { special })
+            .await
+    }
+
+    async fn get_posts_by_slugs(
+        &self,
+        post_slugs: &[String],
+        with_content: bool,
+    ) -> Result<PostsBySlugs, Self::Error> {
+        self.execute_request(&Request::GetPostsBySlugs {
+            post_slugs: Cow::Borrowed(post_slugs),
+            with_content,
+        })
+        .await
+    }
+
+    async fn set_post_meta(
+        &self,
+        post_slug: &str,
+        key: &str,
+        value: &str,
+    ) -> Result<bool, Self::Error> {
+        self.execute_request(&Request::SetPostMeta {
+            post_slug: Cow::Borrowed(post_slug),
+            key: Cow::Borrowed(key),
+            value: Cow::Borrowed(value),
+        })
+        .await
+    }
+
+    async fn get_post_meta(
+        &self,
+        post_slug: &str,
+        key: &str,
+    ) -> Result<Option<String>, Self::Error> {
+        self.execute_request(&Request::GetPostMeta {
+            post_slug: Cow::Borrowed(post_slug),
+            key: Cow::Borrowed(key),
+        })
+        .await
+    }
+
+    async fn get_all_post_meta(
+        &self,
+        post_slug: &str,
+    ) -> Result<BTreeMap<String, String>, Self::Error> {
+        self.execute_request(&Request::GetAllPostMeta {
+            post_slug: Cow::Borrowed(post_slug),
+        })
+        .await
+    }
+
     async fn insert_resource(&self, resource: &Resource) -> Result<(), Self::Error> {
         self.execute_request(&Request::InsertResource {
             resource: Cow::Borrowed(resource),
@@ -238,9 +570,49 @@ where
         .await
     }
 
-    async fn get_resource(&self, resource_id: &Uuid) -> Result<Option<Resource>, Self::Error> {
+    async fn delete_post_resources(&self, post_slug: &str) -> Result<usize, Self::Error> {
+        self.execute_request(&Request::DeletePostResources {
+            post_slug: Cow::Borrowed(post_slug),
+        })
+        .await
+    }
+
+    async fn get_resource(
+        &self,
+        resource_id: &Uuid,
+        public_only: bool,
+    ) -> Result<Option<Resource>, Self::Error> {
         self.execute_request(&Request::GetResource {
             resource_id: *resource_id,
+            public_only,
+        })
+        .await
+    }
+
+    async fn count_post_resources(&self, post_slug: &str) -> Result<u64, Self::Error> {
+        self.execute_request(&Request::CountPostResources {
+            post_slug: Cow::Borrowed(post_slug),
+        })
+        .await
+    }
+
+    async fn get_private_resource_ids(&self, post_slug: &str) -> Result<Vec<Uuid>, Self::Error> {
+        self.execute_request(&Request::GetPrivateResourceIds {
+            post_slug: Cow::Borrowed(post_slug),
+        })
+        .await
+    }
+
+    async fn set_resource_public(
+        &self,
+        post_slug: &str,
+        resource_id: &Uuid,
+        is_public: bool,
+    ) -> Result<bool, Self::Error> {
+        self.execute_request(&Request::SetResourcePublic {
+            post_slug: Cow::Borrowed(post_slug),
+            resource_id: *resource_id,
+            is_public,
         })
         .await
     }
@@ -264,6 +636,10 @@ where
         })
         .await
     }
+
+    async fn get_schema_info(&self) -> Result<Vec<(u32, i64)>, Self::Error> {
+        self.execute_request(&Request::GetSchemaInfo).await
+    }
 }
 
 /// Error type of the remote storage.
@@ -300,9 +676,19 @@ enum Request<'a> {
         post: Cow<'a, Post>,
         post_resources: Cow<'a, [Resource]>,
     },
+    UpsertPost {
+        post: Cow<'a, Post>,
+        post_resources: Cow<'a, [Resource]>,
+    },
+    TouchPost {
+        post_slug: Cow<'a, str>,
+    },
     DeletePost {
         post_slug: Cow<'a, str>,
     },
+    ValidatePost {
+        post: Cow<'a, Post>,
+    },
     GetPost {
         post_slug: Cow<'a, str>,
     },
@@ -313,14 +699,92 @@ enum Request<'a> {
         special: bool,
         pagination: Cow<'a, Pagination>,
     },
+    GetPostsOfKind {
+        special: bool,
+        kind: PostKind,
+        pagination: Cow<'a, Pagination>,
+    },
+    GetUncategorizedPosts {
+        pagination: Cow<'a, Pagination>,
+    },
+    GetLongformPosts {
+        min_words: usize,
+        pagination: Cow<'a, Pagination>,
+    },
+    GetPostsByTags {
+        tags: Cow<'a, [String]>,
+        mode: TagMatch,
+        pagination: Cow<'a, Pagination>,
+    },
+    SuggestTags {
+        selected: Cow<'a, [String]>,
+        limit: usize,
+    },
+    GetArchiveIndex {
+        special: bool,
+        time_zone: TimeZone,
+    },
+    GetArchivePosts {
+        special: bool,
+        year: i32,
+        month: u8,
+        time_zone: TimeZone,
+        pagination: Cow<'a, Pagination>,
+    },
+    GetPostSlugs,
+    GetPostsWithSimilarTitle {
+        title: Cow<'a, str>,
+        max_distance: usize,
+    },
+    GetSimilarSlugs {
+        slug: Cow<'a, str>,
+        limit: usize,
+    },
+    GetLatestPost {
+        special: bool,
+    },
+    GetOldestPost {
+        special: bool,
+    },
+    GetPostsBySlugs {
+        post_slugs: Cow<'a, [String]>,
+        with_content: bool,
+    },
+    SetPostMeta {
+        post_slug: Cow<'a, str>,
+        key: Cow<'a, str>,
+        value: Cow<'a, str>,
+    },
+    GetPostMeta {
+        post_slug: Cow<'a, str>,
+        key: Cow<'a, str>,
+    },
+    GetAllPostMeta {
+        post_slug: Cow<'a, str>,
+    },
     InsertResource {
         resource: Cow<'a, Resource>,
     },
     DeleteResource {
         resource_id: Uuid,
     },
+    DeletePostResources {
+        post_slug: Cow<'a, str>,
+    },
     GetResource {
         resource_id: Uuid,
+        public_only: bool,
+    },
+    CountPostResources {
+        post_slug: Cow<'a, str>,
+    },
+    GetPrivateResourceIds {
+        post_slug: Cow<'a, str>,
+    },
+    SetResourcePublic {
+        post_slug: Cow<'a, str>,
+        resource_id: Uuid,
+        is_public: bool,
     },
     GetResources,
     GetCommitsSince {
@@ -330,6 +794,7 @@ enum Request<'a> {
     ApplyDelta {
         delta: Cow<'a, Delta>,
     },
+    GetSchemaInfo,
 }
 
 #[derive(Debug)]
@@ -0,0 +1,76 @@
+use std::fs::File;
+use std::io::Write;
+use std::path::PathBuf;
+
+use rusqlite::Connection;
+use uuid::Uuid;
+
+use crate::storage::sqlite::SqliteStorageError;
+
+/// A consistent copy of a database in a temporary file, which is removed when dropped.
+pub(super) struct BackupFile(PathBuf);
+
+impl BackupFile {
+    fn new() -> Self {
+        let file_name = format!("ublog-backup-{}.db", Uuid::new_v4());
+        Self(std::env::temp_dir().join(file_name))
+    }
+
+    /// Stream the backup into the given writer, and return the number of bytes written.
+    pub(super) fn write_to<W>(&self, writer: &mut W) -> Result<u64, SqliteStorageError>
+    where
+        W: Write + ?Sized,
+    {
+        let mut file = File::open(&self.0)?;
+        let size = std::io::copy(&mut file, writer)?;
+        writer.flush()?;
+
+        Ok(size)
+    }
+}
+
+impl Drop for BackupFile {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.0);
+    }
+}
+
+/// Copy the database behind the given connection into a temporary file with `VACUUM INTO`.
+///
+/// The copy is a complete sqlite database file that reflects a single point in time.
+pub(super) fn create_backup_file(conn: &Connection) -> Result<BackupFile, SqliteStorageError> {
+    let backup_file = BackupFile::new();
+    conn.execute("VACUUM INTO ?;", (backup_file.0.to_string_lossy(),))?;
+    Ok(backup_file)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::clock::SystemClock;
+    use crate::fixtures::PostBuilder;
+
+    use super::*;
+
+    #[test]
+    fn test_backup_and_restore() {
+        let conn = Connection::open_in_memory().unwrap();
+        crate::storage::sqlite::init_db_schema(&conn, &SystemClock).unwrap();
+        for i in 0..3 {
+            let post = PostBuilder::new(format!("slug{}", i)).tags(["tag"]).build();
+            crate::storage::sqlite::post::insert_post(&conn, &post, &[]).unwrap();
+        }
+
+        let backup_file = create_backup_file(&conn).unwrap();
+        let mut backup = Vec::new();
+        let size = backup_file.write_to(&mut backup).unwrap();
+        assert_eq!(size, backup.len() as u64);
+        assert!(backup.starts_with(b"SQLite format 3\0"));
+
+        drop(backup_file);
+        let restored_file = BackupFile::new();
+        std::fs::write(&restored_file.0, &backup).unwrap();
+        let restored_conn = Connection::open(&restored_file.0).unwrap();
+        let slugs = crate::storage::sqlite::post::get_post_slugs(&restored_conn).unwrap();
+        assert_eq!(slugs, ["slug0", "slug1", "slug2"]);
+    }
+}
@@ -0,0 +1,174 @@
+use std::path::Path;
+
+use rusqlite::{Connection, ErrorCode};
+
+use crate::storage::sqlite::SqliteStorageError;
+
+/// Keys of databases encrypted by SQLCipher.
+#[derive(Clone)]
+pub enum SqliteKey {
+    /// A passphrase from which SQLCipher derives the encryption key with PBKDF2.
+    Passphrase(String),
+
+    /// A 256-bit encryption key, used as is without key derivation.
+    Raw([u8; 32]),
+}
+
+impl SqliteKey {
+    fn as_pragma_value(&self) -> String {
+        match self {
+            Self::Passphrase(passphrase) => passphrase.clone(),
+            Self::Raw(key) => {
+                let hex: String = key.iter().map(|b| format!("{:02X}", b)).collect();
+                format!("x'{}'", hex)
+            }
+        }
+    }
+}
+
+impl std::fmt::Debug for SqliteKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // Keys are never written to logs.
+        match self {
+            Self::Passphrase(_) => f.write_str("Passphrase(..)"),
+            Self::Raw(_) => f.write_str("Raw(..)"),
+        }
+    }
+}
+
+/// Set the key of the database behind the given connection and check that the key decrypts the database.
+///
+/// This must be done before anything else is done on the connection.
+pub(super) fn apply_key(conn: &Connection, key: &SqliteKey) -> Result<(), SqliteStorageError> {
+    conn.pragma_update(None, "key", key.as_pragma_value())?;
+
+    // SQLCipher only decrypts the database when it is first read, so read it right away to check the key.
+    conn.query_row("SELECT count(*) FROM sqlite_master;", (), |_| Ok(()))
+        .map_err(|err| match err {
+            rusqlite::Error::SqliteFailure(ref ffi_err, _)
+                if ffi_err.code == ErrorCode::NotADatabase =>
+            {
+                SqliteStorageError::WrongKey
+            }
+            _ => SqliteStorageError::from(err),
+        })?;
+
+    Ok(())
+}
+
+/// Change the key of the database behind the given connection.
+pub(super) fn rekey(conn: &Connection, new_key: &SqliteKey) -> Result<(), SqliteStorageError> {
+    conn.pragma_update(None, "rekey", new_key.as_pragma_value())?;
+    Ok(())
+}
+
+/// Export the plaintext database behind the given connection into a new database file encrypted with the given key.
+pub(super) fn export_encrypted<P>(
+    conn: &Connection,
+    path: P,
+    key: &SqliteKey,
+) -> Result<(), SqliteStorageError>
+where
+    P: AsRef<Path>,
+{
+    let path = path.as_ref().to_string_lossy();
+    conn.execute(
+        "ATTACH DATABASE ? AS encrypted KEY ?;",
+        (&*path, key.as_pragma_value()),
+    )?;
+
+    let export_result = conn.query_row("SELECT sqlcipher_export('encrypted');", (), |_| Ok(()));
+    conn.execute("DETACH DATABASE encrypted;", ())?;
+    export_result?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fixtures::TempDatabaseFile;
+    use crate::storage::sqlite::{SqliteExt, SqliteOpenOptions, SqliteStorage};
+
+    use super::*;
+
+    fn open_with_key(path: &Path, key: SqliteKey) -> Result<SqliteStorage, SqliteStorageError> {
+        SqliteStorage::new_file_with_options(path, SqliteOpenOptions::new().key(key))
+    }
+
+    fn count_resources(storage: &SqliteStorage) -> usize {
+        storage
+            .lock()
+            .query_one("SELECT count(*) FROM resources;", (), |row| {
+                row.get(0).map_err(From::from)
+            })
+            .unwrap()
+            .unwrap()
+    }
+
+    fn insert_resource(storage: &SqliteStorage) {
+        storage
+            .lock()
+            .execute(
+                "INSERT INTO resources (id, name, ty, data) VALUES ('id', 'res', 'image/png', x'00');",
+                (),
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn test_open_and_rekey() {
+        let db_file = TempDatabaseFile::new();
+
+        let storage =
+            open_with_key(db_file.path(), SqliteKey::Passphrase(String::from("key1"))).unwrap();
+        insert_resource(&storage);
+        storage.rekey(&SqliteKey::Raw([7; 32])).unwrap();
+        drop(storage);
+
+        let err =
+            open_with_key(db_file.path(), SqliteKey::Passphrase(String::from("key1"))).unwrap_err();
+        assert!(matches!(err, SqliteStorageError::WrongKey));
+
+        let storage = open_with_key(db_file.path(), SqliteKey::Raw([7; 32])).unwrap();
+        assert_eq!(count_resources(&storage), 1);
+    }
+
+    #[test]
+    fn test_open_wrong_key() {
+        let db_file = TempDatabaseFile::new();
+
+        let storage =
+            open_with_key(db_file.path(), SqliteKey::Passphrase(String::from("key"))).unwrap();
+        drop(storage);
+
+        let err = open_with_key(db_file.path(), SqliteKey::Passphrase(String::from("wrong")))
+            .unwrap_err();
+        assert!(matches!(err, SqliteStorageError::WrongKey));
+    }
+
+    #[test]
+    fn test_encrypt_plaintext() {
+        let plain_file = TempDatabaseFile::new();
+        let encrypted_file = TempDatabaseFile::new();
+
+        let storage = SqliteStorage::new_file(plain_file.path()).unwrap();
+        insert_resource(&storage);
+        storage
+            .export_encrypted(
+                encrypted_file.path(),
+                &SqliteKey::Passphrase(String::from("key")),
+            )
+            .unwrap();
+        drop(storage);
+
+        let err = SqliteStorage::new_file(encrypted_file.path()).unwrap_err();
+        assert!(matches!(err, SqliteStorageError::Sqlite(_)));
+
+        let storage = open_with_key(
+            encrypted_file.path(),
+            SqliteKey::Passphrase(String::from("key")),
+        )
+        .unwrap();
+        assert_eq!(count_resources(&storage), 1);
+    }
+}
@@ -23,7 +23,7 @@ pub(crate) fn get_latest_commit(conn: &Connection) -> Result<Option<Commit>, Sql
     const SELECT_SQL: &str = r#"
         SELECT id, timestamp, prev_commit_id, payload
         FROM commits
-        ORDER BY timestamp DESC
+        ORDER BY timestamp DESC, rowid DESC
         LIMIT 1;
     "#;
 
@@ -38,7 +38,7 @@ pub(crate) fn get_commits(
         SELECT id, timestamp, prev_commit_id, payload
         FROM commits
         WHERE timestamp >= ?
-        ORDER BY timestamp ASC;
+        ORDER BY timestamp ASC, rowid ASC;
     "#;
 
     conn.query_many(SELECT_SQL, (since_timestamp,), create_commit_from_row)
@@ -52,6 +52,10 @@ pub(crate) fn insert_commits(
     conn: &Connection,
     commits: &[Commit],
 ) -> Result<(), SqliteStorageError> {
+    if commits.is_empty() {
+        return Ok(());
+    }
+
     let mut serialized_payload_data = Vec::with_capacity(commits.len());
     for c in commits {
         let payload = serialize_commit_payload(&c.payload);
@@ -66,12 +70,10 @@ pub(crate) fn insert_commits(
         commit_sql_params.push(payload_data);
     }
 
-    let insert_sql = format!(
-        r#"
-            INSERT INTO commits (id, timestamp, prev_commit_id, payload)
-            VALUES {};
-        "#,
-        vec!["(?, ?, ?, ?)"; commits.len()].join(","),
+    let insert_sql = crate::storage::sqlite::query::insert_statement(
+        "commits",
+        &["id", "timestamp", "prev_commit_id", "payload"],
+        commits.len(),
     );
     conn.execute(&insert_sql, commit_sql_params.as_slice())?;
 
@@ -0,0 +1,172 @@
+use std::collections::BTreeMap;
+
+use rusqlite::Connection;
+
+use crate::storage::sqlite::{SqliteExt, SqliteStorageError};
+
+pub(crate) fn init_db_schema(conn: &Connection) -> Result<(), SqliteStorageError> {
+    const INIT_SQL: &str = r#"
+        CREATE TABLE IF NOT EXISTS posts_meta (
+            post_slug TEXT NOT NULL REFERENCES posts(slug) ON DELETE CASCADE,
+            key       TEXT NOT NULL,
+            value     TEXT NOT NULL,
+            PRIMARY KEY (post_slug, key)
+        ) WITHOUT ROWID;
+    "#;
+    conn.execute_batch(INIT_SQL)?;
+
+    Ok(())
+}
+
+/// Set the metadata value of the given key on the post with the given slug, replacing any previous value.
+///
+/// Returns `false` without setting anything if there is no post with the given slug.
+pub(super) fn set_post_meta(
+    conn: &Connection,
+    post_slug: &str,
+    key: &str,
+    value: &str,
+) -> Result<bool, SqliteStorageError> {
+    const UPSERT_SQL: &str = r#"
+        INSERT INTO posts_meta (post_slug, key, value)
+        SELECT ?1, ?2, ?3
+        WHERE EXISTS (SELECT 1 FROM posts WHERE slug == ?1)
+        ON CONFLICT (post_slug, key) DO UPDATE SET value = excluded.value;
+    "#;
+
+    let changes = conn.execute(UPSERT_SQL, (post_slug, key, value))?;
+    Ok(changes > 0)
+}
+
+pub(super) fn get_post_meta(
+    conn: &Connection,
+    post_slug: &str,
+    key: &str,
+) -> Result<Option<String>, SqliteStorageError> {
+    const SELECT_SQL: &str = r#"
+        SELECT value
+        FROM posts_meta
+        WHERE post_slug == ? AND key == ?;
+    "#;
+
+    conn.query_one(SELECT_SQL, (post_slug, key), |row| {
+        row.get(0).map_err(From::from)
+    })
+}
+
+pub(super) fn get_all_post_meta(
+    conn: &Connection,
+    post_slug: &str,
+) -> Result<BTreeMap<String, String>, SqliteStorageError> {
+    const SELECT_SQL: &str = r#"
+        SELECT key, value
+        FROM posts_meta
+        WHERE post_slug == ?;
+    "#;
+
+    let meta = conn.query_many(SELECT_SQL, (post_slug,), |row| {
+        Ok((row.get(0)?, row.get(1)?))
+    })?;
+    Ok(meta.into_iter().collect())
+}
+
+pub(super) fn delete_post_meta(
+    conn: &Connection,
+    post_slug: &str,
+) -> Result<(), SqliteStorageError> {
+    const DELETE_SQL: &str = r#"
+        DELETE FROM posts_meta
+        WHERE post_slug == ?;
+    "#;
+
+    conn.execute(DELETE_SQL, (post_slug,))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::clock::SystemClock;
+    use crate::fixtures::PostBuilder;
+
+    use super::*;
+
+    fn init_db_connection() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        crate::storage::sqlite::init_db_schema(&conn, &SystemClock).unwrap();
+
+        let post = PostBuilder::new("slug").build();
+        crate::storage::sqlite::post::insert_post(&conn, &post, &[]).unwrap();
+
+        conn
+    }
+
+    #[test]
+    fn test_set_upsert() {
+        let conn = init_db_connection();
+
+        assert!(set_post_meta(&conn, "slug", "key", "value1").unwrap());
+        assert!(set_post_meta(&conn, "slug", "key", "value2").unwrap());
+
+        let value = get_post_meta(&conn, "slug", "key").unwrap();
+        assert_eq!(value.as_deref(), Some("value2"));
+
+        let count: usize = conn
+            .query_one("SELECT count(*) FROM posts_meta;", (), |row| {
+                row.get(0).map_err(From::from)
+            })
+            .unwrap()
+            .unwrap();
+        assert_eq!(count, 1);
+    }
+
+    #[test]
+    fn test_set_post_not_exist() {
+        let conn = init_db_connection();
+
+        assert!(!set_post_meta(&conn, "missing", "key", "value").unwrap());
+        assert!(get_post_meta(&conn, "missing", "key").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_select_all() {
+        let conn = init_db_connection();
+
+        set_post_meta(&conn, "slug", "og:image", "https://example.com/a.png").unwrap();
+        set_post_meta(&conn, "slug", "css", "body {}").unwrap();
+
+        let meta = get_all_post_meta(&conn, "slug").unwrap();
+        let meta: Vec<_> = meta
+            .iter()
+            .map(|(key, value)| (key.as_str(), value.as_str()))
+            .collect();
+        assert_eq!(
+            meta,
+            [
+                ("css", "body {}"),
+                ("og:image", "https://example.com/a.png")
+            ]
+        );
+
+        assert!(get_all_post_meta(&conn, "missing").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_delete() {
+        let conn = init_db_connection();
+
+        set_post_meta(&conn, "slug", "key", "value").unwrap();
+        delete_post_meta(&conn, "slug").unwrap();
+
+        assert!(get_post_meta(&conn, "slug", "key").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_delete_with_post() {
+        let conn = init_db_connection();
+
+        set_post_meta(&conn, "slug", "key", "value").unwrap();
+        crate::storage::sqlite::post::delete_post(&conn, "slug").unwrap();
+
+        assert!(get_all_post_meta(&conn, "slug").unwrap().is_empty());
+    }
+}
@@ -0,0 +1,231 @@
+use rusqlite::Connection;
+use ublog_doc::DocumentNode;
+
+use crate::clock::Clock;
+use crate::storage::sqlite::{SqliteExt, SqliteStorageError};
+
+type Migration = fn(&Connection) -> Result<(), SqliteStorageError>;
+
+/// Migrations of the database schema, in the order in which they are applied.
+///
+/// The version of a migration is its 1-based index within this list. Migrations are never removed or reordered once
+/// released; schema changes are made by appending new migrations.
+const MIGRATIONS: &[Migration] = &[
+    migrate_initial_schema,
+    migrate_posts_meta,
+    migrate_resources_visibility,
+    migrate_post_kinds,
+    migrate_posts_canonical_url,
+    migrate_posts_word_count,
+    migrate_posts_source,
+];
+
+fn migrate_initial_schema(conn: &Connection) -> Result<(), SqliteStorageError> {
+    crate::storage::sqlite::commit::init_db_schema(conn)?;
+    crate::storage::sqlite::post::init_db_schema(conn)?;
+    crate::storage::sqlite::resource::init_db_schema(conn)?;
+    Ok(())
+}
+
+fn migrate_posts_meta(conn: &Connection) -> Result<(), SqliteStorageError> {
+    crate::storage::sqlite::meta::init_db_schema(conn)
+}
+
+fn migrate_resources_visibility(conn: &Connection) -> Result<(), SqliteStorageError> {
+    const MIGRATE_SQL: &str = r#"
+        ALTER TABLE posts_resources ADD COLUMN is_public INTEGER NOT NULL DEFAULT 1;
+    "#;
+    conn.execute_batch(MIGRATE_SQL)?;
+
+    Ok(())
+}
+
+fn migrate_post_kinds(conn: &Connection) -> Result<(), SqliteStorageError> {
+    const MIGRATE_SQL: &str = r#"
+        ALTER TABLE posts ADD COLUMN kind     INTEGER NOT NULL DEFAULT 0;
+        ALTER TABLE posts ADD COLUMN link_url TEXT;
+    "#;
+    conn.execute_batch(MIGRATE_SQL)?;
+
+    Ok(())
+}
+
+fn migrate_posts_canonical_url(conn: &Connection) -> Result<(), SqliteStorageError> {
+    const MIGRATE_SQL: &str = r#"
+        ALTER TABLE posts ADD COLUMN canonical_url TEXT;
+    "#;
+    conn.execute_batch(MIGRATE_SQL)?;
+
+    Ok(())
+}
+
+fn migrate_posts_word_count(conn: &Connection) -> Result<(), SqliteStorageError> {
+    const MIGRATE_SQL: &str = r#"
+        ALTER TABLE posts ADD COLUMN word_count INTEGER NOT NULL DEFAULT 0;
+        CREATE INDEX IF NOT EXISTS posts_idx_word_count ON posts (word_count);
+    "#;
+
+    const SELECT_SQL: &str = r#"
+        SELECT slug, content FROM posts;
+    "#;
+
+    const UPDATE_SQL: &str = r#"
+        UPDATE posts
+        SET word_count = ?
+        WHERE slug == ?;
+    "#;
+
+    conn.execute_batch(MIGRATE_SQL)?;
+
+    // Count the words of the existing posts.
+    let posts: Vec<(String, Vec<u8>)> =
+        conn.query_many(SELECT_SQL, (), |row| Ok((row.get(0)?, row.get(1)?)))?;
+    for (slug, content_data) in posts {
+        let content: DocumentNode = bson::from_slice(&content_data)?;
+        conn.execute(UPDATE_SQL, (content.word_count() as i64, &slug))?;
+    }
+
+    Ok(())
+}
+
+fn migrate_posts_source(conn: &Connection) -> Result<(), SqliteStorageError> {
+    const MIGRATE_SQL: &str = r#"
+        ALTER TABLE posts ADD COLUMN source TEXT;
+        ALTER TABLE posts ADD COLUMN source_version INTEGER;
+    "#;
+    conn.execute_batch(MIGRATE_SQL)?;
+
+    Ok(())
+}
+
+/// Apply every migration that has not been applied to the database yet.
+///
+/// Each migration runs in its own transaction together with the record of its application, which is timestamped with
+/// the given clock.
+pub(super) fn run_migrations(
+    conn: &Connection,
+    clock: &dyn Clock,
+) -> Result<(), SqliteStorageError> {
+    const INIT_SQL: &str = r#"
+        CREATE TABLE IF NOT EXISTS schema_migrations (
+            version    INTEGER NOT NULL PRIMARY KEY,
+            applied_ts INTEGER NOT NULL
+        );
+    "#;
+
+    const INSERT_SQL: &str = r#"
+        INSERT INTO schema_migrations (version, applied_ts)
+        VALUES (?, ?);
+    "#;
+
+    conn.execute_batch(INIT_SQL)?;
+
+    let applied_versions: Vec<u32> = get_schema_info(conn)?
+        .into_iter()
+        .map(|(version, _)| version)
+        .collect();
+
+    for (idx, migration) in MIGRATIONS.iter().enumerate() {
+        let version = idx as u32 + 1;
+        if applied_versions.contains(&version) {
+            continue;
+        }
+
+        let trans = conn.unchecked_transaction()?;
+        migration(&trans)?;
+        trans.execute(INSERT_SQL, (version, clock.now_unix_timestamp()))?;
+        trans.commit()?;
+    }
+
+    Ok(())
+}
+
+/// Get the versions of the migrations applied to the database, together with the Unix timestamps at which they are
+/// applied, from the earliest version to the latest one.
+pub(super) fn get_schema_info(conn: &Connection) -> Result<Vec<(u32, i64)>, SqliteStorageError> {
+    const SELECT_SQL: &str = r#"
+        SELECT version, applied_ts
+        FROM schema_migrations
+        ORDER BY version ASC;
+    "#;
+
+    conn.query_many(SELECT_SQL, (), |row| Ok((row.get(0)?, row.get(1)?)))
+}
+
+#[cfg(test)]
+mod tests {
+    use ublog_doc::DocumentNodeTag;
+
+    use crate::clock::FixedClock;
+
+    use super::*;
+
+    #[test]
+    fn test_run_migrations() {
+        let conn = Connection::open_in_memory().unwrap();
+        run_migrations(&conn, &FixedClock::new(1000)).unwrap();
+
+        let info = get_schema_info(&conn).unwrap();
+        let expected: Vec<_> = (1..=MIGRATIONS.len() as u32)
+            .map(|version| (version, 1000))
+            .collect();
+        assert_eq!(info, expected);
+    }
+
+    #[test]
+    fn test_run_migrations_idempotent() {
+        let conn = Connection::open_in_memory().unwrap();
+        run_migrations(&conn, &FixedClock::new(1000)).unwrap();
+        run_migrations(&conn, &FixedClock::new(2000)).unwrap();
+
+        let info = get_schema_info(&conn).unwrap();
+        assert_eq!(info.len(), MIGRATIONS.len());
+        assert!(info.iter().all(|(_, applied_ts)| *applied_ts == 1000));
+    }
+
+    #[test]
+    fn test_run_migrations_pending() {
+        let conn = Connection::open_in_memory().unwrap();
+        run_migrations(&conn, &FixedClock::new(1000)).unwrap();
+        conn.execute("DELETE FROM schema_migrations WHERE version == 2;", ())
+            .unwrap();
+
+        run_migrations(&conn, &FixedClock::new(2000)).unwrap();
+        let info = get_schema_info(&conn).unwrap();
+        assert_eq!(&info[..2], [(1, 1000), (2, 2000)]);
+    }
+
+    #[test]
+    fn test_migrate_posts_word_count() {
+        const INSERT_SQL: &str = r#"
+            INSERT INTO posts (title, slug, author, create_timestamp, update_timestamp, category, is_special, content)
+            VALUES ('title', 'slug', 'msr', 0, 0, '', 0, ?);
+        "#;
+
+        // Bring the database to the version just before word counts are added, and add a post to it.
+        let conn = Connection::open_in_memory().unwrap();
+        let word_count_version = 6;
+        for migration in &MIGRATIONS[..word_count_version - 1] {
+            migration(&conn).unwrap();
+        }
+
+        let mut content = DocumentNode::new_empty();
+        content
+            .children
+            .push(DocumentNode::new(DocumentNodeTag::InlineText {
+                text: String::from("one two three"),
+            }));
+        conn.execute(INSERT_SQL, (bson::to_vec(&content).unwrap(),))
+            .unwrap();
+
+        migrate_posts_word_count(&conn).unwrap();
+        let word_count: i64 = conn
+            .query_row(
+                "SELECT word_count FROM posts WHERE slug == 'slug';",
+                (),
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(word_count, 3);
+    }
+}
@@ -1,9 +1,30 @@
+mod backup;
+#[cfg(feature = "sqlcipher")]
+mod cipher;
 mod commit;
+mod meta;
+mod migration;
+mod options;
 mod post;
+mod query;
+mod report;
 mod resource;
+mod snapshot;
+mod stats;
+mod wal;
 
+#[cfg(feature = "sqlcipher")]
+pub use cipher::SqliteKey;
+pub use options::{SqliteOpenOptions, SqliteSynchronous, SqliteTempStore};
+pub use report::{PostStorageReport, ResourceUsage, StorageReport};
+pub use snapshot::SqliteSnapshot;
+pub use stats::SqliteIoStats;
+pub use wal::{SqliteCheckpoint, SqliteCheckpointMode, SqliteWalStats};
+
+use std::collections::BTreeMap;
 use std::error::Error;
 use std::fmt::{Display, Formatter};
+use std::io::Write;
 use std::path::Path;
 use std::sync::{Mutex, MutexGuard};
 
@@ -11,22 +32,44 @@ use async_trait::async_trait;
 use rusqlite::{Connection, Params, Row};
 use uuid::Uuid;
 
-use crate::models::{Commit, CommitPayload, Delta, Post, Resource};
-use crate::storage::{PaginatedList, Pagination, Storage};
+use crate::clock::{Clock, SystemClock};
+use crate::models::{Commit, CommitPayload, Delta, Post, PostKind, Resource, ValidationError};
+use crate::storage::{
+    ArchiveEntry, PaginatedList, Pagination, PostsBySlugs, SlugSuggestion, Storage, TagMatch,
+};
+use crate::time_zone::TimeZone;
 
 /// Provide sqlite-based storage for databases.
 #[derive(Debug)]
 pub struct SqliteStorage {
     conn: Mutex<Connection>,
+    clock: Box<dyn Clock>,
 }
 
 impl SqliteStorage {
     /// Create a new `SqliteStorage` from the given sqlite connection.
+    ///
+    /// The storage reads the current time from the [`SystemClock`].
     pub fn new(conn: Connection) -> Result<Self, SqliteStorageError> {
-        init_db_schema(&conn)?;
+        Self::new_with_clock(conn, SystemClock)
+    }
+
+    /// Create a new `SqliteStorage` from the given sqlite connection that reads the current time from the given
+    /// clock.
+    ///
+    /// The clock timestamps both the schema migrations applied while the storage is created and the commits created
+    /// by the storage afterwards.
+    pub fn new_with_clock<C>(conn: Connection, clock: C) -> Result<Self, SqliteStorageError>
+    where
+        C: Clock + 'static,
+    {
+        init_db_schema(&conn, &clock)?;
 
         let conn = Mutex::new(conn);
-        Ok(Self { conn })
+        Ok(Self {
+            conn,
+            clock: Box::new(clock),
+        })
     }
 
     /// Create a new sqlite connection to the specified sqlite database file and then create a new `SqliteStorage` from
@@ -34,48 +77,224 @@ impl SqliteStorage {
     pub fn new_file<P>(path: P) -> Result<Self, SqliteStorageError>
     where
         P: AsRef<Path>,
+    {
+        Self::new_file_with_options(path, &SqliteOpenOptions::default())
+    }
+
+    /// Create a new sqlite connection to the specified sqlite database file with the given options and then create a
+    /// new `SqliteStorage` from that sqlite connection.
+    pub fn new_file_with_options<P>(
+        path: P,
+        options: &SqliteOpenOptions,
+    ) -> Result<Self, SqliteStorageError>
+    where
+        P: AsRef<Path>,
+    {
+        Self::new_file_with_clock(path, options, SystemClock)
+    }
+
+    /// Create a new sqlite connection to the specified sqlite database file with the given options and then create a
+    /// new `SqliteStorage` from that sqlite connection that reads the current time from the given clock.
+    pub fn new_file_with_clock<P, C>(
+        path: P,
+        options: &SqliteOpenOptions,
+        clock: C,
+    ) -> Result<Self, SqliteStorageError>
+    where
+        P: AsRef<Path>,
+        C: Clock + 'static,
     {
         let conn = Connection::open(path)?;
-        Self::new(conn)
+        options.apply(&conn)?;
+        Self::new_with_clock(conn, clock)
     }
 
     /// Create a new in-memory sqlite connection and then create a new `SqliteStorage` from that sqlite connection.
     pub fn new_memory() -> Result<Self, SqliteStorageError> {
+        Self::new_memory_with_clock(SystemClock)
+    }
+
+    /// Create a new in-memory sqlite connection and then create a new `SqliteStorage` from that sqlite connection that
+    /// reads the current time from the given clock.
+    pub fn new_memory_with_clock<C>(clock: C) -> Result<Self, SqliteStorageError>
+    where
+        C: Clock + 'static,
+    {
         let conn = Connection::open_in_memory()?;
-        Self::new(conn)
+        Self::new_with_clock(conn, clock)
+    }
+
+    /// Prepare the statements of the most frequently run queries, such as selecting a post or a resource, into the
+    /// statement cache of the underlying connection.
+    ///
+    /// This is best run right after the storage is created, so that the first requests do not pay for compiling these
+    /// statements.
+    pub fn warm_statement_cache(&self) -> Result<(), SqliteStorageError> {
+        let conn = self.lock();
+        warm_statement_cache(&conn)
+    }
+
+    /// Run sqlite's `PRAGMA optimize` on the underlying connection.
+    ///
+    /// This is best run right before the storage is closed.
+    pub fn optimize(&self) -> Result<(), SqliteStorageError> {
+        self.lock().execute_batch("PRAGMA optimize;")?;
+        Ok(())
+    }
+
+    /// Change the key of the encrypted database.
+    ///
+    /// The database must have been opened with its current key through [`SqliteOpenOptions::key`].
+    #[cfg(feature = "sqlcipher")]
+    pub fn rekey(&self, new_key: &SqliteKey) -> Result<(), SqliteStorageError> {
+        let conn = self.lock();
+        crate::storage::sqlite::cipher::rekey(&*conn, new_key)
+    }
+
+    /// Export the plaintext database into a new database file at the given path, encrypted with the given key.
+    ///
+    /// This is how an existing plaintext database is converted into an encrypted one; the plaintext database is left
+    /// untouched.
+    #[cfg(feature = "sqlcipher")]
+    pub fn export_encrypted<P>(&self, path: P, key: &SqliteKey) -> Result<(), SqliteStorageError>
+    where
+        P: AsRef<Path>,
+    {
+        let conn = self.lock();
+        crate::storage::sqlite::cipher::export_encrypted(&*conn, path, key)
+    }
+
+    /// Get the counters of the I/O performed through the underlying connection, such as page cache hits and misses.
+    pub fn io_stats(&self) -> Result<SqliteIoStats, SqliteStorageError> {
+        let conn = self.lock();
+        crate::storage::sqlite::stats::get_io_stats(&conn)
+    }
+
+    /// Get the statistics of the write-ahead log of the database, such as the size of the log file.
+    pub fn wal_stats(&self) -> Result<SqliteWalStats, SqliteStorageError> {
+        let conn = self.lock();
+        crate::storage::sqlite::wal::get_wal_stats(&conn)
+    }
+
+    /// Checkpoint the write-ahead log of the database in the given mode.
+    ///
+    /// Returns `None` if the database is not in WAL mode. A long-running read transaction on another connection keeps
+    /// the log from being checkpointed past the point it reads from, in which case the result is reported as busy.
+    pub fn checkpoint(
+        &self,
+        mode: SqliteCheckpointMode,
+    ) -> Result<Option<SqliteCheckpoint>, SqliteStorageError> {
+        let conn = self.lock();
+        crate::storage::sqlite::wal::checkpoint(&conn, mode)
+    }
+
+    /// Write a consistent copy of the database into the given writer, such as a network stream, and return the number
+    /// of bytes written.
+    ///
+    /// The copy is a complete sqlite database file, which is restored by writing it to a file and opening that file
+    /// with [`SqliteStorage::new_file`]. The copy is first made into a temporary file, so the temporary directory must
+    /// have room for it. The storage is locked only while the copy is made.
+    pub fn backup_to_writer<W>(&self, writer: &mut W) -> Result<u64, SqliteStorageError>
+    where
+        W: Write + ?Sized,
+    {
+        let backup_file = {
+            let conn = self.lock();
+            crate::storage::sqlite::backup::create_backup_file(&conn)?
+        };
+        backup_file.write_to(writer)
+    }
+
+    /// Get a report of the space taken by the database and by the resources of each post.
+    ///
+    /// Only the sizes of resources are read; resource data is never loaded.
+    pub fn storage_report(&self) -> Result<StorageReport, SqliteStorageError> {
+        let conn = self.lock();
+        crate::storage::sqlite::report::get_storage_report(&conn)
+    }
+
+    /// Find the post-resource relations that refer to posts that do not exist.
+    ///
+    /// Returns the slug of the missing post and the ID of the resource for each such relation.
+    pub fn find_orphaned_resources(&self) -> Result<Vec<(String, Uuid)>, SqliteStorageError> {
+        let conn = self.lock();
+        crate::storage::sqlite::post::get_orphaned_post_resources(&conn)
+    }
+
+    /// Delete the post-resource relations that refer to posts that do not exist, together with the resources that are
+    /// no longer attached to any post afterwards.
+    ///
+    /// Returns the number of deleted resources.
+    pub fn delete_orphaned_resources(&self) -> Result<usize, SqliteStorageError> {
+        self.transact_and_commit_with(|conn| {
+            let res_ids = crate::storage::sqlite::post::delete_orphaned_post_resources(conn)?;
+            let commit_payloads: Vec<_> = res_ids
+                .iter()
+                .map(|id| CommitPayload::delete_resource(*id))
+                .collect();
+            Ok((res_ids.len(), commit_payloads))
+        })
+    }
+
+    /// Run several reads against a single consistent snapshot of the database.
+    ///
+    /// `read` is given a [`SqliteSnapshot`] through which it can make any number of reads; all of them observe the
+    /// database as it was when `read_snapshot` was called. The snapshot only lives as long as the call to `read` and
+    /// cannot be kept beyond it.
+    ///
+    /// The storage is locked while `read` runs, so other operations on this `SqliteStorage` wait until it returns.
+    /// Writes from other connections to the same database file proceed concurrently if the database is in WAL mode,
+    /// and are blocked until the snapshot ends otherwise.
+    pub fn read_snapshot<F, R>(&self, read: F) -> Result<R, SqliteStorageError>
+    where
+        F: FnOnce(&SqliteSnapshot) -> Result<R, SqliteStorageError>,
+    {
+        let conn = self.lock();
+        crate::storage::sqlite::snapshot::read_snapshot(&conn, read)
     }
 
-    fn lock(&self) -> MutexGuard<Connection> {
+    fn lock(&self) -> MutexGuard<'_, Connection> {
         self.conn.lock().unwrap()
     }
 
-    fn transact_and_commit<T, F>(
+    fn transact_and_commit<T, F, R>(
         &self,
         commit_payloads: T,
         transact: F,
-    ) -> Result<(), SqliteStorageError>
+    ) -> Result<R, SqliteStorageError>
     where
         T: IntoIterator<Item = CommitPayload>,
-        F: FnOnce(&Connection) -> Result<(), SqliteStorageError>,
+        F: FnOnce(&Connection) -> Result<R, SqliteStorageError>,
+    {
+        self.transact_and_commit_with(|conn| Ok((transact(conn)?, commit_payloads)))
+    }
+
+    /// Like `transact_and_commit`, but the commit payloads are produced by the transaction itself, for mutations
+    /// whose affected objects are only known once the transaction is running.
+    fn transact_and_commit_with<T, F, R>(&self, transact: F) -> Result<R, SqliteStorageError>
+    where
+        T: IntoIterator<Item = CommitPayload>,
+        F: FnOnce(&Connection) -> Result<(R, T), SqliteStorageError>,
     {
         let mut conn = self.lock();
         let trans = conn.transaction()?;
 
-        let last_commit = crate::storage::sqlite::commit::get_latest_commit(&*trans)?;
+        let last_commit = crate::storage::sqlite::commit::get_latest_commit(&trans)?;
         let mut last_commit_id = last_commit.map(|commit| commit.id).unwrap_or_default();
 
-        transact(&*trans)?;
+        let (ret, commit_payloads) = transact(&trans)?;
 
+        let timestamp = self.clock.now_unix_timestamp();
         for payload in commit_payloads {
-            let commit = Commit::new(last_commit_id, payload);
+            let commit = Commit::with_timestamp(last_commit_id, timestamp, payload);
             last_commit_id = commit.id.clone();
 
-            crate::storage::sqlite::commit::insert_commit(&*trans, &commit)?;
+            crate::storage::sqlite::commit::insert_commit(&trans, &commit)?;
         }
 
         trans.commit()?;
 
-        Ok(())
+        Ok(ret)
     }
 }
 
@@ -88,6 +307,8 @@ impl Storage for SqliteStorage {
         post: &Post,
         post_resources: &[Resource],
     ) -> Result<(), Self::Error> {
+        check_post(post)?;
+
         let commit_payload = CommitPayload::create_post(post.slug.clone());
         self.transact_and_commit([commit_payload], |conn| {
             crate::storage::sqlite::post::insert_post(conn, post, post_resources)
@@ -99,27 +320,78 @@ impl Storage for SqliteStorage {
         post: &Post,
         post_resources: &[Resource],
     ) -> Result<(), Self::Error> {
+        check_post(post)?;
+
         let commit_payloads = [
             CommitPayload::delete_post(post.slug.clone()),
             CommitPayload::create_post(post.slug.clone()),
         ];
         self.transact_and_commit(commit_payloads, |conn| {
+            // Resources that stay attached to the post keep their visibility, and the post keeps its metadata.
+            let private_resource_ids =
+                crate::storage::sqlite::resource::get_private_resource_ids(conn, &post.slug)?;
+            let post_meta = crate::storage::sqlite::meta::get_all_post_meta(conn, &post.slug)?;
             crate::storage::sqlite::post::delete_post(conn, &post.slug)?;
             crate::storage::sqlite::post::insert_post(conn, post, post_resources)?;
+            for id in &private_resource_ids {
+                crate::storage::sqlite::resource::set_resource_public(conn, &post.slug, id, false)?;
+            }
+            for (key, value) in &post_meta {
+                crate::storage::sqlite::meta::set_post_meta(conn, &post.slug, key, value)?;
+            }
             Ok(())
         })
     }
 
-    async fn delete_post(&self, post_slug: &str) -> Result<(), Self::Error> {
+    async fn upsert_post(
+        &self,
+        post: &Post,
+        post_resources: &[Resource],
+    ) -> Result<bool, Self::Error> {
+        check_post(post)?;
+
+        self.transact_and_commit_with(|conn| {
+            let inserted = crate::storage::sqlite::post::upsert_post(conn, post, post_resources)?;
+            let mut commit_payloads = Vec::with_capacity(2);
+            if !inserted {
+                commit_payloads.push(CommitPayload::delete_post(post.slug.clone()));
+            }
+            commit_payloads.push(CommitPayload::create_post(post.slug.clone()));
+            Ok((inserted, commit_payloads))
+        })
+    }
+
+    async fn touch_post(&self, post_slug: &str) -> Result<Option<i64>, Self::Error> {
+        let timestamp = self.clock.now_unix_timestamp();
+        self.transact_and_commit_with(|conn| {
+            if !crate::storage::sqlite::post::touch_post(conn, post_slug, timestamp)? {
+                return Ok((None, Vec::new()));
+            }
+
+            // Replicas pick up the new timestamp by adding the post again.
+            let commit_payloads = vec![
+                CommitPayload::delete_post(post_slug),
+                CommitPayload::create_post(post_slug),
+            ];
+            Ok((Some(timestamp), commit_payloads))
+        })
+    }
+
+    async fn delete_post(&self, post_slug: &str) -> Result<Option<Post>, Self::Error> {
         let commit_payload = CommitPayload::delete_post(post_slug);
         self.transact_and_commit([commit_payload], |conn| {
             crate::storage::sqlite::post::delete_post(conn, post_slug)
         })
     }
 
+    async fn validate_post(&self, post: &Post) -> Result<Vec<ValidationError>, Self::Error> {
+        let conn = self.lock();
+        crate::storage::sqlite::post::validate_post(&conn, post)
+    }
+
     async fn get_post(&self, post_slug: &str) -> Result<Option<Post>, Self::Error> {
         let conn = self.lock();
-        crate::storage::sqlite::post::get_post(&*conn, post_slug)
+        crate::storage::sqlite::post::get_post(&conn, post_slug)
     }
 
     async fn get_post_with_resources(
@@ -127,7 +399,7 @@ impl Storage for SqliteStorage {
         post_slug: &str,
     ) -> Result<Option<(Post, Vec<Resource>)>, Self::Error> {
         let conn = self.lock();
-        crate::storage::sqlite::post::get_post_with_resources(&*conn, post_slug)
+        crate::storage::sqlite::post::get_post_with_resources(&conn, post_slug)
     }
 
     async fn get_posts(
@@ -136,7 +408,150 @@ impl Storage for SqliteStorage {
         pagination: &Pagination,
     ) -> Result<PaginatedList<Post>, Self::Error> {
         let conn = self.lock();
-        crate::storage::sqlite::post::get_posts(&*conn, special, pagination)
+        crate::storage::sqlite::post::get_posts(&conn, special, pagination)
+    }
+
+    async fn get_posts_of_kind(
+        &self,
+        special: bool,
+        kind: PostKind,
+        pagination: &Pagination,
+    ) -> Result<PaginatedList<Post>, Self::Error> {
+        let conn = self.lock();
+        crate::storage::sqlite::post::get_posts_of_kind(&conn, special, kind, pagination)
+    }
+
+    async fn get_uncategorized_posts(
+        &self,
+        pagination: &Pagination,
+    ) -> Result<PaginatedList<Post>, Self::Error> {
+        let conn = self.lock();
+        crate::storage::sqlite::post::get_uncategorized_posts(&conn, pagination)
+    }
+
+    async fn get_longform_posts(
+        &self,
+        min_words: usize,
+        pagination: &Pagination,
+    ) -> Result<PaginatedList<Post>, Self::Error> {
+        let conn = self.lock();
+        crate::storage::sqlite::post::get_longform_posts(&conn, min_words, pagination)
+    }
+
+    async fn get_posts_by_tags(
+        &self,
+        tags: &[String],
+        mode: TagMatch,
+        pagination: &Pagination,
+    ) -> Result<PaginatedList<Post>, Self::Error> {
+        let conn = self.lock();
+        crate::storage::sqlite::post::get_posts_by_tags(&conn, tags, mode, pagination)
+    }
+
+    async fn suggest_tags(
+        &self,
+        selected: &[String],
+        limit: usize,
+    ) -> Result<Vec<(String, u64)>, Self::Error> {
+        let conn = self.lock();
+        crate::storage::sqlite::post::suggest_tags(&conn, selected, limit)
+    }
+
+    async fn get_archive_index(
+        &self,
+        special: bool,
+        time_zone: TimeZone,
+    ) -> Result<Vec<ArchiveEntry>, Self::Error> {
+        let conn = self.lock();
+        crate::storage::sqlite::post::get_archive_index(&conn, special, time_zone)
+    }
+
+    async fn get_archive_posts(
+        &self,
+        special: bool,
+        year: i32,
+        month: u8,
+        time_zone: TimeZone,
+        pagination: &Pagination,
+    ) -> Result<PaginatedList<Post>, Self::Error> {
+        let conn = self.lock();
+        crate::storage::sqlite::post::get_archive_posts(
+            &conn, special, year, month, time_zone, pagination,
+        )
+    }
+
+    async fn get_post_slugs(&self) -> Result<Vec<String>, Self::Error> {
+        let conn = self.lock();
+        crate::storage::sqlite::post::get_post_slugs(&conn)
+    }
+
+    async fn get_posts_with_similar_title(
+        &self,
+        title: &str,
+        max_distance: usize,
+    ) -> Result<Vec<Post>, Self::Error> {
+        let conn = self.lock();
+        crate::storage::sqlite::post::get_posts_with_similar_title(&conn, title, max_distance)
+    }
+
+    async fn get_similar_slugs(
+        &self,
+        slug: &str,
+        limit: usize,
+    ) -> Result<Vec<SlugSuggestion>, Self::Error> {
+        let conn = self.lock();
+        crate::storage::sqlite::post::get_similar_slugs(&conn, slug, limit)
+    }
+
+    async fn get_latest_post(&self, special: bool) -> Result<Option<Post>, Self::Error> {
+        let conn = self.lock();
+        crate::storage::sqlite::post::get_latest_post(&conn, special)
+    }
+
+    async fn get_oldest_post(&self, special: bool) -> Result<Option<Post>, Self::Error> {
+        let conn = self.lock();
+        crate::storage::sqlite::post::get_oldest_post(&conn, special)
+    }
+
+    async fn get_posts_by_slugs(
+        &self,
+        post_slugs: &[String],
+        with_content: bool,
+    ) -> Result<PostsBySlugs, Self::Error> {
+        let conn = self.lock();
+        crate::storage::sqlite::post::get_posts_by_slugs(&conn, post_slugs, with_content)
+    }
+
+    async fn set_post_meta(
+        &self,
+        post_slug: &str,
+        key: &str,
+        value: &str,
+    ) -> Result<bool, Self::Error> {
+        self.transact_and_commit_with(|conn| {
+            if !crate::storage::sqlite::meta::set_post_meta(conn, post_slug, key, value)? {
+                return Ok((false, Vec::new()));
+            }
+
+            Ok((true, vec![CommitPayload::update_post_meta(post_slug)]))
+        })
+    }
+
+    async fn get_post_meta(
+        &self,
+        post_slug: &str,
+        key: &str,
+    ) -> Result<Option<String>, Self::Error> {
+        let conn = self.lock();
+        crate::storage::sqlite::meta::get_post_meta(&conn, post_slug, key)
+    }
+
+    async fn get_all_post_meta(
+        &self,
+        post_slug: &str,
+    ) -> Result<BTreeMap<String, String>, Self::Error> {
+        let conn = self.lock();
+        crate::storage::sqlite::meta::get_all_post_meta(&conn, post_slug)
     }
 
     async fn insert_resource(&self, resource: &Resource) -> Result<(), Self::Error> {
@@ -153,24 +568,74 @@ impl Storage for SqliteStorage {
         })
     }
 
-    async fn get_resource(&self, resource_id: &Uuid) -> Result<Option<Resource>, Self::Error> {
+    async fn delete_post_resources(&self, post_slug: &str) -> Result<usize, Self::Error> {
+        self.transact_and_commit_with(|conn| {
+            let res_ids = crate::storage::sqlite::post::delete_post_resources(conn, post_slug)?;
+            let commit_payloads: Vec<_> = res_ids
+                .iter()
+                .map(|id| CommitPayload::delete_resource(*id))
+                .collect();
+            Ok((res_ids.len(), commit_payloads))
+        })
+    }
+
+    async fn get_resource(
+        &self,
+        resource_id: &Uuid,
+        public_only: bool,
+    ) -> Result<Option<Resource>, Self::Error> {
+        let conn = self.lock();
+        crate::storage::sqlite::resource::get_resource(&conn, resource_id, public_only)
+    }
+
+    async fn count_post_resources(&self, post_slug: &str) -> Result<u64, Self::Error> {
         let conn = self.lock();
-        crate::storage::sqlite::resource::get_resource(&*conn, resource_id)
+        crate::storage::sqlite::resource::count_post_resources(&conn, post_slug)
+    }
+
+    async fn get_private_resource_ids(&self, post_slug: &str) -> Result<Vec<Uuid>, Self::Error> {
+        let conn = self.lock();
+        crate::storage::sqlite::resource::get_private_resource_ids(&conn, post_slug)
+    }
+
+    async fn set_resource_public(
+        &self,
+        post_slug: &str,
+        resource_id: &Uuid,
+        is_public: bool,
+    ) -> Result<bool, Self::Error> {
+        self.transact_and_commit_with(|conn| {
+            if !crate::storage::sqlite::resource::set_resource_public(
+                conn,
+                post_slug,
+                resource_id,
+                is_public,
+            )? {
+                return Ok((false, Vec::new()));
+            }
+
+            // Replicas pick up the new visibility by adding the post again.
+            let commit_payloads = vec![
+                CommitPayload::delete_post(post_slug),
+                CommitPayload::create_post(post_slug),
+            ];
+            Ok((true, commit_payloads))
+        })
     }
 
     async fn get_resources(&self) -> Result<Vec<Resource>, Self::Error> {
         let conn = self.lock();
-        crate::storage::sqlite::resource::get_resources(&*conn)
+        crate::storage::sqlite::resource::get_resources(&conn)
     }
 
     async fn get_commits_since(&self, since_timestamp: i64) -> Result<Vec<Commit>, Self::Error> {
         let conn = self.lock();
-        crate::storage::sqlite::commit::get_commits(&*conn, since_timestamp)
+        crate::storage::sqlite::commit::get_commits(&conn, since_timestamp)
     }
 
     async fn get_latest_commit(&self) -> Result<Option<Commit>, Self::Error> {
         let conn = self.lock();
-        crate::storage::sqlite::commit::get_latest_commit(&*conn)
+        crate::storage::sqlite::commit::get_latest_commit(&conn)
     }
 
     async fn apply_delta(&self, delta: &Delta) -> Result<(), Self::Error> {
@@ -185,20 +650,37 @@ impl Storage for SqliteStorage {
             crate::storage::sqlite::resource::delete_resource(&trans, id)?;
         }
 
-        for (post, post_resources) in &delta.added_posts {
+        for (post, post_resources, private_resource_ids) in &delta.added_posts {
             crate::storage::sqlite::post::insert_post(&trans, post, post_resources)?;
+            for id in private_resource_ids {
+                crate::storage::sqlite::resource::set_resource_public(
+                    &trans, &post.slug, id, false,
+                )?;
+            }
         }
 
         for resource in &delta.added_resources {
             crate::storage::sqlite::resource::insert_resource(&trans, resource)?;
         }
 
+        for (slug, post_meta) in &delta.post_meta {
+            crate::storage::sqlite::meta::delete_post_meta(&trans, slug)?;
+            for (key, value) in post_meta {
+                crate::storage::sqlite::meta::set_post_meta(&trans, slug, key, value)?;
+            }
+        }
+
         crate::storage::sqlite::commit::insert_commits(&trans, &delta.commits)?;
 
         trans.commit()?;
 
         Ok(())
     }
+
+    async fn get_schema_info(&self) -> Result<Vec<(u32, i64)>, Self::Error> {
+        let conn = self.lock();
+        crate::storage::sqlite::migration::get_schema_info(&conn)
+    }
 }
 
 /// SQlite storage errors.
@@ -207,6 +689,13 @@ pub enum SqliteStorageError {
     Sqlite(rusqlite::Error),
     Bson(bson::de::Error),
     Uuid(uuid::Error),
+    Io(std::io::Error),
+    TooManySlugs(usize),
+    InvalidMonth(i32, u8),
+    PageTooLarge(usize),
+    WrongKey,
+    DuplicateSlug(String),
+    InvalidPost(Vec<ValidationError>),
 }
 
 impl Display for SqliteStorageError {
@@ -215,6 +704,32 @@ impl Display for SqliteStorageError {
             Self::Sqlite(err) => write!(f, "sqlite error: {}", err),
             Self::Bson(err) => write!(f, "bson deserialize error: {}", err),
             Self::Uuid(err) => write!(f, "uuid error: {}", err),
+            Self::Io(err) => write!(f, "io error: {}", err),
+            Self::TooManySlugs(count) => write!(
+                f,
+                "too many slugs: {} (at most {})",
+                count,
+                crate::storage::MAX_SELECT_SLUGS
+            ),
+            Self::InvalidMonth(year, month) => write!(f, "invalid month: {}-{}", year, month),
+            Self::PageTooLarge(page_size) => write!(
+                f,
+                "page too large: {} (at most {})",
+                page_size,
+                crate::storage::MAX_PAGE_SIZE
+            ),
+            Self::WrongKey => write!(f, "wrong database key"),
+            Self::DuplicateSlug(slug) => write!(f, "duplicate slug: {}", slug),
+            Self::InvalidPost(errors) => {
+                write!(f, "invalid post: ")?;
+                for (i, err) in errors.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, "; ")?;
+                    }
+                    write!(f, "{}", err)?;
+                }
+                Ok(())
+            }
         }
     }
 }
@@ -239,10 +754,41 @@ impl From<uuid::Error> for SqliteStorageError {
     }
 }
 
-fn init_db_schema(conn: &Connection) -> Result<(), SqliteStorageError> {
-    crate::storage::sqlite::commit::init_db_schema(conn)?;
-    crate::storage::sqlite::post::init_db_schema(conn)?;
-    crate::storage::sqlite::resource::init_db_schema(conn)?;
+impl From<std::io::Error> for SqliteStorageError {
+    fn from(err: std::io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+fn init_db_schema(conn: &Connection, clock: &dyn Clock) -> Result<(), SqliteStorageError> {
+    crate::storage::sqlite::migration::run_migrations(conn, clock)
+}
+
+/// Check the post's fields against every validation rule before it is written.
+///
+/// The uniqueness of the slug is left to the insertion itself, which reports it as
+/// [`SqliteStorageError::DuplicateSlug`].
+fn check_post(post: &Post) -> Result<(), SqliteStorageError> {
+    let errors = post.validate();
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(SqliteStorageError::InvalidPost(errors))
+    }
+}
+
+fn warm_statement_cache(conn: &Connection) -> Result<(), SqliteStorageError> {
+    const STATEMENTS: [&str; 5] = [
+        crate::storage::sqlite::post::SELECT_POST_SQL,
+        crate::storage::sqlite::post::SELECT_POST_TAGS_SQL,
+        crate::storage::sqlite::post::SELECT_POSTS_SQL,
+        crate::storage::sqlite::post::SELECT_POSTS_COUNT_SQL,
+        crate::storage::sqlite::resource::SELECT_RESOURCE_SQL,
+    ];
+
+    for sql in STATEMENTS {
+        conn.prepare_cached(sql)?;
+    }
 
     Ok(())
 }
@@ -283,7 +829,7 @@ impl SqliteExt for Connection {
         P: Params,
         F: FnOnce(&Row) -> Result<T, SqliteStorageError>,
     {
-        let mut stmt = self.prepare(sql.as_ref()).unwrap();
+        let mut stmt = self.prepare_cached(sql.as_ref())?;
         let mut rows = stmt.query(params)?;
         rows.next()?.map(map_row).transpose()
     }
@@ -299,7 +845,7 @@ impl SqliteExt for Connection {
         P: Params,
         F: FnMut(&Row) -> Result<T, SqliteStorageError>,
     {
-        let mut stmt = self.prepare(sql.as_ref()).unwrap();
+        let mut stmt = self.prepare_cached(sql.as_ref())?;
         let mut rows = stmt.query(params)?;
         let mut results = Vec::new();
 
@@ -311,3 +857,296 @@ impl SqliteExt for Connection {
         Ok(results)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::clock::{FixedClock, MockClock};
+    use crate::fixtures::{PostBuilder, ResourceBuilder};
+    use ublog_doc::{DocumentNode, DocumentNodeTag};
+
+    #[test]
+    fn test_commit_timestamp_from_clock() {
+        let conn = Connection::open_in_memory().unwrap();
+        let storage = SqliteStorage::new_with_clock(conn, FixedClock::new(1234)).unwrap();
+
+        storage
+            .transact_and_commit([CommitPayload::create_post("slug")], |_| Ok(()))
+            .unwrap();
+
+        let commit = crate::storage::sqlite::commit::get_latest_commit(&storage.lock())
+            .unwrap()
+            .unwrap();
+        assert_eq!(commit.timestamp, 1234);
+    }
+
+    #[tokio::test]
+    async fn test_schema_timestamp_from_clock() {
+        let conn = Connection::open_in_memory().unwrap();
+        let storage = SqliteStorage::new_with_clock(conn, FixedClock::new(1234)).unwrap();
+
+        let schema_info = storage.get_schema_info().await.unwrap();
+        assert!(!schema_info.is_empty());
+        assert!(schema_info.iter().all(|(_, timestamp)| *timestamp == 1234));
+    }
+
+    #[test]
+    fn test_commit_timestamp_from_mock_clock() {
+        let clock = MockClock::new(1000);
+        let conn = Connection::open_in_memory().unwrap();
+        let storage = SqliteStorage::new_with_clock(conn, clock.clone()).unwrap();
+
+        let get_latest_commit_timestamp = || {
+            crate::storage::sqlite::commit::get_latest_commit(&storage.lock())
+                .unwrap()
+                .unwrap()
+                .timestamp
+        };
+
+        storage
+            .transact_and_commit([CommitPayload::create_post("slug1")], |_| Ok(()))
+            .unwrap();
+        assert_eq!(get_latest_commit_timestamp(), 1000);
+
+        clock.advance(60);
+        storage
+            .transact_and_commit([CommitPayload::create_post("slug2")], |_| Ok(()))
+            .unwrap();
+        assert_eq!(get_latest_commit_timestamp(), 1060);
+    }
+
+    #[tokio::test]
+    async fn test_insert_post_invalid() {
+        let storage = SqliteStorage::new_memory().unwrap();
+
+        let post = PostBuilder::new("bad slug").title(" ").build();
+        let err = storage.insert_post(&post, &[]).await.unwrap_err();
+        assert!(matches!(
+            err,
+            SqliteStorageError::InvalidPost(errors) if errors == [
+                ValidationError::EmptyTitle,
+                ValidationError::InvalidSlug(String::from("bad slug")),
+            ]
+        ));
+        assert!(storage.get_post("bad slug").await.unwrap().is_none());
+        assert!(storage.get_latest_commit().await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_upsert_post_invalid() {
+        let storage = SqliteStorage::new_memory().unwrap();
+
+        let post = PostBuilder::new("slug").build();
+        storage.insert_post(&post, &[]).await.unwrap();
+
+        let post = PostBuilder::new("slug").title("").build();
+        let err = storage.upsert_post(&post, &[]).await.unwrap_err();
+        assert!(matches!(
+            err,
+            SqliteStorageError::InvalidPost(errors) if errors == [ValidationError::EmptyTitle]
+        ));
+        assert_eq!(
+            storage.get_post("slug").await.unwrap().unwrap().title,
+            "title"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_insert_link_post() {
+        let storage = SqliteStorage::new_memory().unwrap();
+
+        let post = PostBuilder::new("link").kind(PostKind::Link).build();
+        let err = storage.insert_post(&post, &[]).await.unwrap_err();
+        assert!(matches!(
+            err,
+            SqliteStorageError::InvalidPost(errors) if errors == [ValidationError::MissingLinkUrl]
+        ));
+
+        let post = PostBuilder::new("link")
+            .kind(PostKind::Link)
+            .link_url("ftp://example.com/file")
+            .build();
+        let err = storage.insert_post(&post, &[]).await.unwrap_err();
+        assert!(matches!(
+            err,
+            SqliteStorageError::InvalidPost(errors)
+                if errors == [ValidationError::InvalidLinkUrl(String::from("ftp://example.com/file"))]
+        ));
+
+        let post = PostBuilder::new("link")
+            .kind(PostKind::Link)
+            .link_url("https://example.com/article")
+            .build();
+        storage.insert_post(&post, &[]).await.unwrap();
+        let post = storage.get_post("link").await.unwrap().unwrap();
+        assert_eq!(
+            post.link_url.as_deref(),
+            Some("https://example.com/article")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_upsert_link_post() {
+        let storage = SqliteStorage::new_memory().unwrap();
+
+        let post = PostBuilder::new("article")
+            .link_url("https://example.com/article")
+            .build();
+        let err = storage.upsert_post(&post, &[]).await.unwrap_err();
+        assert!(matches!(
+            err,
+            SqliteStorageError::InvalidPost(errors) if errors == [ValidationError::UnexpectedLinkUrl]
+        ));
+
+        let post = PostBuilder::new("link")
+            .kind(PostKind::Link)
+            .link_url("https://example.com/article")
+            .build();
+        assert!(storage.upsert_post(&post, &[]).await.unwrap());
+
+        let post = PostBuilder::new("link").kind(PostKind::Link).build();
+        let err = storage.upsert_post(&post, &[]).await.unwrap_err();
+        assert!(matches!(
+            err,
+            SqliteStorageError::InvalidPost(errors) if errors == [ValidationError::MissingLinkUrl]
+        ));
+        let post = storage.get_post("link").await.unwrap().unwrap();
+        assert_eq!(
+            post.link_url.as_deref(),
+            Some("https://example.com/article")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_canonical_url() {
+        let storage = SqliteStorage::new_memory().unwrap();
+
+        let post = PostBuilder::new("syndicated")
+            .canonical_url("example.com/original")
+            .build();
+        let err = storage.insert_post(&post, &[]).await.unwrap_err();
+        assert!(matches!(
+            err,
+            SqliteStorageError::InvalidPost(errors)
+                if errors == [ValidationError::InvalidCanonicalUrl(String::from("example.com/original"))]
+        ));
+
+        let post = PostBuilder::new("syndicated")
+            .canonical_url("https://example.com/original")
+            .build();
+        storage.insert_post(&post, &[]).await.unwrap();
+        let post = storage.get_post("syndicated").await.unwrap().unwrap();
+        assert_eq!(
+            post.canonical_url.as_deref(),
+            Some("https://example.com/original")
+        );
+
+        let post = PostBuilder::new("syndicated").build();
+        assert!(!storage.upsert_post(&post, &[]).await.unwrap());
+        let post = storage.get_post("syndicated").await.unwrap().unwrap();
+        assert_eq!(post.canonical_url, None);
+    }
+
+    #[tokio::test]
+    async fn test_post_source() {
+        let storage = SqliteStorage::new_memory().unwrap();
+
+        let post = PostBuilder::new("synced").source("notion", 100).build();
+        storage.insert_post(&post, &[]).await.unwrap();
+        let post = storage.get_post("synced").await.unwrap().unwrap();
+        assert_eq!(post.source.as_deref(), Some("notion"));
+        assert_eq!(post.source_version, Some(100));
+
+        let post = PostBuilder::new("synced").source("notion", 200).build();
+        storage.update_post(&post, &[]).await.unwrap();
+        let posts = storage
+            .get_posts(false, &Pagination::from_page_and_size(1, 10))
+            .await
+            .unwrap();
+        assert_eq!(posts.objects[0].source_version, Some(200));
+    }
+
+    #[tokio::test]
+    async fn test_update_post_word_count() {
+        let storage = SqliteStorage::new_memory().unwrap();
+        let pagination = Pagination::from_page_and_size(1, 10);
+
+        let post = PostBuilder::new("post").build();
+        storage.insert_post(&post, &[]).await.unwrap();
+        let posts = storage.get_longform_posts(2, &pagination).await.unwrap();
+        assert_eq!(posts.total_count, 0);
+
+        let mut content = DocumentNode::new_empty();
+        content
+            .children
+            .push(DocumentNode::new(DocumentNodeTag::InlineText {
+                text: String::from("two words"),
+            }));
+        // The words are recounted from the content rather than taken from the stored post.
+        let mut post = PostBuilder::new("post").content(content).build();
+        post.word_count = 0;
+        storage.update_post(&post, &[]).await.unwrap();
+        let posts = storage.get_longform_posts(2, &pagination).await.unwrap();
+        assert_eq!(posts.total_count, 1);
+        assert_eq!(posts.objects[0].word_count, 2);
+    }
+
+    #[test]
+    fn test_query_invalid_statement() {
+        let conn = Connection::open_in_memory().unwrap();
+
+        let res = conn.query_one("SELECT FROM nowhere;", (), |row| {
+            row.get::<_, i64>(0).map_err(From::from)
+        });
+        assert!(matches!(res, Err(SqliteStorageError::Sqlite(_))));
+
+        let res = conn.query_many("SELECT FROM nowhere;", (), |row| {
+            row.get::<_, i64>(0).map_err(From::from)
+        });
+        assert!(matches!(res, Err(SqliteStorageError::Sqlite(_))));
+    }
+
+    #[test]
+    fn test_warm_statement_cache() {
+        let conn = Connection::open_in_memory().unwrap();
+        SqliteOpenOptions::new()
+            .statement_cache_capacity(8)
+            .apply(&conn)
+            .unwrap();
+        let storage = SqliteStorage::new(conn).unwrap();
+        storage.warm_statement_cache().unwrap();
+
+        let post = PostBuilder::new("slug").tags(["tag"]).build();
+        let resource = ResourceBuilder::new("res").build();
+        crate::storage::sqlite::post::insert_post(
+            &storage.lock(),
+            &post,
+            std::slice::from_ref(&resource),
+        )
+        .unwrap();
+
+        // Run every query twice so that the second run goes through the cached statement.
+        for _ in 0..2 {
+            let conn = storage.lock();
+
+            let selected_post = crate::storage::sqlite::post::get_post(&conn, "slug")
+                .unwrap()
+                .unwrap();
+            assert_eq!(selected_post.slug, "slug");
+            assert_eq!(selected_post.tags, ["tag"]);
+
+            let pagination = Pagination::from_page_and_size(1, 10);
+            let selected_posts =
+                crate::storage::sqlite::post::get_posts(&conn, false, &pagination).unwrap();
+            assert_eq!(selected_posts.total_count, 1);
+            assert_eq!(selected_posts.objects[0].tags, ["tag"]);
+
+            let selected_resource =
+                crate::storage::sqlite::resource::get_resource(&conn, &resource.id, true)
+                    .unwrap()
+                    .unwrap();
+            assert_eq!(selected_resource.data, resource.data);
+        }
+    }
+}
@@ -0,0 +1,231 @@
+use rusqlite::Connection;
+
+use crate::storage::sqlite::SqliteStorageError;
+
+/// Options for opening sqlite databases.
+///
+/// Options that are not set leave the corresponding sqlite setting at its default value: a 4096-byte page size, a
+/// 2000 KiB page cache, memory-mapped I/O disabled, `FULL` synchronization and temporary storage in files. These defaults
+/// are safe for most blogs.
+#[derive(Clone, Debug, Default)]
+pub struct SqliteOpenOptions {
+    page_size: Option<u32>,
+    cache_size_kb: Option<u32>,
+    mmap_size: Option<u64>,
+    synchronous: Option<SqliteSynchronous>,
+    temp_store: Option<SqliteTempStore>,
+    wal_autocheckpoint: Option<u32>,
+    statement_cache_capacity: Option<usize>,
+    #[cfg(feature = "sqlcipher")]
+    key: Option<crate::storage::sqlite::SqliteKey>,
+}
+
+impl SqliteOpenOptions {
+    /// Create a new `SqliteOpenOptions` with all options left at their default values.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the page size of the database, in bytes.
+    ///
+    /// The page size must be a power of two between 512 and 65536. It only takes effect if it is set before the first
+    /// table is created in the database; changing the page size of an existing database requires a `VACUUM`.
+    pub fn page_size(&mut self, page_size: u32) -> &mut Self {
+        self.page_size = Some(page_size);
+        self
+    }
+
+    /// Set the maximum size of the page cache, in KiB.
+    pub fn cache_size_kb(&mut self, cache_size_kb: u32) -> &mut Self {
+        self.cache_size_kb = Some(cache_size_kb);
+        self
+    }
+
+    /// Set the maximum number of bytes of the database file that can be accessed through memory-mapped I/O.
+    ///
+    /// Setting this to 0 disables memory-mapped I/O.
+    pub fn mmap_size(&mut self, mmap_size: u64) -> &mut Self {
+        self.mmap_size = Some(mmap_size);
+        self
+    }
+
+    /// Set the synchronization mode of the database.
+    pub fn synchronous(&mut self, synchronous: SqliteSynchronous) -> &mut Self {
+        self.synchronous = Some(synchronous);
+        self
+    }
+
+    /// Set where temporary tables and indices are stored.
+    pub fn temp_store(&mut self, temp_store: SqliteTempStore) -> &mut Self {
+        self.temp_store = Some(temp_store);
+        self
+    }
+
+    /// Set the number of frames the write-ahead log grows to before it is checkpointed automatically.
+    ///
+    /// Setting this to 0 disables automatic checkpoints, in which case the log must be checkpointed with
+    /// [`SqliteStorage::checkpoint`](crate::storage::sqlite::SqliteStorage::checkpoint). This only matters for databases
+    /// in WAL mode.
+    pub fn wal_autocheckpoint(&mut self, frames: u32) -> &mut Self {
+        self.wal_autocheckpoint = Some(frames);
+        self
+    }
+
+    /// Set the maximum number of prepared statements cached by the connection.
+    ///
+    /// Every query is prepared through the cache, so queries that run often are only compiled by sqlite once as long
+    /// as the cache is large enough to hold them. The default capacity is 16 statements.
+    pub fn statement_cache_capacity(&mut self, capacity: usize) -> &mut Self {
+        self.statement_cache_capacity = Some(capacity);
+        self
+    }
+
+    /// Set the key of the database, which is then encrypted by SQLCipher.
+    ///
+    /// Opening an existing database with a key other than the one it is encrypted with fails with
+    /// [`SqliteStorageError::WrongKey`].
+    #[cfg(feature = "sqlcipher")]
+    pub fn key(&mut self, key: crate::storage::sqlite::SqliteKey) -> &mut Self {
+        self.key = Some(key);
+        self
+    }
+
+    /// Apply the options to the given sqlite connection.
+    pub(super) fn apply(&self, conn: &Connection) -> Result<(), SqliteStorageError> {
+        // The key must be set before the database is accessed in any other way.
+        #[cfg(feature = "sqlcipher")]
+        if let Some(key) = &self.key {
+            crate::storage::sqlite::cipher::apply_key(conn, key)?;
+        }
+
+        if let Some(page_size) = self.page_size {
+            conn.pragma_update(None, "page_size", page_size)?;
+        }
+
+        if let Some(cache_size_kb) = self.cache_size_kb {
+            // A negative cache size is interpreted by sqlite as a size in KiB rather than a number of pages.
+            conn.pragma_update(None, "cache_size", -i64::from(cache_size_kb))?;
+        }
+
+        if let Some(mmap_size) = self.mmap_size {
+            conn.pragma_update(None, "mmap_size", mmap_size)?;
+        }
+
+        if let Some(synchronous) = self.synchronous {
+            conn.pragma_update(None, "synchronous", synchronous.as_pragma_value())?;
+        }
+
+        if let Some(temp_store) = self.temp_store {
+            conn.pragma_update(None, "temp_store", temp_store.as_pragma_value())?;
+        }
+
+        if let Some(wal_autocheckpoint) = self.wal_autocheckpoint {
+            conn.pragma_update(None, "wal_autocheckpoint", wal_autocheckpoint)?;
+        }
+
+        if let Some(capacity) = self.statement_cache_capacity {
+            conn.set_prepared_statement_cache_capacity(capacity);
+        }
+
+        Ok(())
+    }
+}
+
+/// Synchronization modes of sqlite databases.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum SqliteSynchronous {
+    Off,
+    Normal,
+    Full,
+    Extra,
+}
+
+impl SqliteSynchronous {
+    fn as_pragma_value(&self) -> &'static str {
+        match self {
+            Self::Off => "OFF",
+            Self::Normal => "NORMAL",
+            Self::Full => "FULL",
+            Self::Extra => "EXTRA",
+        }
+    }
+}
+
+/// Storage locations of temporary tables and indices of sqlite databases.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum SqliteTempStore {
+    /// Use the location chosen when sqlite is compiled, which is a file by default.
+    Default,
+    File,
+    Memory,
+}
+
+impl SqliteTempStore {
+    fn as_pragma_value(&self) -> &'static str {
+        match self {
+            Self::Default => "DEFAULT",
+            Self::File => "FILE",
+            Self::Memory => "MEMORY",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn query_pragma(conn: &Connection, name: &str) -> i64 {
+        conn.pragma_query_value(None, name, |row| row.get(0))
+            .unwrap()
+    }
+
+    #[test]
+    fn test_apply_cache_size() {
+        let conn = Connection::open_in_memory().unwrap();
+
+        SqliteOpenOptions::new()
+            .cache_size_kb(8192)
+            .apply(&conn)
+            .unwrap();
+
+        assert_eq!(query_pragma(&conn, "cache_size"), -8192);
+    }
+
+    #[test]
+    fn test_apply_page_size_and_synchronous() {
+        let conn = Connection::open_in_memory().unwrap();
+
+        SqliteOpenOptions::new()
+            .page_size(8192)
+            .synchronous(SqliteSynchronous::Normal)
+            .apply(&conn)
+            .unwrap();
+
+        assert_eq!(query_pragma(&conn, "page_size"), 8192);
+        assert_eq!(query_pragma(&conn, "synchronous"), 1);
+    }
+
+    #[test]
+    fn test_apply_temp_store() {
+        let conn = Connection::open_in_memory().unwrap();
+
+        SqliteOpenOptions::new()
+            .temp_store(SqliteTempStore::Memory)
+            .apply(&conn)
+            .unwrap();
+
+        assert_eq!(query_pragma(&conn, "temp_store"), 2);
+    }
+
+    #[test]
+    fn test_apply_wal_autocheckpoint() {
+        let conn = Connection::open_in_memory().unwrap();
+
+        SqliteOpenOptions::new()
+            .wal_autocheckpoint(500)
+            .apply(&conn)
+            .unwrap();
+
+        assert_eq!(query_pragma(&conn, "wal_autocheckpoint"), 500);
+    }
+}
@@ -1,10 +1,17 @@
-use rusqlite::{Connection, Row, ToSql};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
+use rusqlite::{ffi, Connection, ErrorCode, Row, ToSql};
+use time::{Date, Month};
 use ublog_doc::DocumentNode;
 use uuid::Uuid;
 
-use crate::models::{Post, Resource};
+use crate::models::{Post, PostKind, Resource, ValidationError};
 use crate::storage::sqlite::{SqliteExt, SqliteStorageError};
-use crate::storage::{PaginatedList, Pagination};
+use crate::storage::{
+    ArchiveEntry, PaginatedList, Pagination, PostsBySlugs, SlugSuggestion, TagMatch,
+    MAX_SELECT_SLUGS,
+};
+use crate::time_zone::TimeZone;
 
 pub(crate) fn init_db_schema(conn: &Connection) -> Result<(), SqliteStorageError> {
     const INIT_SQL: &str = r#"
@@ -43,17 +50,36 @@ pub(crate) fn init_db_schema(conn: &Connection) -> Result<(), SqliteStorageError
     Ok(())
 }
 
+pub(super) const SELECT_POST_SQL: &str = r#"
+    SELECT title, slug, author, create_timestamp, update_timestamp, category, is_special, kind, link_url, canonical_url, source, source_version, word_count, content
+    FROM posts
+    WHERE slug == ?;
+"#;
+
+pub(super) const SELECT_POSTS_SQL: &str = r#"
+    SELECT title, slug, author, create_timestamp, update_timestamp, category, is_special, kind, link_url, canonical_url, source, source_version, word_count
+    FROM posts
+    WHERE is_special == ?
+    ORDER BY create_timestamp DESC
+    LIMIT ? OFFSET ?;
+"#;
+
+pub(super) const SELECT_POSTS_COUNT_SQL: &str = r#"
+    SELECT count(*) AS cnt
+    FROM posts
+    WHERE is_special == ?;
+"#;
+
+pub(super) const SELECT_POST_TAGS_SQL: &str = r#"
+    SELECT tag_name FROM posts_tags
+    WHERE post_slug == ?;
+"#;
+
 pub(super) fn get_post(
     conn: &Connection,
     post_slug: &str,
 ) -> Result<Option<Post>, SqliteStorageError> {
-    const SELECT_SQL: &str = r#"
-        SELECT title, slug, author, create_timestamp, update_timestamp, category, is_special, content
-        FROM posts
-        WHERE slug == ?;
-    "#;
-
-    let mut post = conn.query_one(SELECT_SQL, (post_slug,), create_post_from_row)?;
+    let mut post = conn.query_one(SELECT_POST_SQL, (post_slug,), create_post_from_row)?;
     if let Some(post) = post.as_mut() {
         populate_post_tags(conn, post)?;
     }
@@ -71,7 +97,8 @@ pub(super) fn get_post_with_resources(
             return Ok(None);
         }
     };
-    let post_resources = crate::storage::sqlite::resource::get_post_resources(conn, post_slug)?;
+    let post_resources =
+        crate::storage::sqlite::resource::get_post_resources(conn, post_slug, false)?;
 
     Ok(Some((post, post_resources)))
 }
@@ -80,11 +107,44 @@ pub(super) fn get_posts(
     conn: &Connection,
     special: bool,
     pagination: &Pagination,
+) -> Result<PaginatedList<Post>, SqliteStorageError> {
+    let is_special = if special { 1 } else { 0 };
+
+    check_page_size(pagination)?;
+
+    let total_count: usize = conn
+        .query_one(SELECT_POSTS_COUNT_SQL, (is_special,), |row| {
+            row.get(0).map_err(From::from)
+        })?
+        .unwrap();
+
+    let mut posts = match get_limit_offset(pagination) {
+        Some((limit, offset)) => conn.query_many(
+            SELECT_POSTS_SQL,
+            (is_special, limit, offset),
+            create_post_from_row_no_content,
+        )?,
+        None => Vec::new(),
+    };
+    populate_posts_tags(conn, &mut posts)?;
+
+    Ok(PaginatedList {
+        objects: posts,
+        total_count,
+    })
+}
+
+/// Get the posts of the given kind, from the latest post to the earliest one.
+pub(super) fn get_posts_of_kind(
+    conn: &Connection,
+    special: bool,
+    kind: PostKind,
+    pagination: &Pagination,
 ) -> Result<PaginatedList<Post>, SqliteStorageError> {
     const SELECT_SQL: &str = r#"
-        SELECT title, slug, author, create_timestamp, update_timestamp, category, is_special
+        SELECT title, slug, author, create_timestamp, update_timestamp, category, is_special, kind, link_url, canonical_url, source, source_version, word_count
         FROM posts
-        WHERE is_special == ?
+        WHERE is_special == ? AND kind == ?
         ORDER BY create_timestamp DESC
         LIMIT ? OFFSET ?;
     "#;
@@ -92,27 +152,29 @@ pub(super) fn get_posts(
     const SELECT_COUNT_SQL: &str = r#"
         SELECT count(*) AS cnt
         FROM posts
-        WHERE is_special == ?;
+        WHERE is_special == ? AND kind == ?;
     "#;
 
     let is_special = if special { 1 } else { 0 };
-    let limit = pagination.page_size();
-    let offset = pagination.skip_count();
+    let kind = kind.to_code();
+
+    check_page_size(pagination)?;
 
     let total_count: usize = conn
-        .query_one(SELECT_COUNT_SQL, (is_special,), |row| {
+        .query_one(SELECT_COUNT_SQL, (is_special, kind), |row| {
             row.get(0).map_err(From::from)
         })?
         .unwrap();
 
-    let mut posts = conn.query_many(
-        SELECT_SQL,
-        (is_special, limit, offset),
-        create_post_from_row_no_content,
-    )?;
-    for p in &mut posts {
-        populate_post_tags(conn, p)?;
-    }
+    let mut posts = match get_limit_offset(pagination) {
+        Some((limit, offset)) => conn.query_many(
+            SELECT_SQL,
+            (is_special, kind, limit, offset),
+            create_post_from_row_no_content,
+        )?,
+        None => Vec::new(),
+    };
+    populate_posts_tags(conn, &mut posts)?;
 
     Ok(PaginatedList {
         objects: posts,
@@ -120,356 +182,1953 @@ pub(super) fn get_posts(
     })
 }
 
-pub(super) fn insert_post(
+/// Get the posts that have no category, from the latest post to the earliest one.
+///
+/// The `category` column is `NOT NULL`, so a post without a category is stored with an empty category.
+pub(super) fn get_uncategorized_posts(
     conn: &Connection,
-    post: &Post,
-    post_resources: &[Resource],
-) -> Result<(), SqliteStorageError> {
-    const INSERT_POST_SQL: &str = r#"
-        INSERT INTO posts (title, slug, author, create_timestamp, update_timestamp, category, is_special, content)
-        VALUES (?, ?, ?, ?, ?, ?, ?, ?);
+    pagination: &Pagination,
+) -> Result<PaginatedList<Post>, SqliteStorageError> {
+    const SELECT_SQL: &str = r#"
+        SELECT title, slug, author, create_timestamp, update_timestamp, category, is_special, kind, link_url, canonical_url, source, source_version, word_count
+        FROM posts
+        WHERE category == ''
+        ORDER BY create_timestamp DESC
+        LIMIT ? OFFSET ?;
     "#;
 
-    let is_special = if post.is_special { 1 } else { 0 };
-    let content_data = bson::to_vec(&post.content).unwrap();
+    const SELECT_COUNT_SQL: &str = r#"
+        SELECT count(*) AS cnt
+        FROM posts
+        WHERE category == '';
+    "#;
 
-    // Insert the post object into the database.
-    conn.execute(
-        INSERT_POST_SQL,
-        (
-            &post.title,
-            &post.slug,
-            &post.author,
-            post.create_timestamp,
-            post.update_timestamp,
-            &post.category,
-            is_special,
-            &content_data,
-        ),
-    )?;
+    check_page_size(pagination)?;
 
-    // Insert tags into the database.
-    if !post.tags.is_empty() {
-        crate::storage::sqlite::post::insert_post_tags(conn, &post.slug, &post.tags)?;
-    }
+    let total_count: usize = conn
+        .query_one(SELECT_COUNT_SQL, (), |row| row.get(0).map_err(From::from))?
+        .unwrap();
 
-    // Insert post resources into the database.
-    crate::storage::sqlite::post::insert_post_resources(conn, &post.slug, post_resources)?;
+    let mut posts = match get_limit_offset(pagination) {
+        Some((limit, offset)) => {
+            conn.query_many(SELECT_SQL, (limit, offset), create_post_from_row_no_content)?
+        }
+        None => Vec::new(),
+    };
+    populate_posts_tags(conn, &mut posts)?;
 
-    Ok(())
+    Ok(PaginatedList {
+        objects: posts,
+        total_count,
+    })
 }
 
-pub(super) fn delete_post(conn: &Connection, post_slug: &str) -> Result<(), SqliteStorageError> {
-    const DELETE_SQL: &str = r#"
-        DELETE FROM posts
-        WHERE slug == ?;
+/// Get the posts whose content has at least `min_words` words, from the latest post to the earliest one.
+pub(super) fn get_longform_posts(
+    conn: &Connection,
+    min_words: usize,
+    pagination: &Pagination,
+) -> Result<PaginatedList<Post>, SqliteStorageError> {
+    const SELECT_SQL: &str = r#"
+        SELECT title, slug, author, create_timestamp, update_timestamp, category, is_special, kind, link_url, canonical_url, source, source_version, word_count
+        FROM posts
+        WHERE word_count >= ?
+        ORDER BY create_timestamp DESC
+        LIMIT ? OFFSET ?;
     "#;
 
-    delete_post_resources(conn, post_slug)?;
-
-    conn.execute(DELETE_SQL, (post_slug,))?;
+    const SELECT_COUNT_SQL: &str = r#"
+        SELECT count(*) AS cnt
+        FROM posts
+        WHERE word_count >= ?;
+    "#;
 
-    Ok(())
-}
+    let min_words = min_words as i64;
 
-fn populate_post_tags(conn: &Connection, post: &mut Post) -> Result<(), SqliteStorageError> {
-    const SELECT_SQL: &str = r#"
-        SELECT tag_name FROM posts_tags
-        WHERE post_slug == ?;
-    "#;
+    check_page_size(pagination)?;
 
-    let mut select_stmt = conn.prepare_cached(SELECT_SQL).unwrap();
-    let rows = select_stmt.query((&post.slug,))?;
+    let total_count: usize = conn
+        .query_one(SELECT_COUNT_SQL, (min_words,), |row| {
+            row.get(0).map_err(From::from)
+        })?
+        .unwrap();
 
-    post.tags = rows
-        .mapped(|row| row.get(0))
-        .collect::<Result<Vec<String>, rusqlite::Error>>()?;
+    let mut posts = match get_limit_offset(pagination) {
+        Some((limit, offset)) => conn.query_many(
+            SELECT_SQL,
+            (min_words, limit, offset),
+            create_post_from_row_no_content,
+        )?,
+        None => Vec::new(),
+    };
+    populate_posts_tags(conn, &mut posts)?;
 
-    Ok(())
+    Ok(PaginatedList {
+        objects: posts,
+        total_count,
+    })
 }
 
-fn insert_post_tags(
+pub(super) fn get_posts_by_tags(
     conn: &Connection,
-    post_slug: &str,
     tags: &[String],
-) -> Result<(), SqliteStorageError> {
-    if tags.is_empty() {
-        return Ok(());
-    }
+    mode: TagMatch,
+    pagination: &Pagination,
+) -> Result<PaginatedList<Post>, SqliteStorageError> {
+    check_page_size(pagination)?;
 
-    let mut param_values: Vec<&dyn ToSql> = vec![&post_slug];
-    param_values.reserve(tags.len());
-    for t in tags {
-        param_values.push(t);
+    let tags: BTreeSet<&String> = tags.iter().collect();
+    if tags.is_empty() {
+        return Ok(PaginatedList {
+            objects: Vec::new(),
+            total_count: 0,
+        });
     }
 
-    let insert_tags_sql = format!(
+    let tags_count = tags.len();
+    let filter_sql = match mode {
+        TagMatch::All => format!(
+            r#"
+                SELECT post_slug FROM posts_tags
+                WHERE {}
+                GROUP BY post_slug
+                HAVING count(DISTINCT tag_name) == ?
+            "#,
+            crate::storage::sqlite::query::in_array_expression("tag_name"),
+        ),
+        TagMatch::Any => format!(
+            r#"
+                SELECT post_slug FROM posts_tags
+                WHERE {}
+            "#,
+            crate::storage::sqlite::query::in_array_expression("tag_name"),
+        ),
+    };
+    let select_sql = format!(
         r#"
-            INSERT INTO posts_tags (post_slug, tag_name)
-            VALUES {};
+            SELECT title, slug, author, create_timestamp, update_timestamp, category, is_special, kind, link_url, canonical_url, source, source_version, word_count
+            FROM posts
+            WHERE slug IN ({})
+            ORDER BY create_timestamp DESC
+            LIMIT ? OFFSET ?;
         "#,
-        vec!["(?1, ?)"; tags.len()].join(",")
+        filter_sql
+    );
+    let select_count_sql = format!(
+        r#"
+            SELECT count(*) AS cnt
+            FROM posts
+            WHERE slug IN ({});
+        "#,
+        filter_sql
     );
-    conn.execute(&insert_tags_sql, param_values.as_slice())?;
 
-    Ok(())
+    let tags_array = crate::storage::sqlite::query::json_array(&tags);
+    let mut param_values: Vec<&dyn ToSql> = Vec::with_capacity(4);
+    param_values.push(&tags_array);
+    if mode == TagMatch::All {
+        param_values.push(&tags_count);
+    }
+
+    let total_count: usize = conn
+        .query_one(&select_count_sql, param_values.as_slice(), |row| {
+            row.get(0).map_err(From::from)
+        })?
+        .unwrap();
+
+    let (limit, offset) = match get_limit_offset(pagination) {
+        Some(limit_offset) => limit_offset,
+        None => {
+            return Ok(PaginatedList {
+                objects: Vec::new(),
+                total_count,
+            })
+        }
+    };
+    param_values.push(&limit);
+    param_values.push(&offset);
+
+    let mut posts = conn.query_many(
+        &select_sql,
+        param_values.as_slice(),
+        create_post_from_row_no_content,
+    )?;
+    populate_posts_tags(conn, &mut posts)?;
+
+    Ok(PaginatedList {
+        objects: posts,
+        total_count,
+    })
 }
 
-fn insert_post_resources(
+/// Get at most `limit` tags that most often appear on the same posts as the given selected tags, together with their
+/// co-occurrence counts, from the most frequent tag to the least frequent one.
+///
+/// A tag's co-occurrence count is the number of pairs of it and a selected tag on the same post, so tags that appear
+/// together with several selected tags rank higher. The selected tags themselves are never suggested. Tags with equal
+/// counts are ordered by their names.
+pub(super) fn suggest_tags(
     conn: &Connection,
-    post_slug: &str,
-    resources: &[Resource],
-) -> Result<(), SqliteStorageError> {
-    for res in resources {
-        crate::storage::sqlite::resource::insert_resource(conn, res)?;
-
-        const INSERT_RELATION_SQL: &str = r#"
-            INSERT INTO posts_resources (post_slug, res_id)
-            VALUES (?, ?);
-        "#;
-        let res_id = format!("{}", res.id.as_hyphenated());
-        conn.execute(INSERT_RELATION_SQL, (post_slug, &res_id))?;
+    selected: &[String],
+    limit: usize,
+) -> Result<Vec<(String, u64)>, SqliteStorageError> {
+    let selected: BTreeSet<&String> = selected.iter().collect();
+    if selected.is_empty() || limit == 0 {
+        return Ok(Vec::new());
     }
 
-    Ok(())
+    let select_sql = format!(
+        r#"
+            SELECT other.tag_name, count(*) AS cnt
+            FROM posts_tags AS sel
+            INNER JOIN posts_tags AS other ON other.post_slug == sel.post_slug
+            WHERE sel.{} AND NOT other.{}
+            GROUP BY other.tag_name
+            ORDER BY cnt DESC, other.tag_name ASC
+            LIMIT ?;
+        "#,
+        crate::storage::sqlite::query::in_array_expression("tag_name"),
+        crate::storage::sqlite::query::in_array_expression("tag_name"),
+    );
+
+    let limit = i64::try_from(limit).unwrap_or(i64::MAX);
+    let selected_array = crate::storage::sqlite::query::json_array(&selected);
+    conn.query_many(
+        &select_sql,
+        (&selected_array, &selected_array, limit),
+        |row| {
+            let tag: String = row.get(0)?;
+            let count: i64 = row.get(1)?;
+            Ok((tag, count as u64))
+        },
+    )
 }
 
-fn delete_post_resources(conn: &Connection, post_slug: &str) -> Result<(), SqliteStorageError> {
-    const SELECT_RES_ID_SQL: &str = r#"
-        SELECT res_id
-        FROM posts_resources
-        WHERE post_slug == ?;
+pub(super) fn get_archive_index(
+    conn: &Connection,
+    special: bool,
+    time_zone: TimeZone,
+) -> Result<Vec<ArchiveEntry>, SqliteStorageError> {
+    const SELECT_SQL: &str = r#"
+        SELECT create_timestamp
+        FROM posts
+        WHERE is_special == ?;
     "#;
-    let res_ids = conn.query_many(SELECT_RES_ID_SQL, (post_slug,), |row| {
-        let res_id_str: String = row.get("res_id")?;
-        let res_id = Uuid::try_parse(&res_id_str)?;
-        Ok(res_id)
+
+    let is_special = if special { 1 } else { 0 };
+    let timestamps: Vec<i64> = conn.query_many(SELECT_SQL, (is_special,), |row| {
+        row.get(0).map_err(From::from)
     })?;
 
-    for res_id in res_ids {
-        crate::storage::sqlite::resource::delete_resource(conn, &res_id)?;
+    // The offset from UTC differs between posts across daylight saving time transitions, so posts are grouped here
+    // rather than in SQL.
+    let mut months_count = BTreeMap::new();
+    for timestamp in timestamps {
+        let date = time_zone.local_time(timestamp).date();
+        *months_count
+            .entry((date.year(), u8::from(date.month())))
+            .or_insert(0) += 1;
     }
 
-    Ok(())
+    Ok(months_count
+        .into_iter()
+        .rev()
+        .map(|((year, month), posts_count)| ArchiveEntry {
+            year,
+            month,
+            posts_count,
+        })
+        .collect())
 }
 
-fn create_post_from_row(row: &Row) -> Result<Post, SqliteStorageError> {
-    let content_data: Vec<u8> = row.get("content")?;
-    let content = bson::from_slice(&content_data)?;
-    Ok(Post {
-        title: row.get("title")?,
-        slug: row.get("slug")?,
-        author: row.get("author")?,
-        create_timestamp: row.get("create_timestamp")?,
-        update_timestamp: row.get("update_timestamp")?,
-        category: row.get("category")?,
-        tags: Vec::new(),
-        is_special: row.get::<_, i32>("is_special")? != 0,
-        content,
+pub(super) fn get_archive_posts(
+    conn: &Connection,
+    special: bool,
+    year: i32,
+    month: u8,
+    time_zone: TimeZone,
+    pagination: &Pagination,
+) -> Result<PaginatedList<Post>, SqliteStorageError> {
+    const SELECT_SQL: &str = r#"
+        SELECT title, slug, author, create_timestamp, update_timestamp, category, is_special, kind, link_url, canonical_url, source, source_version, word_count
+        FROM posts
+        WHERE is_special == ? AND create_timestamp >= ? AND create_timestamp < ?
+        ORDER BY create_timestamp DESC
+        LIMIT ? OFFSET ?;
+    "#;
+
+    const SELECT_COUNT_SQL: &str = r#"
+        SELECT count(*) AS cnt
+        FROM posts
+        WHERE is_special == ? AND create_timestamp >= ? AND create_timestamp < ?;
+    "#;
+
+    let (since, until) = get_month_range(year, month, time_zone)
+        .ok_or(SqliteStorageError::InvalidMonth(year, month))?;
+
+    let is_special = if special { 1 } else { 0 };
+
+    check_page_size(pagination)?;
+
+    let total_count: usize = conn
+        .query_one(SELECT_COUNT_SQL, (is_special, since, until), |row| {
+            row.get(0).map_err(From::from)
+        })?
+        .unwrap();
+
+    let mut posts = match get_limit_offset(pagination) {
+        Some((limit, offset)) => conn.query_many(
+            SELECT_SQL,
+            (is_special, since, until, limit, offset),
+            create_post_from_row_no_content,
+        )?,
+        None => Vec::new(),
+    };
+    populate_posts_tags(conn, &mut posts)?;
+
+    Ok(PaginatedList {
+        objects: posts,
+        total_count,
     })
 }
 
-fn create_post_from_row_no_content(row: &Row) -> Result<Post, SqliteStorageError> {
-    Ok(Post {
-        title: row.get("title")?,
-        slug: row.get("slug")?,
-        author: row.get("author")?,
-        create_timestamp: row.get("create_timestamp")?,
-        update_timestamp: row.get("update_timestamp")?,
-        category: row.get("category")?,
-        tags: Vec::new(),
-        is_special: row.get::<_, i32>("is_special")? != 0,
-        content: DocumentNode::new_empty(),
-    })
+/// Check that the page size of the given pagination is within `MAX_PAGE_SIZE`.
+fn check_page_size(pagination: &Pagination) -> Result<(), SqliteStorageError> {
+    if pagination.page_size() > crate::storage::MAX_PAGE_SIZE {
+        return Err(SqliteStorageError::PageTooLarge(pagination.page_size()));
+    }
+
+    Ok(())
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use std::collections::HashSet;
+/// Get the values of the `LIMIT` and `OFFSET` clauses that select the specified page.
+///
+/// Returns `None` if the page lies beyond any offset that sqlite can represent, in which case the page is empty.
+fn get_limit_offset(pagination: &Pagination) -> Option<(i64, i64)> {
+    let limit = i64::try_from(pagination.page_size()).unwrap_or(i64::MAX);
+    let offset = i64::try_from(pagination.skip_count()?).ok()?;
+    Some((limit, offset))
+}
 
-    fn init_db_connection() -> Connection {
-        let conn = Connection::open_in_memory().unwrap();
+/// Get the range of Unix timestamps covered by the specified month in the given time zone.
+///
+/// The start of the range is inclusive while the end of the range is exclusive.
+fn get_month_range(year: i32, month: u8, time_zone: TimeZone) -> Option<(i64, i64)> {
+    let month = Month::try_from(month).ok()?;
+    let (next_year, next_month) = match month {
+        Month::December => (year.checked_add(1)?, Month::January),
+        _ => (year, month.next()),
+    };
 
-        init_db_schema(&conn).unwrap();
-        crate::storage::sqlite::resource::init_db_schema(&conn).unwrap();
+    let since = Date::from_calendar_date(year, month, 1).ok()?;
+    let until = Date::from_calendar_date(next_year, next_month, 1).ok()?;
 
+    Some((
+        time_zone.unix_timestamp(since.midnight()),
+        time_zone.unix_timestamp(until.midnight()),
+    ))
+}
+
+pub(super) fn get_post_slugs(conn: &Connection) -> Result<Vec<String>, SqliteStorageError> {
+    const SELECT_SQL: &str = r#"
+        SELECT slug
+        FROM posts
+        ORDER BY slug ASC;
+    "#;
+
+    conn.query_many(SELECT_SQL, (), |row| row.get(0).map_err(From::from))
+}
+
+/// Get the posts whose titles are within `max_distance` edits of the given title, from the most similar post to the
+/// least similar one.
+///
+/// Titles are compared case-insensitively by their Levenshtein distance. Posts whose title lengths alone differ by more
+/// than `max_distance` characters cannot be similar enough and are filtered out by sqlite. The content of the posts is
+/// not loaded.
+pub(super) fn get_posts_with_similar_title(
+    conn: &Connection,
+    title: &str,
+    max_distance: usize,
+) -> Result<Vec<Post>, SqliteStorageError> {
+    const SELECT_SQL: &str = r#"
+        SELECT title, slug, author, create_timestamp, update_timestamp, category, is_special, kind, link_url, canonical_url, source, source_version, word_count
+        FROM posts
+        WHERE abs(length(title) - ?1) <= ?2
+        ORDER BY create_timestamp DESC;
+    "#;
+
+    let title = title.to_lowercase();
+    let title_len = i64::try_from(title.chars().count()).unwrap_or(i64::MAX);
+    let max_distance_param = i64::try_from(max_distance).unwrap_or(i64::MAX);
+
+    let candidates = conn.query_many(
+        SELECT_SQL,
+        (title_len, max_distance_param),
+        create_post_from_row_no_content,
+    )?;
+
+    let mut posts: Vec<_> = candidates
+        .into_iter()
+        .filter_map(|post| {
+            let distance = get_edit_distance(&title, &post.title.to_lowercase());
+            if distance <= max_distance {
+                Some((distance, post))
+            } else {
+                None
+            }
+        })
+        .collect();
+    // The sort is stable, so posts equally similar to the title stay ordered from the latest to the earliest.
+    posts.sort_by_key(|(distance, _)| *distance);
+
+    let mut posts: Vec<_> = posts.into_iter().map(|(_, post)| post).collect();
+    populate_posts_tags(conn, &mut posts)?;
+
+    Ok(posts)
+}
+
+/// Get at most `limit` posts whose slugs are similar to the given slug, from the most similar post to the least similar
+/// one.
+///
+/// Slugs are compared case-insensitively by their Levenshtein distance, which must not exceed a quarter of the length
+/// of the given slug (but at least 1). Only posts whose slug lengths are within that distance are loaded from sqlite.
+pub(super) fn get_similar_slugs(
+    conn: &Connection,
+    slug: &str,
+    limit: usize,
+) -> Result<Vec<SlugSuggestion>, SqliteStorageError> {
+    const SELECT_SQL: &str = r#"
+        SELECT slug, title
+        FROM posts
+        WHERE abs(length(slug) - ?1) <= ?2
+        ORDER BY create_timestamp DESC;
+    "#;
+
+    let slug = slug.to_lowercase();
+    let slug_len = slug.chars().count();
+    let max_distance = (slug_len / 4).max(1);
+
+    let candidates = conn.query_many(
+        SELECT_SQL,
+        (
+            i64::try_from(slug_len).unwrap_or(i64::MAX),
+            max_distance as i64,
+        ),
+        |row| {
+            Ok(SlugSuggestion {
+                slug: row.get(0)?,
+                title: row.get(1)?,
+            })
+        },
+    )?;
+
+    let mut suggestions: Vec<_> = candidates
+        .into_iter()
+        .filter_map(|suggestion| {
+            let distance = get_edit_distance(&slug, &suggestion.slug.to_lowercase());
+            if distance <= max_distance {
+                Some((distance, suggestion))
+            } else {
+                None
+            }
+        })
+        .collect();
+    // The sort is stable, so posts equally similar to the slug stay ordered from the latest to the earliest.
+    suggestions.sort_by_key(|(distance, _)| *distance);
+
+    Ok(suggestions
+        .into_iter()
+        .take(limit)
+        .map(|(_, suggestion)| suggestion)
+        .collect())
+}
+
+/// Get the Levenshtein distance between the two given strings, counted in characters.
+fn get_edit_distance(lhs: &str, rhs: &str) -> usize {
+    let rhs: Vec<char> = rhs.chars().collect();
+
+    // distances[j] holds the distance between the prefix of lhs processed so far and the first j characters of rhs.
+    let mut distances: Vec<usize> = (0..=rhs.len()).collect();
+    for (i, lhs_ch) in lhs.chars().enumerate() {
+        let mut prev_diagonal = distances[0];
+        distances[0] = i + 1;
+        for (j, rhs_ch) in rhs.iter().enumerate() {
+            let substitution = prev_diagonal + if lhs_ch == *rhs_ch { 0 } else { 1 };
+            let deletion = distances[j + 1] + 1;
+            let insertion = distances[j] + 1;
+            prev_diagonal = distances[j + 1];
+            distances[j + 1] = substitution.min(deletion).min(insertion);
+        }
+    }
+
+    distances[rhs.len()]
+}
+
+pub(super) fn get_latest_post(
+    conn: &Connection,
+    special: bool,
+) -> Result<Option<Post>, SqliteStorageError> {
+    const SELECT_SQL: &str = r#"
+        SELECT title, slug, author, create_timestamp, update_timestamp, category, is_special, kind, link_url, canonical_url, source, source_version, word_count, content
+        FROM posts
+        WHERE is_special == ?
+        ORDER BY create_timestamp DESC, slug ASC
+        LIMIT 1;
+    "#;
+
+    get_edge_post(conn, SELECT_SQL, special)
+}
+
+pub(super) fn get_oldest_post(
+    conn: &Connection,
+    special: bool,
+) -> Result<Option<Post>, SqliteStorageError> {
+    const SELECT_SQL: &str = r#"
+        SELECT title, slug, author, create_timestamp, update_timestamp, category, is_special, kind, link_url, canonical_url, source, source_version, word_count, content
+        FROM posts
+        WHERE is_special == ?
+        ORDER BY create_timestamp ASC, slug ASC
+        LIMIT 1;
+    "#;
+
+    get_edge_post(conn, SELECT_SQL, special)
+}
+
+fn get_edge_post(
+    conn: &Connection,
+    select_sql: &str,
+    special: bool,
+) -> Result<Option<Post>, SqliteStorageError> {
+    let is_special = if special { 1 } else { 0 };
+
+    let mut post = conn.query_one(select_sql, (is_special,), create_post_from_row)?;
+    if let Some(post) = post.as_mut() {
+        populate_post_tags(conn, post)?;
+    }
+
+    Ok(post)
+}
+
+pub(super) fn get_posts_by_slugs(
+    conn: &Connection,
+    post_slugs: &[String],
+    with_content: bool,
+) -> Result<PostsBySlugs, SqliteStorageError> {
+    if post_slugs.len() > MAX_SELECT_SLUGS {
+        return Err(SqliteStorageError::TooManySlugs(post_slugs.len()));
+    }
+
+    let mut unique_slugs: Vec<&String> = Vec::with_capacity(post_slugs.len());
+    for slug in post_slugs {
+        if !unique_slugs.contains(&slug) {
+            unique_slugs.push(slug);
+        }
+    }
+
+    if unique_slugs.is_empty() {
+        return Ok(PostsBySlugs::default());
+    }
+
+    let (columns, create_post): (&str, PostRowMapper) = if with_content {
+        (
+                "title, slug, author, create_timestamp, update_timestamp, category, is_special, kind, link_url, canonical_url, source, source_version, word_count, content",
+                create_post_from_row,
+            )
+    } else {
+        (
+                "title, slug, author, create_timestamp, update_timestamp, category, is_special, kind, link_url, canonical_url, source, source_version, word_count",
+                create_post_from_row_no_content,
+            )
+    };
+    let select_sql = format!(
+        r#"
+            SELECT {}
+            FROM posts
+            WHERE {};
+        "#,
+        columns,
+        crate::storage::sqlite::query::in_array_expression("slug"),
+    );
+
+    let mut posts = conn.query_many(
+        &select_sql,
+        (crate::storage::sqlite::query::json_array(&unique_slugs),),
+        create_post,
+    )?;
+    populate_posts_tags(conn, &mut posts)?;
+
+    let mut posts: HashMap<String, Post> = posts.into_iter().map(|p| (p.slug.clone(), p)).collect();
+
+    let mut selected = PostsBySlugs::default();
+    for slug in unique_slugs {
+        match posts.remove(slug) {
+            Some(post) => selected.posts.push(post),
+            None => selected.missing_slugs.push(slug.clone()),
+        }
+    }
+
+    Ok(selected)
+}
+
+pub(super) fn validate_post(
+    conn: &Connection,
+    post: &Post,
+) -> Result<Vec<ValidationError>, SqliteStorageError> {
+    const SELECT_SQL: &str = r#"
+        SELECT EXISTS (SELECT 1 FROM posts WHERE slug == ?);
+    "#;
+
+    let mut errors = post.validate();
+
+    let slug_exists: bool = conn
+        .query_one(SELECT_SQL, (&post.slug,), |row| {
+            row.get(0).map_err(From::from)
+        })?
+        .unwrap();
+    if slug_exists {
+        errors.push(ValidationError::DuplicateSlug(post.slug.clone()));
+    }
+
+    Ok(errors)
+}
+
+pub(super) fn insert_post(
+    conn: &Connection,
+    post: &Post,
+    post_resources: &[Resource],
+) -> Result<(), SqliteStorageError> {
+    const INSERT_POST_SQL: &str = r#"
+        INSERT INTO posts (title, slug, author, create_timestamp, update_timestamp, category, is_special, kind, link_url, canonical_url, source, source_version, word_count, content)
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?);
+    "#;
+
+    let is_special = if post.is_special { 1 } else { 0 };
+    let word_count = post.content.word_count() as i64;
+    let content_data = bson::to_vec(&post.content).unwrap();
+
+    // Insert the post object into the database.
+    conn.execute(
+        INSERT_POST_SQL,
+        (
+            &post.title,
+            &post.slug,
+            &post.author,
+            post.create_timestamp,
+            post.update_timestamp,
+            &post.category,
+            is_special,
+            post.kind.to_code(),
+            &post.link_url,
+            &post.canonical_url,
+            &post.source,
+            post.source_version,
+            word_count,
+            &content_data,
+        ),
+    )
+    .map_err(|err| match err {
+        // The slug is the primary key of the posts table, which is the only uniqueness constraint on it.
+        rusqlite::Error::SqliteFailure(ref ffi_err, _)
+            if ffi_err.code == ErrorCode::ConstraintViolation
+                && (ffi_err.extended_code == ffi::SQLITE_CONSTRAINT_PRIMARYKEY
+                    || ffi_err.extended_code == ffi::SQLITE_CONSTRAINT_UNIQUE) =>
+        {
+            SqliteStorageError::DuplicateSlug(post.slug.clone())
+        }
+        _ => SqliteStorageError::from(err),
+    })?;
+
+    // Insert tags into the database.
+    if !post.tags.is_empty() {
+        crate::storage::sqlite::post::insert_post_tags(conn, &post.slug, &post.tags)?;
+    }
+
+    // Insert post resources into the database.
+    crate::storage::sqlite::post::insert_post_resources(conn, &post.slug, post_resources)?;
+
+    Ok(())
+}
+
+/// Set the last update timestamp of the post with the given slug, leaving the rest of the post unchanged.
+///
+/// Returns `false` if there is no post with the given slug.
+pub(super) fn touch_post(
+    conn: &Connection,
+    post_slug: &str,
+    update_timestamp: i64,
+) -> Result<bool, SqliteStorageError> {
+    const UPDATE_SQL: &str = r#"
+        UPDATE posts
+        SET update_timestamp = ?
+        WHERE slug == ?;
+    "#;
+
+    let changes = conn.execute(UPDATE_SQL, (update_timestamp, post_slug))?;
+    Ok(changes > 0)
+}
+
+/// Insert the given post, or update the existing post with the same slug in place.
+///
+/// An existing post keeps its creation timestamp, while the rest of its fields, its tags and its resources are replaced.
+/// Resources that stay attached to the post keep their visibility. Returns whether the post is newly inserted.
+pub(super) fn upsert_post(
+    conn: &Connection,
+    post: &Post,
+    post_resources: &[Resource],
+) -> Result<bool, SqliteStorageError> {
+    const SELECT_SQL: &str = r#"
+        SELECT EXISTS (SELECT 1 FROM posts WHERE slug == ?);
+    "#;
+
+    const UPSERT_SQL: &str = r#"
+        INSERT INTO posts (title, slug, author, create_timestamp, update_timestamp, category, is_special, kind, link_url, canonical_url, source, source_version, word_count, content)
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+        ON CONFLICT (slug) DO UPDATE SET
+            title            = excluded.title,
+            author           = excluded.author,
+            update_timestamp = excluded.update_timestamp,
+            category         = excluded.category,
+            is_special       = excluded.is_special,
+            kind             = excluded.kind,
+            link_url         = excluded.link_url,
+            canonical_url    = excluded.canonical_url,
+            source           = excluded.source,
+            source_version   = excluded.source_version,
+            word_count       = excluded.word_count,
+            content          = excluded.content;
+    "#;
+
+    let exists: bool = conn
+        .query_one(SELECT_SQL, (&post.slug,), |row| {
+            row.get(0).map_err(From::from)
+        })?
+        .unwrap();
+
+    let mut private_resource_ids = Vec::new();
+    if exists {
+        private_resource_ids =
+            crate::storage::sqlite::resource::get_private_resource_ids(conn, &post.slug)?;
+        delete_post_tags(conn, &post.slug)?;
+        delete_post_resources(conn, &post.slug)?;
+    }
+
+    let is_special = if post.is_special { 1 } else { 0 };
+    let word_count = post.content.word_count() as i64;
+    let content_data = bson::to_vec(&post.content).unwrap();
+    conn.execute(
+        UPSERT_SQL,
+        (
+            &post.title,
+            &post.slug,
+            &post.author,
+            post.create_timestamp,
+            post.update_timestamp,
+            &post.category,
+            is_special,
+            post.kind.to_code(),
+            &post.link_url,
+            &post.canonical_url,
+            &post.source,
+            post.source_version,
+            word_count,
+            &content_data,
+        ),
+    )?;
+
+    insert_post_tags(conn, &post.slug, &post.tags)?;
+    insert_post_resources(conn, &post.slug, post_resources)?;
+    for id in &private_resource_ids {
+        crate::storage::sqlite::resource::set_resource_public(conn, &post.slug, id, false)?;
+    }
+
+    Ok(!exists)
+}
+
+pub(super) fn delete_post(
+    conn: &Connection,
+    post_slug: &str,
+) -> Result<Option<Post>, SqliteStorageError> {
+    const DELETE_SQL: &str = r#"
+        DELETE FROM posts
+        WHERE slug == ?;
+    "#;
+
+    let post = get_post(conn, post_slug)?;
+
+    // Remove dependent rows explicitly rather than relying on the foreign key cascades, which only take effect when
+    // foreign key enforcement is enabled on the connection.
+    delete_post_tags(conn, post_slug)?;
+    delete_post_resources(conn, post_slug)?;
+    crate::storage::sqlite::meta::delete_post_meta(conn, post_slug)?;
+
+    conn.execute(DELETE_SQL, (post_slug,))?;
+
+    Ok(post)
+}
+
+fn populate_post_tags(conn: &Connection, post: &mut Post) -> Result<(), SqliteStorageError> {
+    let mut select_stmt = conn.prepare_cached(SELECT_POST_TAGS_SQL).unwrap();
+    let rows = select_stmt.query((&post.slug,))?;
+
+    post.tags = rows
+        .mapped(|row| row.get(0))
+        .collect::<Result<Vec<String>, rusqlite::Error>>()?;
+
+    Ok(())
+}
+
+fn populate_posts_tags(conn: &Connection, posts: &mut [Post]) -> Result<(), SqliteStorageError> {
+    if posts.is_empty() {
+        return Ok(());
+    }
+
+    let select_sql = format!(
+        r#"
+            SELECT post_slug, tag_name FROM posts_tags
+            WHERE {}
+            ORDER BY rowid;
+        "#,
+        crate::storage::sqlite::query::in_array_expression("post_slug"),
+    );
+    let post_tags = conn.query_many(
+        &select_sql,
+        (crate::storage::sqlite::query::json_array(
+            posts.iter().map(|p| &p.slug),
+        ),),
+        |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)),
+    )?;
+
+    let mut tags: HashMap<String, Vec<String>> = HashMap::new();
+    for (post_slug, tag_name) in post_tags {
+        tags.entry(post_slug).or_default().push(tag_name);
+    }
+
+    for p in posts {
+        p.tags = tags.remove(&p.slug).unwrap_or_default();
+    }
+
+    Ok(())
+}
+
+fn insert_post_tags(
+    conn: &Connection,
+    post_slug: &str,
+    tags: &[String],
+) -> Result<(), SqliteStorageError> {
+    if tags.is_empty() {
+        return Ok(());
+    }
+
+    // Tags that are equal after normalization are inserted once, in the spelling of their first occurrence.
+    let mut normalized_tags = HashSet::new();
+    let tags: Vec<&String> = tags
+        .iter()
+        .filter(|t| normalized_tags.insert(crate::models::normalize_tag(t)))
+        .collect();
+
+    let mut param_values: Vec<&dyn ToSql> = Vec::with_capacity(tags.len() * 2);
+    for t in &tags {
+        param_values.push(&post_slug);
+        param_values.push(t);
+    }
+
+    // Tags the post already has are skipped rather than failing the whole insertion on the unique index.
+    let insert_tags_sql = crate::storage::sqlite::query::insert_or_ignore_statement(
+        "posts_tags",
+        &["post_slug", "tag_name"],
+        tags.len(),
+    );
+    conn.execute(&insert_tags_sql, param_values.as_slice())?;
+
+    Ok(())
+}
+
+fn delete_post_tags(conn: &Connection, post_slug: &str) -> Result<(), SqliteStorageError> {
+    const DELETE_SQL: &str = r#"
+        DELETE FROM posts_tags
+        WHERE post_slug == ?;
+    "#;
+
+    conn.execute(DELETE_SQL, (post_slug,))?;
+
+    Ok(())
+}
+
+fn insert_post_resources(
+    conn: &Connection,
+    post_slug: &str,
+    resources: &[Resource],
+) -> Result<(), SqliteStorageError> {
+    for res in resources {
+        crate::storage::sqlite::resource::insert_resource(conn, res)?;
+
+        const INSERT_RELATION_SQL: &str = r#"
+            INSERT INTO posts_resources (post_slug, res_id)
+            VALUES (?, ?);
+        "#;
+        let res_id = format!("{}", res.id.as_hyphenated());
+        conn.execute(INSERT_RELATION_SQL, (post_slug, &res_id))?;
+    }
+
+    Ok(())
+}
+
+/// Delete all resources attached to the post with the given slug, leaving the post itself in place.
+///
+/// Returns the IDs of the deleted resources.
+pub(super) fn delete_post_resources(
+    conn: &Connection,
+    post_slug: &str,
+) -> Result<Vec<Uuid>, SqliteStorageError> {
+    const SELECT_RES_ID_SQL: &str = r#"
+        SELECT res_id
+        FROM posts_resources
+        WHERE post_slug == ?;
+    "#;
+    let res_ids = conn.query_many(SELECT_RES_ID_SQL, (post_slug,), |row| {
+        let res_id_str: String = row.get("res_id")?;
+        let res_id = Uuid::try_parse(&res_id_str)?;
+        Ok(res_id)
+    })?;
+
+    const DELETE_RES_SQL: &str = r#"
+        DELETE FROM resources
+        WHERE id IN (
+            SELECT res_id
+            FROM posts_resources
+            WHERE post_slug == ?
+        );
+    "#;
+    conn.execute(DELETE_RES_SQL, (post_slug,))?;
+
+    const DELETE_RELATION_SQL: &str = r#"
+        DELETE FROM posts_resources
+        WHERE post_slug == ?;
+    "#;
+    conn.execute(DELETE_RELATION_SQL, (post_slug,))?;
+
+    Ok(res_ids)
+}
+
+/// Find the post-resource relations that refer to posts that do not exist.
+///
+/// Such relations are left behind if a post was deleted while its relations were not, which could happen before
+/// posts were deleted together with their dependent rows. Returns the slug of the missing post and the ID of the
+/// resource for each such relation.
+pub(super) fn get_orphaned_post_resources(
+    conn: &Connection,
+) -> Result<Vec<(String, Uuid)>, SqliteStorageError> {
+    const SELECT_SQL: &str = r#"
+        SELECT post_slug, res_id
+        FROM posts_resources
+        WHERE post_slug NOT IN (SELECT slug FROM posts)
+        ORDER BY post_slug, res_id;
+    "#;
+
+    conn.query_many(SELECT_SQL, (), |row| {
+        let post_slug: String = row.get("post_slug")?;
+        let res_id_str: String = row.get("res_id")?;
+        let res_id = Uuid::try_parse(&res_id_str)?;
+        Ok((post_slug, res_id))
+    })
+}
+
+/// Delete the post-resource relations that refer to posts that do not exist, together with the resources that are
+/// no longer attached to any post afterwards.
+///
+/// Returns the IDs of the deleted resources.
+pub(super) fn delete_orphaned_post_resources(
+    conn: &Connection,
+) -> Result<Vec<Uuid>, SqliteStorageError> {
+    let orphans = get_orphaned_post_resources(conn)?;
+
+    const DELETE_RELATION_SQL: &str = r#"
+        DELETE FROM posts_resources
+        WHERE post_slug NOT IN (SELECT slug FROM posts);
+    "#;
+    conn.execute(DELETE_RELATION_SQL, ())?;
+
+    const DELETE_RES_SQL: &str = r#"
+        DELETE FROM resources
+        WHERE id == ? AND id NOT IN (SELECT res_id FROM posts_resources);
+    "#;
+
+    let mut res_ids = Vec::new();
+    for (_, res_id) in orphans {
+        let res_id_str = format!("{}", res_id.as_hyphenated());
+        if conn.execute(DELETE_RES_SQL, (&res_id_str,))? > 0 {
+            res_ids.push(res_id);
+        }
+    }
+
+    Ok(res_ids)
+}
+
+fn get_post_kind(row: &Row) -> Result<PostKind, SqliteStorageError> {
+    // Kinds unknown to this version are shown as articles rather than failing the whole selection.
+    let code: i32 = row.get("kind")?;
+    Ok(PostKind::from_code(code).unwrap_or_default())
+}
+
+/// A function that creates a post from a selected row.
+type PostRowMapper = fn(&Row) -> Result<Post, SqliteStorageError>;
+
+fn create_post_from_row(row: &Row) -> Result<Post, SqliteStorageError> {
+    let content_data: Vec<u8> = row.get("content")?;
+    let content = bson::from_slice(&content_data)?;
+    Ok(Post {
+        title: row.get("title")?,
+        slug: row.get("slug")?,
+        author: row.get("author")?,
+        create_timestamp: row.get("create_timestamp")?,
+        update_timestamp: row.get("update_timestamp")?,
+        category: row.get("category")?,
+        tags: Vec::new(),
+        is_special: row.get::<_, i32>("is_special")? != 0,
+        kind: get_post_kind(row)?,
+        link_url: row.get("link_url")?,
+        canonical_url: row.get("canonical_url")?,
+        source: row.get("source")?,
+        source_version: row.get("source_version")?,
+        word_count: row.get("word_count")?,
+        content,
+    })
+}
+
+fn create_post_from_row_no_content(row: &Row) -> Result<Post, SqliteStorageError> {
+    Ok(Post {
+        title: row.get("title")?,
+        slug: row.get("slug")?,
+        author: row.get("author")?,
+        create_timestamp: row.get("create_timestamp")?,
+        update_timestamp: row.get("update_timestamp")?,
+        category: row.get("category")?,
+        tags: Vec::new(),
+        is_special: row.get::<_, i32>("is_special")? != 0,
+        kind: get_post_kind(row)?,
+        link_url: row.get("link_url")?,
+        canonical_url: row.get("canonical_url")?,
+        source: row.get("source")?,
+        source_version: row.get("source_version")?,
+        word_count: row.get("word_count")?,
+        content: DocumentNode::new_empty(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::SystemClock;
+    use crate::fixtures::{PostBuilder, ResourceBuilder};
+    use std::collections::HashSet;
+
+    fn init_db_connection() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        crate::storage::sqlite::init_db_schema(&conn, &SystemClock).unwrap();
         conn
     }
 
-    fn select_tag_names(conn: &Connection, post_slug: &str) -> Vec<String> {
-        const SELECT_SQL: &str = r#"
-            SELECT tag_name FROM posts_tags
-            WHERE post_slug == ?;
-        "#;
+    fn select_tag_names(conn: &Connection, post_slug: &str) -> Vec<String> {
+        const SELECT_SQL: &str = r#"
+            SELECT tag_name FROM posts_tags
+            WHERE post_slug == ?;
+        "#;
+
+        conn.query_many(SELECT_SQL, (post_slug,), |row| {
+            row.get(0).map_err(From::from)
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_insert_post_basic() {
+        let conn = init_db_connection();
+
+        let post = PostBuilder::new("slug").build();
+        insert_post(&conn, &post, &[]).unwrap();
+    }
+
+    #[test]
+    fn test_insert_post_conflict_slug() {
+        let conn = init_db_connection();
+
+        let post = PostBuilder::new("slug").build();
+        insert_post(&conn, &post, &[]).unwrap();
+
+        let insert_res = insert_post(&conn, &post, &[]);
+        assert!(matches!(
+            insert_res,
+            Err(SqliteStorageError::DuplicateSlug(slug)) if slug == "slug"
+        ));
+    }
+
+    #[test]
+    fn test_insert_post_duplicate_tags() {
+        let conn = init_db_connection();
+
+        let post = PostBuilder::new("slug")
+            .tags(["tag1", "tag2", "tag1"])
+            .build();
+        insert_post(&conn, &post, &[]).unwrap();
+
+        assert_eq!(select_tag_names(&conn, &post.slug), ["tag1", "tag2"]);
+    }
+
+    #[test]
+    fn test_insert_post_normalized_duplicate_tags() {
+        let conn = init_db_connection();
+
+        let post = PostBuilder::new("slug")
+            .tags(["Rust", " rust ", "RUST", "tag"])
+            .build();
+        insert_post(&conn, &post, &[]).unwrap();
+
+        assert_eq!(select_tag_names(&conn, &post.slug), ["Rust", "tag"]);
+        assert!(post
+            .validate()
+            .contains(&ValidationError::DuplicateTag(String::from(" rust "))));
+    }
+
+    #[test]
+    fn test_insert_post_existing_tag() {
+        let conn = init_db_connection();
+
+        let post = PostBuilder::new("slug").tags(["tag1", "tag2"]).build();
+        insert_post(&conn, &post, &[]).unwrap();
+
+        // Tags the post already has are ignored, while the others are added.
+        insert_post_tags(&conn, "slug", &[String::from("tag2"), String::from("tag3")]).unwrap();
+        assert_eq!(select_tag_names(&conn, "slug"), ["tag1", "tag2", "tag3"]);
+    }
+
+    #[test]
+    fn test_insert_post_tags() {
+        let conn = init_db_connection();
+
+        let post = PostBuilder::new("slug").tags(["tag1", "tag2"]).build();
+        insert_post(&conn, &post, &[]).unwrap();
+
+        let tags: HashSet<_> = select_tag_names(&conn, &post.slug).into_iter().collect();
+        let expected_tags: HashSet<_> = vec![String::from("tag1"), String::from("tag2")]
+            .into_iter()
+            .collect();
+        assert_eq!(tags, expected_tags);
+    }
+
+    #[test]
+    fn test_validate_post_collects_all_errors() {
+        let conn = init_db_connection();
+
+        let post = PostBuilder::new("slug").build();
+        assert!(validate_post(&conn, &post).unwrap().is_empty());
+        insert_post(&conn, &post, &[]).unwrap();
+
+        let post = PostBuilder::new("slug").title("").build();
+        assert_eq!(
+            validate_post(&conn, &post).unwrap(),
+            [
+                ValidationError::EmptyTitle,
+                ValidationError::DuplicateSlug(String::from("slug")),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_touch_post() {
+        let conn = init_db_connection();
+
+        let post = PostBuilder::new("slug").timestamp(100).build();
+        insert_post(&conn, &post, &[]).unwrap();
+
+        assert!(touch_post(&conn, "slug", 200).unwrap());
+        assert!(!touch_post(&conn, "missing", 200).unwrap());
+
+        let selected_post = get_post(&conn, "slug").unwrap().unwrap();
+        assert_eq!(selected_post.create_timestamp, 100);
+        assert_eq!(selected_post.update_timestamp, 200);
+        assert_eq!(
+            bson::to_vec(&selected_post.content).unwrap(),
+            bson::to_vec(&post.content).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_upsert_post() {
+        let conn = init_db_connection();
+
+        let post = PostBuilder::new("slug")
+            .title("title1")
+            .timestamp(100)
+            .tags(["tag1", "tag2"])
+            .build();
+        let res = ResourceBuilder::new("res").build();
+        assert!(upsert_post(&conn, &post, std::slice::from_ref(&res)).unwrap());
+
+        let post = PostBuilder::new("slug")
+            .title("title2")
+            .timestamp(200)
+            .category("other")
+            .tags(["tag2", "tag3"])
+            .build();
+        assert!(!upsert_post(&conn, &post, &[res]).unwrap());
+
+        let (selected_post, selected_resources) =
+            get_post_with_resources(&conn, "slug").unwrap().unwrap();
+        assert_eq!(selected_post.title, "title2");
+        assert_eq!(selected_post.create_timestamp, 100);
+        assert_eq!(selected_post.update_timestamp, 200);
+        assert_eq!(selected_post.category, "other");
+        assert_eq!(selected_post.tags, ["tag2", "tag3"]);
+        assert_eq!(selected_resources.len(), 1);
+        assert_eq!(get_post_slugs(&conn).unwrap(), ["slug"]);
+    }
+
+    #[test]
+    fn test_select_one_post_basic() {
+        let conn = init_db_connection();
+
+        let post = PostBuilder::new("slug").tags(["tag1", "tag2"]).build();
+        insert_post(&conn, &post, &[]).unwrap();
+
+        let selected_post = get_post(&conn, "slug").unwrap().unwrap();
+        assert_eq!(post.title, selected_post.title);
+        assert_eq!(post.slug, selected_post.slug);
+        assert_eq!(post.author, selected_post.author);
+        assert_eq!(post.create_timestamp, selected_post.create_timestamp);
+        assert_eq!(post.update_timestamp, selected_post.update_timestamp);
+        assert_eq!(post.category, selected_post.category);
+        assert_eq!(post.tags, selected_post.tags);
+    }
+
+    #[test]
+    fn test_select_one_post_not_exist() {
+        let conn = init_db_connection();
+
+        let selected_post = get_post(&conn, "slug").unwrap();
+        assert!(selected_post.is_none());
+    }
+
+    #[test]
+    fn test_select_many_basic() {
+        let conn = init_db_connection();
+
+        let post1 = PostBuilder::new("slug1")
+            .timestamp(30)
+            .tags(["tag1", "tag2"])
+            .build();
+        insert_post(&conn, &post1, &[]).unwrap();
+
+        let post2 = Post {
+            slug: String::from("slug2"),
+            create_timestamp: 20,
+            update_timestamp: 20,
+            ..post1.clone()
+        };
+        insert_post(&conn, &post2, &[]).unwrap();
+
+        let post3 = Post {
+            slug: String::from("slug3"),
+            create_timestamp: 10,
+            update_timestamp: 10,
+            ..post1
+        };
+        insert_post(&conn, &post3, &[]).unwrap();
+
+        let selected_posts =
+            get_posts(&conn, false, &Pagination::from_page_and_size(2, 1)).unwrap();
+        assert_eq!(selected_posts.objects.len(), 1);
+        assert_eq!(selected_posts.total_count, 3);
+
+        let selected_post = &selected_posts.objects[0];
+        assert_eq!(post2.title, selected_post.title);
+        assert_eq!(post2.slug, selected_post.slug);
+        assert_eq!(post2.author, selected_post.author);
+        assert_eq!(post2.create_timestamp, selected_post.create_timestamp);
+        assert_eq!(post2.update_timestamp, selected_post.update_timestamp);
+        assert_eq!(post2.category, selected_post.category);
+        assert_eq!(post2.tags, selected_post.tags);
+    }
+
+    #[test]
+    fn test_select_many_tags() {
+        let conn = init_db_connection();
+
+        let post_tags = [
+            vec![String::from("tag1"), String::from("tag2")],
+            Vec::new(),
+            vec![String::from("tag2"), String::from("tag3")],
+        ];
+        for (i, tags) in post_tags.iter().enumerate() {
+            let post = PostBuilder::new(format!("slug{}", i))
+                .timestamp(-(i as i64))
+                .tags(tags.clone())
+                .build();
+            insert_post(&conn, &post, &[]).unwrap();
+        }
+
+        let pagination = Pagination::from_page_and_size(1, 10);
+        let selected_posts = get_posts(&conn, false, &pagination).unwrap();
+        let selected_tags: Vec<_> = selected_posts
+            .objects
+            .into_iter()
+            .map(|post| post.tags)
+            .collect();
+        assert_eq!(selected_tags, post_tags);
+    }
+
+    #[test]
+    fn test_select_archive() {
+        let conn = init_db_connection();
+
+        let timestamps = [
+            ("jan31", 1706745599), // 2024-01-31 23:59:59
+            ("feb01", 1706745600), // 2024-02-01 00:00:00
+            ("feb29", 1709208000), // 2024-02-29 12:00:00
+            ("mar01", 1709251200), // 2024-03-01 00:00:00
+        ];
+        for (slug, timestamp) in timestamps {
+            let post = PostBuilder::new(slug)
+                .timestamp(timestamp)
+                .tags(["tag"])
+                .build();
+            insert_post(&conn, &post, &[]).unwrap();
+        }
+
+        let index = get_archive_index(&conn, false, TimeZone::utc()).unwrap();
+        assert_eq!(
+            index,
+            vec![
+                ArchiveEntry {
+                    year: 2024,
+                    month: 3,
+                    posts_count: 1
+                },
+                ArchiveEntry {
+                    year: 2024,
+                    month: 2,
+                    posts_count: 2
+                },
+                ArchiveEntry {
+                    year: 2024,
+                    month: 1,
+                    posts_count: 1
+                },
+            ]
+        );
+
+        let pagination = Pagination::from_page_and_size(1, 10);
+
+        let feb_posts =
+            get_archive_posts(&conn, false, 2024, 2, TimeZone::utc(), &pagination).unwrap();
+        assert_eq!(feb_posts.total_count, 2);
+        let feb_slugs: Vec<_> = feb_posts.objects.iter().map(|p| p.slug.as_str()).collect();
+        assert_eq!(feb_slugs, vec!["feb29", "feb01"]);
+        assert_eq!(feb_posts.objects[0].tags, vec![String::from("tag")]);
+
+        let mar_posts =
+            get_archive_posts(&conn, false, 2024, 3, TimeZone::utc(), &pagination).unwrap();
+        assert_eq!(mar_posts.total_count, 1);
+        assert_eq!(mar_posts.objects[0].slug, "mar01");
+
+        let empty_posts =
+            get_archive_posts(&conn, false, 2023, 6, TimeZone::utc(), &pagination).unwrap();
+        assert_eq!(empty_posts.total_count, 0);
+        assert!(empty_posts.objects.is_empty());
+    }
+
+    #[test]
+    fn test_select_archive_time_zone() {
+        let conn = init_db_connection();
 
-        conn.query_many(SELECT_SQL, (post_slug,), |row| {
-            row.get(0).map_err(From::from)
-        })
-        .unwrap()
+        // 2024-01-31 20:00:00 in UTC, which is 2024-02-01 04:00:00 in UTC+8.
+        let post = PostBuilder::new("slug").timestamp(1706731200).build();
+        insert_post(&conn, &post, &[]).unwrap();
+
+        let utc8 = TimeZone::from_name("Asia/Shanghai").unwrap();
+        let pagination = Pagination::from_page_and_size(1, 10);
+
+        let utc_index = get_archive_index(&conn, false, TimeZone::utc()).unwrap();
+        assert_eq!((utc_index[0].year, utc_index[0].month), (2024, 1));
+        let utc8_index = get_archive_index(&conn, false, utc8).unwrap();
+        assert_eq!((utc8_index[0].year, utc8_index[0].month), (2024, 2));
+
+        let utc_jan_posts =
+            get_archive_posts(&conn, false, 2024, 1, TimeZone::utc(), &pagination).unwrap();
+        assert_eq!(utc_jan_posts.total_count, 1);
+        let utc8_jan_posts = get_archive_posts(&conn, false, 2024, 1, utc8, &pagination).unwrap();
+        assert_eq!(utc8_jan_posts.total_count, 0);
+        let utc8_feb_posts = get_archive_posts(&conn, false, 2024, 2, utc8, &pagination).unwrap();
+        assert_eq!(utc8_feb_posts.total_count, 1);
     }
 
     #[test]
-    fn test_insert_post_basic() {
+    fn test_select_archive_daylight_saving_time() {
         let conn = init_db_connection();
 
-        let post = Post {
-            title: String::from("title"),
-            slug: String::from("slug"),
-            author: String::from("msr"),
-            create_timestamp: 0,
-            update_timestamp: 0,
-            category: String::from("category"),
-            tags: Vec::new(),
-            is_special: false,
-            content: DocumentNode::new_empty(),
-        };
+        // 2024-02-29 23:30:00 in UTC, which is 2024-03-01 00:30:00 in Berlin, in standard time (UTC+1).
+        let post = PostBuilder::new("mar").timestamp(1709249400).build();
         insert_post(&conn, &post, &[]).unwrap();
+        // 2024-03-31 22:30:00 in UTC, which is 2024-04-01 00:30:00 in Berlin, in daylight saving time (UTC+2).
+        let post = PostBuilder::new("apr").timestamp(1711924200).build();
+        insert_post(&conn, &post, &[]).unwrap();
+
+        let berlin = TimeZone::from_name("Europe/Berlin").unwrap();
+        let pagination = Pagination::from_page_and_size(1, 10);
+
+        let index = get_archive_index(&conn, false, berlin).unwrap();
+        let months: Vec<_> = index
+            .iter()
+            .map(|entry| (entry.year, entry.month, entry.posts_count))
+            .collect();
+        assert_eq!(months, [(2024, 4, 1), (2024, 3, 1)]);
+
+        let mar_posts = get_archive_posts(&conn, false, 2024, 3, berlin, &pagination).unwrap();
+        let mar_slugs: Vec<_> = mar_posts.objects.iter().map(|p| p.slug.as_str()).collect();
+        assert_eq!(mar_slugs, ["mar"]);
+        let apr_posts = get_archive_posts(&conn, false, 2024, 4, berlin, &pagination).unwrap();
+        let apr_slugs: Vec<_> = apr_posts.objects.iter().map(|p| p.slug.as_str()).collect();
+        assert_eq!(apr_slugs, ["apr"]);
     }
 
     #[test]
-    fn test_insert_post_conflict_slug() {
+    fn test_select_archive_invalid_month() {
         let conn = init_db_connection();
 
-        let post = Post {
-            title: String::from("title"),
-            slug: String::from("slug"),
-            author: String::from("msr"),
-            create_timestamp: 0,
-            update_timestamp: 0,
-            category: String::from("category"),
-            tags: Vec::new(),
-            is_special: false,
-            content: DocumentNode::new_empty(),
+        let pagination = Pagination::from_page_and_size(1, 10);
+        let posts = get_archive_posts(&conn, false, 2024, 13, TimeZone::utc(), &pagination);
+        assert!(matches!(
+            posts,
+            Err(SqliteStorageError::InvalidMonth(2024, 13))
+        ));
+    }
+
+    #[test]
+    fn test_select_slugs() {
+        let conn = init_db_connection();
+
+        let post1 = PostBuilder::new("slug2").build();
+        insert_post(&conn, &post1, &[]).unwrap();
+
+        let post2 = Post {
+            slug: String::from("slug1"),
+            is_special: true,
+            ..post1.clone()
         };
+        insert_post(&conn, &post2, &[]).unwrap();
+
+        let slugs = get_post_slugs(&conn).unwrap();
+        assert_eq!(slugs, vec![String::from("slug1"), String::from("slug2")]);
+    }
+
+    #[test]
+    fn test_select_many_overflowing_page() {
+        let conn = init_db_connection();
+
+        let post = PostBuilder::new("slug").build();
         insert_post(&conn, &post, &[]).unwrap();
 
-        let insert_res = insert_post(&conn, &post, &[]);
-        assert!(insert_res.is_err());
+        let paginations = [
+            Pagination::from_page_and_size(usize::MAX, crate::storage::MAX_PAGE_SIZE),
+            Pagination::from_page_and_size(usize::MAX, 2),
+            Pagination::from_page_and_size(usize::MAX / 2, 2),
+        ];
+        for pagination in &paginations {
+            let selected_posts = get_posts(&conn, false, pagination).unwrap();
+            assert!(selected_posts.objects.is_empty());
+            assert_eq!(selected_posts.total_count, 1);
+        }
     }
 
     #[test]
-    fn test_insert_post_tags() {
+    fn test_select_many_page_too_large() {
         let conn = init_db_connection();
 
-        let post = Post {
-            title: String::from("title"),
-            slug: String::from("slug"),
-            author: String::from("msr"),
-            create_timestamp: 0,
-            update_timestamp: 0,
-            category: String::from("category"),
-            tags: vec![String::from("tag1"), String::from("tag2")],
-            is_special: false,
-            content: DocumentNode::new_empty(),
-        };
+        let page_size = crate::storage::MAX_PAGE_SIZE + 1;
+        let pagination = Pagination::from_page_and_size(1, page_size);
+
+        let err = get_posts(&conn, false, &pagination).unwrap_err();
+        assert!(matches!(err, SqliteStorageError::PageTooLarge(size) if size == page_size));
+
+        let err = get_uncategorized_posts(&conn, &pagination).unwrap_err();
+        assert!(matches!(err, SqliteStorageError::PageTooLarge(_)));
+
+        let pagination = Pagination::from_page_and_size(1, crate::storage::MAX_PAGE_SIZE);
+        assert!(get_posts(&conn, false, &pagination).is_ok());
+    }
+
+    fn create_content(words: usize) -> DocumentNode {
+        let mut paragraph = DocumentNode::new(ublog_doc::DocumentNodeTag::Paragraph);
+        paragraph
+            .children
+            .push(DocumentNode::new(ublog_doc::DocumentNodeTag::InlineText {
+                text: vec!["word"; words].join(" "),
+            }));
+
+        let mut content = DocumentNode::new_empty();
+        content.children.push(paragraph);
+        content
+    }
+
+    #[test]
+    fn test_select_longform() {
+        let conn = init_db_connection();
+
+        for (idx, words) in [10, 1000, 0, 500].into_iter().enumerate() {
+            let post = PostBuilder::new(format!("post{}", idx))
+                .timestamp(idx as i64)
+                .content(create_content(words))
+                .build();
+            insert_post(&conn, &post, &[]).unwrap();
+        }
+
+        let pagination = Pagination::from_page_and_size(1, 10);
+        let posts = get_longform_posts(&conn, 500, &pagination).unwrap();
+        assert_eq!(posts.total_count, 2);
+        let slugs: Vec<_> = posts.objects.iter().map(|p| p.slug.as_str()).collect();
+        assert_eq!(slugs, ["post3", "post1"]);
+
+        let posts = get_longform_posts(&conn, 0, &pagination).unwrap();
+        assert_eq!(posts.total_count, 4);
+    }
+
+    #[test]
+    fn test_upsert_post_word_count() {
+        let conn = init_db_connection();
+        let pagination = Pagination::from_page_and_size(1, 10);
+
+        let post = PostBuilder::new("post").content(create_content(10)).build();
         insert_post(&conn, &post, &[]).unwrap();
+        assert_eq!(
+            get_longform_posts(&conn, 100, &pagination)
+                .unwrap()
+                .total_count,
+            0
+        );
 
-        let tags: HashSet<_> = select_tag_names(&conn, &post.slug).into_iter().collect();
-        let expected_tags: HashSet<_> = vec![String::from("tag1"), String::from("tag2")]
+        let post = PostBuilder::new("post")
+            .content(create_content(100))
+            .build();
+        upsert_post(&conn, &post, &[]).unwrap();
+        assert_eq!(
+            get_longform_posts(&conn, 100, &pagination)
+                .unwrap()
+                .total_count,
+            1
+        );
+    }
+
+    #[test]
+    fn test_select_of_kind() {
+        let conn = init_db_connection();
+
+        let posts = [
+            PostBuilder::new("article1").timestamp(1).build(),
+            PostBuilder::new("link")
+                .timestamp(2)
+                .kind(PostKind::Link)
+                .link_url("https://example.com/")
+                .build(),
+            PostBuilder::new("note")
+                .title("")
+                .timestamp(3)
+                .kind(PostKind::Note)
+                .build(),
+            PostBuilder::new("article2").timestamp(4).build(),
+        ];
+        for post in &posts {
+            insert_post(&conn, post, &[]).unwrap();
+        }
+
+        let pagination = Pagination::from_page_and_size(1, 10);
+        let articles = get_posts_of_kind(&conn, false, PostKind::Article, &pagination).unwrap();
+        assert_eq!(articles.total_count, 2);
+        let article_slugs: Vec<_> = articles.objects.iter().map(|p| p.slug.as_str()).collect();
+        assert_eq!(article_slugs, ["article2", "article1"]);
+
+        let links = get_posts_of_kind(&conn, false, PostKind::Link, &pagination).unwrap();
+        assert_eq!(links.total_count, 1);
+        assert_eq!(links.objects[0].kind, PostKind::Link);
+        assert_eq!(
+            links.objects[0].link_url.as_deref(),
+            Some("https://example.com/")
+        );
+
+        let all_posts = get_posts(&conn, false, &pagination).unwrap();
+        let kinds: Vec<_> = all_posts.objects.iter().map(|p| p.kind).collect();
+        assert_eq!(
+            kinds,
+            [
+                PostKind::Article,
+                PostKind::Note,
+                PostKind::Link,
+                PostKind::Article
+            ]
+        );
+    }
+
+    #[test]
+    fn test_select_uncategorized() {
+        let conn = init_db_connection();
+
+        let categories = ["category", "", "category", ""];
+        for (i, category) in categories.into_iter().enumerate() {
+            let post = PostBuilder::new(format!("slug{}", i))
+                .title(format!("title{}", i))
+                .timestamp(i as i64)
+                .category(category)
+                .special(i == 3)
+                .build();
+            insert_post(&conn, &post, &[]).unwrap();
+        }
+
+        let pagination = Pagination::from_page_and_size(1, 10);
+        let selected_posts = get_uncategorized_posts(&conn, &pagination).unwrap();
+        assert_eq!(selected_posts.total_count, 2);
+
+        let selected_slugs: Vec<_> = selected_posts
+            .objects
+            .iter()
+            .map(|post| post.slug.as_str())
+            .collect();
+        assert_eq!(selected_slugs, ["slug3", "slug1"]);
+    }
+
+    fn insert_tagged_posts(conn: &Connection) {
+        let post_tags: [&[&str]; 4] = [
+            &["rust", "async"],
+            &["rust"],
+            &["async", "web"],
+            &["rust", "async", "web"],
+        ];
+        for (i, tags) in post_tags.into_iter().enumerate() {
+            let post = PostBuilder::new(format!("slug{}", i))
+                .timestamp(i as i64)
+                .tags(tags.iter().copied())
+                .build();
+            insert_post(conn, &post, &[]).unwrap();
+        }
+    }
+
+    fn select_slugs_by_tags(conn: &Connection, tags: &[&str], mode: TagMatch) -> Vec<String> {
+        let tags: Vec<_> = tags.iter().map(|t| String::from(*t)).collect();
+        let pagination = Pagination::from_page_and_size(1, 10);
+        let selected_posts = get_posts_by_tags(conn, &tags, mode, &pagination).unwrap();
+        assert_eq!(selected_posts.total_count, selected_posts.objects.len());
+
+        selected_posts
+            .objects
             .into_iter()
+            .map(|post| post.slug)
+            .collect()
+    }
+
+    #[test]
+    fn test_select_by_tags_all() {
+        let conn = init_db_connection();
+        insert_tagged_posts(&conn);
+
+        assert_eq!(
+            select_slugs_by_tags(&conn, &["rust", "async"], TagMatch::All),
+            ["slug3", "slug0"]
+        );
+        assert_eq!(
+            select_slugs_by_tags(&conn, &["rust", "async", "web"], TagMatch::All),
+            ["slug3"]
+        );
+        assert_eq!(
+            select_slugs_by_tags(&conn, &["rust", "rust"], TagMatch::All),
+            ["slug3", "slug1", "slug0"]
+        );
+        assert!(select_slugs_by_tags(&conn, &["rust", "missing"], TagMatch::All).is_empty());
+    }
+
+    #[test]
+    fn test_select_by_tags_any() {
+        let conn = init_db_connection();
+        insert_tagged_posts(&conn);
+
+        assert_eq!(
+            select_slugs_by_tags(&conn, &["rust", "web"], TagMatch::Any),
+            ["slug3", "slug2", "slug1", "slug0"]
+        );
+        assert_eq!(
+            select_slugs_by_tags(&conn, &["web", "missing"], TagMatch::Any),
+            ["slug3", "slug2"]
+        );
+        assert!(select_slugs_by_tags(&conn, &[], TagMatch::Any).is_empty());
+    }
+
+    #[test]
+    fn test_select_by_tags_hydrates_tags() {
+        let conn = init_db_connection();
+        insert_tagged_posts(&conn);
+
+        let tags = [String::from("web")];
+        let pagination = Pagination::from_page_and_size(1, 1);
+        let selected_posts = get_posts_by_tags(&conn, &tags, TagMatch::Any, &pagination).unwrap();
+        assert_eq!(selected_posts.total_count, 2);
+        assert_eq!(selected_posts.objects.len(), 1);
+        assert_eq!(selected_posts.objects[0].tags, ["rust", "async", "web"]);
+    }
+
+    #[test]
+    fn test_suggest_tags() {
+        let conn = init_db_connection();
+
+        let post_tags: [&[&str]; 5] = [
+            &["rust", "async", "tokio"],
+            &["rust", "async", "tokio"],
+            &["rust", "async", "web"],
+            &["rust", "cli"],
+            &["python", "web"],
+        ];
+        for (i, tags) in post_tags.into_iter().enumerate() {
+            let post = PostBuilder::new(format!("slug{}", i))
+                .timestamp(i as i64)
+                .tags(tags.iter().copied())
+                .build();
+            insert_post(&conn, &post, &[]).unwrap();
+        }
+
+        let selected = [String::from("rust")];
+        let suggestions = suggest_tags(&conn, &selected, 10).unwrap();
+        assert_eq!(
+            suggestions,
+            [
+                (String::from("async"), 3),
+                (String::from("tokio"), 2),
+                (String::from("cli"), 1),
+                (String::from("web"), 1),
+            ]
+        );
+
+        let selected = [String::from("rust"), String::from("async")];
+        let suggestions = suggest_tags(&conn, &selected, 1).unwrap();
+        assert_eq!(suggestions, [(String::from("tokio"), 4)]);
+
+        assert!(suggest_tags(&conn, &[], 10).unwrap().is_empty());
+        assert!(suggest_tags(&conn, &[String::from("missing")], 10)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(get_edit_distance("", ""), 0);
+        assert_eq!(get_edit_distance("abc", ""), 3);
+        assert_eq!(get_edit_distance("", "abc"), 3);
+        assert_eq!(get_edit_distance("kitten", "sitting"), 3);
+        assert_eq!(get_edit_distance("博客系统", "博客"), 2);
+    }
+
+    #[test]
+    fn test_select_similar_titles() {
+        let conn = init_db_connection();
+
+        let titles = [
+            ("slug1", "Getting Started"),
+            ("slug2", "Getting Startde"),
+            ("slug3", "Getting started!"),
+            ("slug4", "Notes on Rust Async"),
+        ];
+        for (i, (slug, title)) in titles.into_iter().enumerate() {
+            let post = PostBuilder::new(slug)
+                .title(title)
+                .timestamp(i as i64)
+                .tags(["tag"])
+                .build();
+            insert_post(&conn, &post, &[]).unwrap();
+        }
+
+        let selected_posts = get_posts_with_similar_title(&conn, "getting started", 1).unwrap();
+        let selected_slugs: Vec<_> = selected_posts
+            .iter()
+            .map(|post| post.slug.as_str())
             .collect();
-        assert_eq!(tags, expected_tags);
+        assert_eq!(selected_slugs, ["slug1", "slug3"]);
+        assert_eq!(selected_posts[0].tags, ["tag"]);
+
+        let selected_posts = get_posts_with_similar_title(&conn, "Getting Started", 2).unwrap();
+        let selected_slugs: Vec<_> = selected_posts
+            .iter()
+            .map(|post| post.slug.as_str())
+            .collect();
+        assert_eq!(selected_slugs, ["slug1", "slug3", "slug2"]);
     }
 
     #[test]
-    fn test_select_one_post_basic() {
+    fn test_select_similar_slugs() {
         let conn = init_db_connection();
 
-        let post = Post {
-            title: String::from("title"),
-            slug: String::from("slug"),
-            author: String::from("msr"),
-            create_timestamp: 0,
-            update_timestamp: 0,
-            category: String::from("category"),
-            tags: vec![String::from("tag1"), String::from("tag2")],
-            is_special: false,
-            content: DocumentNode::new_empty(),
-        };
-        insert_post(&conn, &post, &[]).unwrap();
+        let slugs = ["hello-world", "hello-words", "rust-async-notes"];
+        for (i, slug) in slugs.into_iter().enumerate() {
+            let post = PostBuilder::new(slug)
+                .title(format!("title{}", i))
+                .timestamp(i as i64)
+                .build();
+            insert_post(&conn, &post, &[]).unwrap();
+        }
 
-        let selected_post = get_post(&conn, "slug").unwrap().unwrap();
-        assert_eq!(post.title, selected_post.title);
-        assert_eq!(post.slug, selected_post.slug);
-        assert_eq!(post.author, selected_post.author);
-        assert_eq!(post.create_timestamp, selected_post.create_timestamp);
-        assert_eq!(post.update_timestamp, selected_post.update_timestamp);
-        assert_eq!(post.category, selected_post.category);
-        assert_eq!(post.tags, selected_post.tags);
+        let suggestions = get_similar_slugs(&conn, "Helo-world", 10).unwrap();
+        assert_eq!(
+            suggestions,
+            [SlugSuggestion {
+                slug: String::from("hello-world"),
+                title: String::from("title0"),
+            }]
+        );
+
+        let suggestions = get_similar_slugs(&conn, "hello-word", 10).unwrap();
+        let suggested_slugs: Vec<_> = suggestions.iter().map(|s| s.slug.as_str()).collect();
+        assert_eq!(suggested_slugs, ["hello-words", "hello-world"]);
+
+        let suggestions = get_similar_slugs(&conn, "hello-world", 1).unwrap();
+        assert_eq!(suggestions.len(), 1);
+        assert_eq!(suggestions[0].slug, "hello-world");
+
+        assert!(get_similar_slugs(&conn, "unrelated-slug", 10)
+            .unwrap()
+            .is_empty());
     }
 
     #[test]
-    fn test_select_one_post_not_exist() {
+    fn test_select_latest_oldest_empty() {
         let conn = init_db_connection();
 
-        let selected_post = get_post(&conn, "slug").unwrap();
-        assert!(selected_post.is_none());
+        assert!(get_latest_post(&conn, false).unwrap().is_none());
+        assert!(get_oldest_post(&conn, false).unwrap().is_none());
     }
 
     #[test]
-    fn test_select_many_basic() {
+    fn test_select_latest_oldest_basic() {
         let conn = init_db_connection();
 
-        let post1 = Post {
-            title: String::from("title"),
-            slug: String::from("slug1"),
-            author: String::from("msr"),
+        let post1 = PostBuilder::new("slug1")
+            .timestamp(20)
+            .tags(["tag1"])
+            .build();
+        insert_post(&conn, &post1, &[]).unwrap();
+
+        let post2 = Post {
+            slug: String::from("slug2"),
             create_timestamp: 30,
             update_timestamp: 30,
-            category: String::from("category"),
-            tags: vec![String::from("tag1"), String::from("tag2")],
-            is_special: false,
-            content: DocumentNode::new_empty(),
+            tags: vec![String::from("tag2")],
+            ..post1.clone()
+        };
+        insert_post(&conn, &post2, &[]).unwrap();
+
+        let post3 = Post {
+            slug: String::from("slug3"),
+            create_timestamp: 10,
+            update_timestamp: 10,
+            tags: vec![String::from("tag3")],
+            ..post1.clone()
+        };
+        insert_post(&conn, &post3, &[]).unwrap();
+
+        let special_post = Post {
+            slug: String::from("about"),
+            create_timestamp: 40,
+            update_timestamp: 40,
+            is_special: true,
+            ..post1.clone()
         };
+        insert_post(&conn, &special_post, &[]).unwrap();
+
+        let latest = get_latest_post(&conn, false).unwrap().unwrap();
+        assert_eq!(latest.slug, post2.slug);
+        assert_eq!(latest.tags, post2.tags);
+
+        let oldest = get_oldest_post(&conn, false).unwrap().unwrap();
+        assert_eq!(oldest.slug, post3.slug);
+        assert_eq!(oldest.tags, post3.tags);
+    }
+
+    #[test]
+    fn test_select_by_slugs_order() {
+        let conn = init_db_connection();
+
+        let post1 = PostBuilder::new("slug1")
+            .timestamp(10)
+            .tags(["tag1", "tag2"])
+            .build();
         insert_post(&conn, &post1, &[]).unwrap();
 
         let post2 = Post {
             slug: String::from("slug2"),
             create_timestamp: 20,
             update_timestamp: 20,
+            tags: vec![String::from("tag3")],
             ..post1.clone()
         };
         insert_post(&conn, &post2, &[]).unwrap();
 
         let post3 = Post {
             slug: String::from("slug3"),
-            create_timestamp: 10,
-            update_timestamp: 10,
-            ..post1
+            create_timestamp: 30,
+            update_timestamp: 30,
+            tags: Vec::new(),
+            ..post1.clone()
         };
         insert_post(&conn, &post3, &[]).unwrap();
 
-        let selected_posts =
-            get_posts(&conn, false, &Pagination::from_page_and_size(2, 1)).unwrap();
-        assert_eq!(selected_posts.objects.len(), 1);
-        assert_eq!(selected_posts.total_count, 3);
+        let slugs = vec![
+            String::from("slug2"),
+            String::from("slug3"),
+            String::from("slug1"),
+        ];
+        let selected = get_posts_by_slugs(&conn, &slugs, false).unwrap();
+        let selected_slugs: Vec<_> = selected.posts.iter().map(|p| p.slug.as_str()).collect();
+        assert_eq!(selected_slugs, vec!["slug2", "slug3", "slug1"]);
+        assert_eq!(selected.posts[0].tags, post2.tags);
+        assert_eq!(selected.posts[1].tags, post3.tags);
+        assert_eq!(selected.posts[2].tags, post1.tags);
+        assert!(selected.missing_slugs.is_empty());
+    }
 
-        let selected_post = &selected_posts.objects[0];
-        assert_eq!(post2.title, selected_post.title);
-        assert_eq!(post2.slug, selected_post.slug);
-        assert_eq!(post2.author, selected_post.author);
-        assert_eq!(post2.create_timestamp, selected_post.create_timestamp);
-        assert_eq!(post2.update_timestamp, selected_post.update_timestamp);
-        assert_eq!(post2.category, selected_post.category);
-        assert_eq!(post2.tags, selected_post.tags);
+    #[test]
+    fn test_select_by_slugs_duplicates_and_missing() {
+        let conn = init_db_connection();
+
+        let post = PostBuilder::new("slug").build();
+        insert_post(&conn, &post, &[]).unwrap();
+
+        let slugs = vec![
+            String::from("missing1"),
+            String::from("slug"),
+            String::from("missing2"),
+            String::from("slug"),
+            String::from("missing1"),
+        ];
+        let selected = get_posts_by_slugs(&conn, &slugs, true).unwrap();
+        assert_eq!(selected.posts.len(), 1);
+        assert_eq!(selected.posts[0].slug, "slug");
+        assert_eq!(
+            selected.missing_slugs,
+            vec![String::from("missing1"), String::from("missing2")]
+        );
+    }
+
+    #[test]
+    fn test_select_by_slugs_too_many() {
+        let conn = init_db_connection();
+
+        let slugs: Vec<_> = (0..=MAX_SELECT_SLUGS)
+            .map(|i| format!("slug{}", i))
+            .collect();
+        let selected = get_posts_by_slugs(&conn, &slugs, false);
+        assert!(matches!(
+            selected,
+            Err(SqliteStorageError::TooManySlugs(count)) if count == MAX_SELECT_SLUGS + 1
+        ));
     }
 
     #[test]
     fn test_delete_basic() {
         let conn = init_db_connection();
 
-        let post = Post {
-            title: String::from("title"),
-            slug: String::from("slug"),
-            author: String::from("msr"),
-            create_timestamp: 0,
-            update_timestamp: 0,
-            category: String::from("category"),
-            tags: Vec::new(),
-            is_special: false,
-            content: DocumentNode::new_empty(),
-        };
+        let post = PostBuilder::new("slug").build();
         insert_post(&conn, &post, &[]).unwrap();
 
         delete_post(&conn, "slug").unwrap();
@@ -479,8 +2138,145 @@ mod tests {
     }
 
     #[test]
-    fn test_delete_not_exist() {
+    fn test_delete_returns_deleted_post() {
+        let conn = init_db_connection();
+
+        let post = PostBuilder::new("slug")
+            .create_timestamp(10)
+            .update_timestamp(20)
+            .tags(["tag1", "tag2"])
+            .build();
+        insert_post(&conn, &post, &[]).unwrap();
+
+        let deleted_post = delete_post(&conn, "slug").unwrap().unwrap();
+        assert_eq!(post.title, deleted_post.title);
+        assert_eq!(post.slug, deleted_post.slug);
+        assert_eq!(post.author, deleted_post.author);
+        assert_eq!(post.create_timestamp, deleted_post.create_timestamp);
+        assert_eq!(post.update_timestamp, deleted_post.update_timestamp);
+        assert_eq!(post.category, deleted_post.category);
+        assert_eq!(post.tags, deleted_post.tags);
+    }
+
+    #[test]
+    fn test_delete_removes_dependent_rows() {
         let conn = init_db_connection();
+
+        let post = PostBuilder::new("slug").tags(["tag1", "tag2"]).build();
+        let resources = vec![
+            Resource {
+                id: Uuid::new_v4(),
+                name: String::from("res1"),
+                ty: String::from("image/png"),
+                data: vec![0, 1, 2, 3],
+            },
+            Resource {
+                id: Uuid::new_v4(),
+                name: String::from("res2"),
+                ty: String::from("image/png"),
+                data: vec![4, 5, 6, 7],
+            },
+        ];
+        insert_post(&conn, &post, &resources).unwrap();
+
         delete_post(&conn, "slug").unwrap();
+
+        const COUNT_SQL: [&str; 3] = [
+            "SELECT count(*) FROM posts_tags WHERE post_slug == 'slug';",
+            "SELECT count(*) FROM posts_resources WHERE post_slug == 'slug';",
+            "SELECT count(*) FROM resources;",
+        ];
+        for sql in COUNT_SQL {
+            let count: usize = conn
+                .query_one(sql, (), |row| row.get(0).map_err(From::from))
+                .unwrap()
+                .unwrap();
+            assert_eq!(count, 0, "{}", sql);
+        }
+    }
+
+    #[test]
+    fn test_delete_post_resources() {
+        let conn = init_db_connection();
+
+        let post = PostBuilder::new("slug").build();
+        let resources: Vec<_> = (1..=3)
+            .map(|i| Resource {
+                id: Uuid::new_v4(),
+                name: format!("res{}", i),
+                ty: String::from("image/png"),
+                data: vec![i],
+            })
+            .collect();
+        insert_post(&conn, &post, &resources).unwrap();
+
+        let mut deleted_ids = delete_post_resources(&conn, "slug").unwrap();
+        deleted_ids.sort();
+        let mut expected_ids: Vec<_> = resources.iter().map(|res| res.id).collect();
+        expected_ids.sort();
+        assert_eq!(deleted_ids, expected_ids);
+
+        let (selected_post, selected_resources) =
+            get_post_with_resources(&conn, "slug").unwrap().unwrap();
+        assert_eq!(selected_post.slug, "slug");
+        assert!(selected_resources.is_empty());
+
+        let deleted_ids = delete_post_resources(&conn, "slug").unwrap();
+        assert!(deleted_ids.is_empty());
+    }
+
+    #[test]
+    fn test_orphaned_post_resources() {
+        let conn = init_db_connection();
+
+        let post = PostBuilder::new("slug").build();
+        let resource = Resource {
+            id: Uuid::new_v4(),
+            name: String::from("res"),
+            ty: String::from("image/png"),
+            data: vec![0, 1, 2, 3],
+        };
+        insert_post(&conn, &post, std::slice::from_ref(&resource)).unwrap();
+
+        // Leave a relation and its resource behind for a post that does not exist, which the foreign key on the relation
+        // would otherwise reject.
+        let orphan_id = Uuid::new_v4();
+        let orphan_id_str = format!("{}", orphan_id.as_hyphenated());
+        conn.execute_batch("PRAGMA foreign_keys = OFF;").unwrap();
+        conn.execute(
+            "INSERT INTO resources (id, name, ty, data) VALUES (?, 'orphan', 'image/png', x'00');",
+            (&orphan_id_str,),
+        )
+        .unwrap();
+        conn.execute(
+            "INSERT INTO posts_resources (post_slug, res_id) VALUES ('missing', ?);",
+            (&orphan_id_str,),
+        )
+        .unwrap();
+        conn.execute_batch("PRAGMA foreign_keys = ON;").unwrap();
+
+        let orphans = get_orphaned_post_resources(&conn).unwrap();
+        assert_eq!(orphans, [(String::from("missing"), orphan_id)]);
+
+        let deleted_ids = delete_orphaned_post_resources(&conn).unwrap();
+        assert_eq!(deleted_ids, [orphan_id]);
+
+        assert!(get_orphaned_post_resources(&conn).unwrap().is_empty());
+        assert!(
+            crate::storage::sqlite::resource::get_resource(&conn, &orphan_id, false)
+                .unwrap()
+                .is_none()
+        );
+
+        let (_, selected_resources) = get_post_with_resources(&conn, "slug").unwrap().unwrap();
+        assert_eq!(selected_resources.len(), 1);
+        assert_eq!(selected_resources[0].id, resource.id);
+    }
+
+    #[test]
+    fn test_delete_not_exist() {
+        let conn = init_db_connection();
+        let deleted_post = delete_post(&conn, "slug").unwrap();
+        assert!(deleted_post.is_none());
     }
 }
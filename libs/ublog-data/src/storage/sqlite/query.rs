@@ -0,0 +1,128 @@
+/// Build an `INSERT` statement that inserts `rows` rows of values into the given columns of the given table.
+///
+/// Every value is bound through an anonymous `?` placeholder, in row-major order.
+pub(crate) fn insert_statement(table: &str, columns: &[&str], rows: usize) -> String {
+    build_insert_statement("INSERT", table, columns, rows)
+}
+
+/// Build an `INSERT OR IGNORE` statement that inserts `rows` rows of values into the given columns of the given table,
+/// skipping the rows that violate a uniqueness constraint.
+///
+/// Every value is bound through an anonymous `?` placeholder, in row-major order.
+pub(crate) fn insert_or_ignore_statement(table: &str, columns: &[&str], rows: usize) -> String {
+    build_insert_statement("INSERT OR IGNORE", table, columns, rows)
+}
+
+fn build_insert_statement(verb: &str, table: &str, columns: &[&str], rows: usize) -> String {
+    assert!(!columns.is_empty());
+    assert!(rows > 0);
+
+    let row = format!("({})", placeholders(columns.len()));
+    format!(
+        "{} INTO {} ({}) VALUES {};",
+        verb,
+        table,
+        columns.join(", "),
+        vec![row; rows].join(", ")
+    )
+}
+
+/// Build an `IN` expression that tests whether the value of the given column equals any of the values in a JSON array.
+///
+/// The array is bound through a single anonymous `?` placeholder and is built by [`json_array`]. Unlike a list of
+/// placeholders, the expression does not depend on the number of values, so statements built with it are prepared
+/// once and then served from the statement cache.
+pub(crate) fn in_array_expression(column: &str) -> String {
+    format!("{} IN (SELECT value FROM json_each(?))", column)
+}
+
+/// Build the JSON array of the given values, to be bound to the placeholder of an [`in_array_expression`].
+pub(crate) fn json_array<I, T>(values: I) -> String
+where
+    I: IntoIterator<Item = T>,
+    T: AsRef<str>,
+{
+    let values: Vec<_> = values
+        .into_iter()
+        .map(|v| serde_json::Value::from(v.as_ref()))
+        .collect();
+    serde_json::Value::Array(values).to_string()
+}
+
+fn placeholders(count: usize) -> String {
+    vec!["?"; count].join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_insert_statement_single_row() {
+        let sql = insert_statement("resources", &["id", "name", "ty", "data"], 1);
+        assert_eq!(
+            sql,
+            "INSERT INTO resources (id, name, ty, data) VALUES (?, ?, ?, ?);"
+        );
+    }
+
+    #[test]
+    fn test_insert_statement_many_rows() {
+        let sql = insert_statement("posts_tags", &["post_slug", "tag_name"], 3);
+        assert_eq!(
+            sql,
+            "INSERT INTO posts_tags (post_slug, tag_name) VALUES (?, ?), (?, ?), (?, ?);"
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_insert_statement_no_rows() {
+        insert_statement("posts_tags", &["post_slug", "tag_name"], 0);
+    }
+
+    #[test]
+    fn test_insert_or_ignore_statement() {
+        let sql = insert_or_ignore_statement("posts_tags", &["post_slug", "tag_name"], 2);
+        assert_eq!(
+            sql,
+            "INSERT OR IGNORE INTO posts_tags (post_slug, tag_name) VALUES (?, ?), (?, ?);"
+        );
+    }
+
+    #[test]
+    fn test_in_array_expression() {
+        assert_eq!(
+            in_array_expression("slug"),
+            "slug IN (SELECT value FROM json_each(?))"
+        );
+    }
+
+    #[test]
+    fn test_in_array_expression_matches() {
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            r#"
+                CREATE TABLE t (name TEXT NOT NULL);
+                INSERT INTO t (name) VALUES ('a'), ('b"'), ('c'), ('d');
+            "#,
+        )
+        .unwrap();
+
+        let select_sql = format!(
+            "SELECT name FROM t WHERE {} ORDER BY name;",
+            in_array_expression("name")
+        );
+        let mut stmt = conn.prepare(&select_sql).unwrap();
+        let mut select_names = |names: &[&str]| -> Vec<String> {
+            stmt.query_map((json_array(names),), |row| row.get(0))
+                .unwrap()
+                .collect::<Result<_, _>>()
+                .unwrap()
+        };
+
+        assert_eq!(select_names(&["a", "b\"", "missing"]), ["a", "b\""]);
+        assert_eq!(select_names(&["d"]), ["d"]);
+        assert!(select_names(&[]).is_empty());
+    }
+}
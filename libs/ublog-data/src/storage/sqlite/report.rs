@@ -0,0 +1,258 @@
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
+
+use rusqlite::Connection;
+use ublog_doc::DocumentNode;
+use uuid::Uuid;
+
+use crate::storage::sqlite::{SqliteExt, SqliteStorageError};
+
+/// Report of the space taken by a sqlite database and by the resources within it.
+#[derive(Clone, Debug)]
+pub struct StorageReport {
+    /// Resource usage of each post, from the post with the largest resources to the one with the smallest.
+    pub posts: Vec<PostStorageReport>,
+
+    /// Resources that are not attached to any post.
+    pub unattached_resources: Vec<ResourceUsage>,
+
+    /// Total number of resources in the database.
+    pub resources_count: usize,
+
+    /// Total size of the data of all resources in the database, in bytes.
+    pub resources_bytes: u64,
+
+    /// Size of the database file, in bytes.
+    pub file_size: u64,
+
+    /// Number of unused pages within the database file.
+    pub freelist_pages: u64,
+
+    /// Size of a database page, in bytes.
+    pub page_size: u64,
+}
+
+/// Resource usage of a single post.
+#[derive(Clone, Debug)]
+pub struct PostStorageReport {
+    /// Slug of the post.
+    pub post_slug: String,
+
+    /// Resources attached to the post, from the largest one to the smallest one.
+    pub resources: Vec<ResourceUsage>,
+
+    /// Total size of the data of the resources attached to the post, in bytes.
+    pub resources_bytes: u64,
+}
+
+impl PostStorageReport {
+    /// Get the largest resource attached to the post.
+    pub fn largest_resource(&self) -> Option<&ResourceUsage> {
+        self.resources.first()
+    }
+}
+
+/// Usage of a single resource.
+#[derive(Clone, Debug)]
+pub struct ResourceUsage {
+    /// ID of the resource.
+    pub id: Uuid,
+
+    /// Name of the resource.
+    pub name: String,
+
+    /// Size of the resource data, in bytes.
+    pub size: u64,
+
+    /// Whether the resource is referenced by the content of the post it is attached to.
+    pub is_referenced: bool,
+}
+
+pub(super) fn get_storage_report(conn: &Connection) -> Result<StorageReport, SqliteStorageError> {
+    const SELECT_POSTS_SQL: &str = r#"
+        SELECT slug, content
+        FROM posts;
+    "#;
+
+    // The length of the resource data is computed by sqlite so that the data itself is never loaded.
+    const SELECT_RES_SQL: &str = r#"
+        SELECT resources.id, resources.name, length(resources.data), posts_resources.post_slug
+        FROM resources LEFT JOIN posts_resources ON resources.id == posts_resources.res_id;
+    "#;
+
+    let posts = conn.query_many(SELECT_POSTS_SQL, (), |row| {
+        let slug: String = row.get("slug")?;
+        let content_data: Vec<u8> = row.get("content")?;
+        let content: DocumentNode = bson::from_slice(&content_data)?;
+        let referenced_ids: HashSet<Uuid> = content
+            .embedded_resources()
+            .iter()
+            .filter_map(|uuid| Uuid::try_parse(uuid).ok())
+            .collect();
+        Ok((slug, referenced_ids))
+    })?;
+
+    let resources = conn.query_many(SELECT_RES_SQL, (), |row| {
+        let id_str: String = row.get(0)?;
+        let id = Uuid::try_parse(&id_str)?;
+        let usage = ResourceUsage {
+            id,
+            name: row.get(1)?,
+            size: row.get(2)?,
+            is_referenced: false,
+        };
+        let post_slug: Option<String> = row.get(3)?;
+        Ok((usage, post_slug))
+    })?;
+
+    let resources_count = resources
+        .iter()
+        .map(|(usage, _)| usage.id)
+        .collect::<HashSet<_>>()
+        .len();
+    let resources_bytes = resources
+        .iter()
+        .map(|(usage, _)| (usage.id, usage.size))
+        .collect::<HashMap<_, _>>()
+        .values()
+        .sum();
+
+    let mut post_resources: HashMap<String, Vec<ResourceUsage>> = HashMap::new();
+    let mut unattached_resources = Vec::new();
+    for (usage, post_slug) in resources {
+        match post_slug {
+            Some(post_slug) => post_resources.entry(post_slug).or_default().push(usage),
+            None => unattached_resources.push(usage),
+        }
+    }
+
+    let mut post_reports: Vec<_> = posts
+        .into_iter()
+        .map(|(post_slug, referenced_ids)| {
+            let mut resources = post_resources.remove(&post_slug).unwrap_or_default();
+            for res in &mut resources {
+                res.is_referenced = referenced_ids.contains(&res.id);
+            }
+            resources.sort_by_key(|res| Reverse(res.size));
+
+            let resources_bytes = resources.iter().map(|res| res.size).sum();
+            PostStorageReport {
+                post_slug,
+                resources,
+                resources_bytes,
+            }
+        })
+        .collect();
+    post_reports.sort_by(|lhs, rhs| {
+        rhs.resources_bytes
+            .cmp(&lhs.resources_bytes)
+            .then_with(|| lhs.post_slug.cmp(&rhs.post_slug))
+    });
+    unattached_resources.sort_by_key(|res| Reverse(res.size));
+
+    let page_size: u64 = conn.pragma_query_value(None, "page_size", |row| row.get(0))?;
+    let page_count: u64 = conn.pragma_query_value(None, "page_count", |row| row.get(0))?;
+    let freelist_pages: u64 = conn.pragma_query_value(None, "freelist_count", |row| row.get(0))?;
+
+    Ok(StorageReport {
+        posts: post_reports,
+        unattached_resources,
+        resources_count,
+        resources_bytes,
+        file_size: page_size * page_count,
+        freelist_pages,
+        page_size,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use ublog_doc::{DocumentNodeTag, DocumentResourceLink};
+
+    use crate::clock::SystemClock;
+    use crate::fixtures::{PostBuilder, ResourceBuilder};
+    use crate::models::{Post, Resource};
+
+    use super::*;
+
+    fn init_db_connection() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        crate::storage::sqlite::init_db_schema(&conn, &SystemClock).unwrap();
+        conn
+    }
+
+    fn create_resource(name: &str, size: usize) -> Resource {
+        ResourceBuilder::new(name).data(vec![0; size]).build()
+    }
+
+    fn create_post(slug: &str, referenced_resources: &[&Resource]) -> Post {
+        let mut content = DocumentNode::new_empty();
+        for res in referenced_resources {
+            content
+                .children
+                .push(DocumentNode::new(DocumentNodeTag::Image {
+                    link: DocumentResourceLink::Embedded {
+                        uuid: format!("{}", res.id.as_hyphenated()),
+                    },
+                    caption: None,
+                }));
+        }
+
+        PostBuilder::new(slug).content(content).build()
+    }
+
+    #[test]
+    fn test_storage_report() {
+        let conn = init_db_connection();
+
+        let res1 = create_resource("res1", 100);
+        let res2 = create_resource("res2", 300);
+        let res3 = create_resource("res3", 200);
+        let res4 = create_resource("res4", 50);
+
+        let post1 = create_post("slug1", &[&res1]);
+        crate::storage::sqlite::post::insert_post(&conn, &post1, std::slice::from_ref(&res1))
+            .unwrap();
+
+        let post2 = create_post("slug2", &[&res2]);
+        crate::storage::sqlite::post::insert_post(&conn, &post2, &[res2.clone(), res3.clone()])
+            .unwrap();
+
+        let post3 = create_post("slug3", &[]);
+        crate::storage::sqlite::post::insert_post(&conn, &post3, &[]).unwrap();
+
+        crate::storage::sqlite::resource::insert_resource(&conn, &res4).unwrap();
+
+        let report = get_storage_report(&conn).unwrap();
+        assert_eq!(report.resources_count, 4);
+        assert_eq!(report.resources_bytes, 650);
+        assert!(report.file_size > 0);
+        assert_eq!(report.file_size % report.page_size, 0);
+
+        let post_slugs: Vec<_> = report.posts.iter().map(|p| p.post_slug.as_str()).collect();
+        assert_eq!(post_slugs, ["slug2", "slug1", "slug3"]);
+
+        let post2_report = &report.posts[0];
+        assert_eq!(post2_report.resources_bytes, 500);
+        let post2_resources: Vec<_> = post2_report
+            .resources
+            .iter()
+            .map(|res| (res.id, res.size, res.is_referenced))
+            .collect();
+        assert_eq!(
+            post2_resources,
+            [(res2.id, 300, true), (res3.id, 200, false)]
+        );
+        assert_eq!(post2_report.largest_resource().unwrap().id, res2.id);
+
+        assert!(report.posts[1].resources[0].is_referenced);
+        assert!(report.posts[2].resources.is_empty());
+
+        let unattached_ids: Vec<_> = report
+            .unattached_resources
+            .iter()
+            .map(|res| res.id)
+            .collect();
+        assert_eq!(unattached_ids, [res4.id]);
+    }
+}
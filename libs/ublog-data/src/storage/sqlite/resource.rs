@@ -18,18 +18,29 @@ pub(crate) fn init_db_schema(conn: &Connection) -> Result<(), SqliteStorageError
     Ok(())
 }
 
+pub(super) const SELECT_RESOURCE_SQL: &str = r#"
+    SELECT id, name, ty, data
+    FROM resources
+    WHERE id == ?1 AND NOT (?2 AND EXISTS (
+        SELECT 1 FROM posts_resources
+        WHERE res_id == resources.id AND is_public == 0
+    ));
+"#;
+
+/// Get the resource with the given ID.
+///
+/// If `public_only` is `true`, resources that are private to any post they are attached to are not selected.
 pub(crate) fn get_resource(
     conn: &Connection,
     uuid: &Uuid,
+    public_only: bool,
 ) -> Result<Option<Resource>, SqliteStorageError> {
-    const SELECT_SQL: &str = r#"
-        SELECT id, name, ty, data
-        FROM resources
-        WHERE id == ?;
-    "#;
-
     let uuid_str = format!("{}", uuid.as_hyphenated());
-    conn.query_one(SELECT_SQL, (&uuid_str,), create_resource_from_row)
+    conn.query_one(
+        SELECT_RESOURCE_SQL,
+        (&uuid_str, public_only),
+        create_resource_from_row,
+    )
 }
 
 pub(crate) fn get_resources(conn: &Connection) -> Result<Vec<Resource>, SqliteStorageError> {
@@ -41,17 +52,81 @@ pub(crate) fn get_resources(conn: &Connection) -> Result<Vec<Resource>, SqliteSt
     conn.query_many(SELECT_SQL, (), create_resources_from_row_no_data)
 }
 
+/// Get the resources attached to the post with the given slug.
+///
+/// If `public_only` is `true`, resources that are private to the post are not selected.
 pub(crate) fn get_post_resources(
     conn: &Connection,
     post_slug: &str,
+    public_only: bool,
 ) -> Result<Vec<Resource>, SqliteStorageError> {
     const SELECT_SQL: &str = r#"
         SELECT id, name, ty, data
         FROM posts_resources JOIN resources ON posts_resources.res_id == resources.id
-        where posts_resources.post_slug == ?;
+        where posts_resources.post_slug == ?1 AND (NOT ?2 OR posts_resources.is_public != 0);
+    "#;
+
+    conn.query_many(
+        SELECT_SQL,
+        (post_slug, public_only),
+        create_resource_from_row,
+    )
+}
+
+/// Get the number of resources attached to the post with the given slug, without loading any resource.
+pub(crate) fn count_post_resources(
+    conn: &Connection,
+    post_slug: &str,
+) -> Result<u64, SqliteStorageError> {
+    const SELECT_COUNT_SQL: &str = r#"
+        SELECT count(*) AS cnt
+        FROM posts_resources
+        WHERE post_slug == ?;
+    "#;
+
+    let count: i64 = conn
+        .query_one(SELECT_COUNT_SQL, (post_slug,), |row| {
+            row.get(0).map_err(From::from)
+        })?
+        .unwrap();
+    Ok(count as u64)
+}
+
+/// Set whether the resource with the given ID is public within the post with the given slug.
+///
+/// Returns `false` without setting anything if the resource is not attached to the post.
+pub(crate) fn set_resource_public(
+    conn: &Connection,
+    post_slug: &str,
+    uuid: &Uuid,
+    is_public: bool,
+) -> Result<bool, SqliteStorageError> {
+    const UPDATE_SQL: &str = r#"
+        UPDATE posts_resources
+        SET is_public = ?
+        WHERE post_slug == ? AND res_id == ?;
+    "#;
+
+    let uuid_str = format!("{}", uuid.as_hyphenated());
+    let changes = conn.execute(UPDATE_SQL, (is_public, post_slug, &uuid_str))?;
+    Ok(changes > 0)
+}
+
+/// Get the IDs of the resources that are private to the post with the given slug.
+pub(crate) fn get_private_resource_ids(
+    conn: &Connection,
+    post_slug: &str,
+) -> Result<Vec<Uuid>, SqliteStorageError> {
+    const SELECT_SQL: &str = r#"
+        SELECT res_id
+        FROM posts_resources
+        WHERE post_slug == ? AND is_public == 0;
     "#;
 
-    conn.query_many(SELECT_SQL, (post_slug,), create_resource_from_row)
+    conn.query_many(SELECT_SQL, (post_slug,), |row| {
+        let id_str: String = row.get(0)?;
+        Ok(id_str.parse()?)
+    })
 }
 
 pub(crate) fn insert_resource(
@@ -102,17 +177,20 @@ fn create_resources_from_row_no_data(row: &Row) -> Result<Resource, SqliteStorag
         id,
         name: row.get("name")?,
         ty: row.get("ty")?,
-        data: row.get("data")?,
+        data: Vec::new(),
     })
 }
 
 #[cfg(test)]
 mod tests {
+    use crate::clock::SystemClock;
+    use crate::fixtures::{PostBuilder, ResourceBuilder};
+
     use super::*;
 
     fn init_db_connection() -> Connection {
         let conn = Connection::open_in_memory().unwrap();
-        init_db_schema(&conn).unwrap();
+        crate::storage::sqlite::init_db_schema(&conn, &SystemClock).unwrap();
         conn
     }
 
@@ -165,19 +243,34 @@ mod tests {
         };
         insert_resource(&conn, &res).unwrap();
 
-        let selected_res = get_resource(&conn, &res.id).unwrap().unwrap();
+        let selected_res = get_resource(&conn, &res.id, false).unwrap().unwrap();
         assert_eq!(res.id, selected_res.id);
         assert_eq!(res.name, selected_res.name);
         assert_eq!(res.ty, selected_res.ty);
         assert_eq!(res.data, selected_res.data);
     }
 
+    #[test]
+    fn test_select_all_without_data() {
+        let conn = init_db_connection();
+
+        let res = ResourceBuilder::new("res").data(vec![0, 1, 2, 3]).build();
+        insert_resource(&conn, &res).unwrap();
+
+        let selected = get_resources(&conn).unwrap();
+        assert_eq!(selected.len(), 1);
+        assert_eq!(selected[0].id, res.id);
+        assert_eq!(selected[0].name, res.name);
+        assert_eq!(selected[0].ty, res.ty);
+        assert!(selected[0].data.is_empty());
+    }
+
     #[test]
     fn test_select_not_exist() {
         let conn = init_db_connection();
 
         let id = Uuid::new_v4();
-        let selected_res = get_resource(&conn, &id).unwrap();
+        let selected_res = get_resource(&conn, &id, false).unwrap();
         assert!(selected_res.is_none());
     }
 
@@ -195,7 +288,7 @@ mod tests {
 
         delete_resource(&conn, &res.id).unwrap();
 
-        let selected_res = get_resource(&conn, &res.id).unwrap();
+        let selected_res = get_resource(&conn, &res.id, false).unwrap();
         assert!(selected_res.is_none());
     }
 
@@ -205,4 +298,61 @@ mod tests {
         let id = Uuid::new_v4();
         delete_resource(&conn, &id).unwrap();
     }
+
+    #[test]
+    fn test_count_post_resources() {
+        let conn = init_db_connection();
+
+        let resources = [
+            ResourceBuilder::new("res1").build(),
+            ResourceBuilder::new("res2").build(),
+            ResourceBuilder::new("res3").build(),
+        ];
+        let post = PostBuilder::new("slug1").build();
+        crate::storage::sqlite::post::insert_post(&conn, &post, &resources).unwrap();
+        let post = PostBuilder::new("slug2").build();
+        crate::storage::sqlite::post::insert_post(&conn, &post, &[]).unwrap();
+
+        assert_eq!(count_post_resources(&conn, "slug1").unwrap(), 3);
+        assert_eq!(count_post_resources(&conn, "slug2").unwrap(), 0);
+        assert_eq!(count_post_resources(&conn, "missing").unwrap(), 0);
+    }
+
+    #[test]
+    fn test_public_only() {
+        let conn = init_db_connection();
+
+        let public_res = ResourceBuilder::new("public").build();
+        let private_res = ResourceBuilder::new("private").build();
+        let post = PostBuilder::new("slug").build();
+        crate::storage::sqlite::post::insert_post(
+            &conn,
+            &post,
+            &[public_res.clone(), private_res.clone()],
+        )
+        .unwrap();
+
+        assert!(set_resource_public(&conn, "slug", &private_res.id, false).unwrap());
+        assert!(!set_resource_public(&conn, "missing", &private_res.id, false).unwrap());
+        assert_eq!(
+            get_private_resource_ids(&conn, "slug").unwrap(),
+            [private_res.id]
+        );
+
+        let public_ids: Vec<_> = get_post_resources(&conn, "slug", true)
+            .unwrap()
+            .into_iter()
+            .map(|res| res.id)
+            .collect();
+        assert_eq!(public_ids, [public_res.id]);
+        assert_eq!(get_post_resources(&conn, "slug", false).unwrap().len(), 2);
+
+        assert!(get_resource(&conn, &public_res.id, true).unwrap().is_some());
+        assert!(get_resource(&conn, &private_res.id, true)
+            .unwrap()
+            .is_none());
+        assert!(get_resource(&conn, &private_res.id, false)
+            .unwrap()
+            .is_some());
+    }
 }
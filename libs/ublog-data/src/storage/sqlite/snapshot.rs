@@ -0,0 +1,133 @@
+use rusqlite::Connection;
+use uuid::Uuid;
+
+use crate::models::{Post, Resource};
+use crate::storage::sqlite::SqliteStorageError;
+use crate::storage::{PaginatedList, Pagination, PostsBySlugs};
+
+/// A consistent, read-only view of a sqlite database.
+///
+/// All reads made through a `SqliteSnapshot` observe the database as it was when the snapshot was taken, even if other
+/// connections write to the database in the meantime. Snapshots are created by [`read_snapshot`].
+///
+/// [`read_snapshot`]: crate::storage::sqlite::SqliteStorage::read_snapshot
+#[derive(Debug)]
+pub struct SqliteSnapshot<'a> {
+    conn: &'a Connection,
+}
+
+impl<'a> SqliteSnapshot<'a> {
+    /// Get the post with the given slug.
+    pub fn get_post(&self, post_slug: &str) -> Result<Option<Post>, SqliteStorageError> {
+        crate::storage::sqlite::post::get_post(self.conn, post_slug)
+    }
+
+    /// Get the post with the given slug, together with its resources.
+    pub fn get_post_with_resources(
+        &self,
+        post_slug: &str,
+    ) -> Result<Option<(Post, Vec<Resource>)>, SqliteStorageError> {
+        crate::storage::sqlite::post::get_post_with_resources(self.conn, post_slug)
+    }
+
+    /// Get a list of posts within the specified page.
+    pub fn get_posts(
+        &self,
+        special: bool,
+        pagination: &Pagination,
+    ) -> Result<PaginatedList<Post>, SqliteStorageError> {
+        crate::storage::sqlite::post::get_posts(self.conn, special, pagination)
+    }
+
+    /// Get the posts with the given slugs.
+    pub fn get_posts_by_slugs(
+        &self,
+        post_slugs: &[String],
+        with_content: bool,
+    ) -> Result<PostsBySlugs, SqliteStorageError> {
+        crate::storage::sqlite::post::get_posts_by_slugs(self.conn, post_slugs, with_content)
+    }
+
+    /// Get the resource with the given ID.
+    ///
+    /// If `public_only` is `true`, resources that are private to any post they are attached to are not selected.
+    pub fn get_resource(
+        &self,
+        resource_id: &Uuid,
+        public_only: bool,
+    ) -> Result<Option<Resource>, SqliteStorageError> {
+        crate::storage::sqlite::resource::get_resource(self.conn, resource_id, public_only)
+    }
+}
+
+/// Run `read` against a snapshot of the database behind the given connection.
+///
+/// The snapshot is a single deferred read transaction, which is ended once `read` returns.
+pub(super) fn read_snapshot<F, R>(conn: &Connection, read: F) -> Result<R, SqliteStorageError>
+where
+    F: FnOnce(&SqliteSnapshot) -> Result<R, SqliteStorageError>,
+{
+    let trans = conn.unchecked_transaction()?;
+
+    // A deferred transaction does not start reading until its first statement, so read something right away to pin
+    // the snapshot to the moment this function is called.
+    trans.query_row("SELECT count(*) FROM sqlite_master;", (), |_| Ok(()))?;
+
+    let ret = read(&SqliteSnapshot { conn: &trans })?;
+
+    trans.commit()?;
+
+    Ok(ret)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use crate::clock::SystemClock;
+    use crate::fixtures::{PostBuilder, TempDatabaseFile};
+
+    use super::*;
+
+    fn open_wal_connection(path: &Path) -> Connection {
+        let conn = Connection::open(path).unwrap();
+        let journal_mode: String = conn
+            .pragma_update_and_check(None, "journal_mode", "WAL", |row| row.get(0))
+            .unwrap();
+        assert_eq!(journal_mode.to_lowercase(), "wal");
+        conn
+    }
+
+    #[test]
+    fn test_snapshot_isolated_from_writes() {
+        let db_file = TempDatabaseFile::new();
+
+        let conn = open_wal_connection(db_file.path());
+        crate::storage::sqlite::init_db_schema(&conn, &SystemClock).unwrap();
+        crate::storage::sqlite::post::insert_post(&conn, &PostBuilder::new("slug1").build(), &[])
+            .unwrap();
+
+        read_snapshot(&conn, |snap| {
+            let path = db_file.path().to_path_buf();
+            std::thread::spawn(move || {
+                let conn = Connection::open(path).unwrap();
+                crate::storage::sqlite::post::insert_post(
+                    &conn,
+                    &PostBuilder::new("slug2").build(),
+                    &[],
+                )
+                .unwrap();
+            })
+            .join()
+            .unwrap();
+
+            assert!(snap.get_post("slug1")?.is_some());
+            assert!(snap.get_post("slug2")?.is_none());
+            Ok(())
+        })
+        .unwrap();
+
+        let post = crate::storage::sqlite::post::get_post(&conn, "slug2").unwrap();
+        assert!(post.is_some());
+    }
+}
@@ -0,0 +1,73 @@
+use std::os::raw::c_int;
+
+use rusqlite::{ffi, Connection};
+
+use crate::storage::sqlite::SqliteStorageError;
+
+/// Counters of the I/O performed through a sqlite connection.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct SqliteIoStats {
+    /// Number of page cache hits since the connection is opened.
+    pub cache_hits: u64,
+
+    /// Number of page cache misses since the connection is opened.
+    pub cache_misses: u64,
+
+    /// Number of bytes of heap memory used by the page cache.
+    pub cache_used_bytes: u64,
+
+    /// Number of lookaside memory slots currently in use.
+    pub lookaside_used: u64,
+}
+
+pub(super) fn get_io_stats(conn: &Connection) -> Result<SqliteIoStats, SqliteStorageError> {
+    Ok(SqliteIoStats {
+        cache_hits: get_db_status(conn, ffi::SQLITE_DBSTATUS_CACHE_HIT)?,
+        cache_misses: get_db_status(conn, ffi::SQLITE_DBSTATUS_CACHE_MISS)?,
+        cache_used_bytes: get_db_status(conn, ffi::SQLITE_DBSTATUS_CACHE_USED)?,
+        lookaside_used: get_db_status(conn, ffi::SQLITE_DBSTATUS_LOOKASIDE_USED)?,
+    })
+}
+
+fn get_db_status(conn: &Connection, op: c_int) -> Result<u64, SqliteStorageError> {
+    let mut current: c_int = 0;
+    let mut highwater: c_int = 0;
+
+    // SAFETY: the handle is valid for as long as `conn` is borrowed, and sqlite3_db_status only writes through the two
+    // given pointers.
+    let ret = unsafe { ffi::sqlite3_db_status(conn.handle(), op, &mut current, &mut highwater, 0) };
+    if ret != ffi::SQLITE_OK {
+        return Err(rusqlite::Error::SqliteFailure(ffi::Error::new(ret), None).into());
+    }
+
+    Ok(u64::try_from(current).unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::clock::SystemClock;
+
+    use super::*;
+
+    #[test]
+    fn test_io_stats_after_workload() {
+        let conn = Connection::open_in_memory().unwrap();
+        crate::storage::sqlite::init_db_schema(&conn, &SystemClock).unwrap();
+
+        for i in 0..100 {
+            conn.execute(
+                "INSERT INTO resources (id, name, ty, data) VALUES (?, 'res', 'image/png', x'00');",
+                (i.to_string(),),
+            )
+            .unwrap();
+        }
+        let count: i64 = conn
+            .query_row("SELECT count(*) FROM resources;", (), |row| row.get(0))
+            .unwrap();
+        assert_eq!(count, 100);
+
+        let stats = get_io_stats(&conn).unwrap();
+        assert!(stats.cache_hits > 0);
+        assert!(stats.cache_used_bytes > 0);
+    }
+}
@@ -0,0 +1,153 @@
+use rusqlite::Connection;
+
+use crate::storage::sqlite::{SqliteExt, SqliteStorageError};
+
+/// Modes of checkpointing the write-ahead log of sqlite databases.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum SqliteCheckpointMode {
+    /// Checkpoint as many frames as possible without waiting for readers or writers.
+    Passive,
+
+    /// Wait for writers to finish, then checkpoint every frame.
+    Full,
+
+    /// Like `Full`, and then wait for readers so that the next writer restarts the log from its beginning.
+    Restart,
+
+    /// Like `Restart`, and then truncate the log file to zero bytes.
+    Truncate,
+}
+
+impl SqliteCheckpointMode {
+    fn as_pragma_value(&self) -> &'static str {
+        match self {
+            Self::Passive => "PASSIVE",
+            Self::Full => "FULL",
+            Self::Restart => "RESTART",
+            Self::Truncate => "TRUNCATE",
+        }
+    }
+}
+
+/// Result of checkpointing the write-ahead log of a sqlite database.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct SqliteCheckpoint {
+    /// Whether the checkpoint could not complete because other connections are reading or writing the database.
+    pub busy: bool,
+
+    /// Number of frames in the write-ahead log.
+    pub log_frames: u64,
+
+    /// Number of frames in the write-ahead log that are checkpointed into the database file.
+    pub checkpointed_frames: u64,
+}
+
+/// Statistics of the write-ahead log of a sqlite database.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct SqliteWalStats {
+    /// Size of the write-ahead log file, in bytes.
+    pub file_size: u64,
+
+    /// Number of frames the write-ahead log grows to before it is checkpointed automatically, or 0 if automatic
+    /// checkpoints are disabled.
+    pub autocheckpoint_frames: u64,
+}
+
+/// Checkpoint the write-ahead log of the database behind the given connection.
+///
+/// Returns `None` if the database is not in WAL mode.
+pub(super) fn checkpoint(
+    conn: &Connection,
+    mode: SqliteCheckpointMode,
+) -> Result<Option<SqliteCheckpoint>, SqliteStorageError> {
+    let checkpoint_sql = format!("PRAGMA wal_checkpoint({});", mode.as_pragma_value());
+    let (busy, log_frames, checkpointed_frames): (i64, i64, i64) = conn
+        .query_one(checkpoint_sql, (), |row| {
+            Ok((row.get(0)?, row.get(1)?, row.get(2)?))
+        })?
+        .unwrap();
+
+    // Both frame counts are -1 if the database is not in WAL mode.
+    if log_frames < 0 {
+        return Ok(None);
+    }
+
+    Ok(Some(SqliteCheckpoint {
+        busy: busy != 0,
+        log_frames: log_frames as u64,
+        checkpointed_frames: u64::try_from(checkpointed_frames).unwrap_or_default(),
+    }))
+}
+
+/// Get the statistics of the write-ahead log of the database behind the given connection.
+///
+/// The log file size is 0 if the database is not in WAL mode or is not backed by a file.
+pub(super) fn get_wal_stats(conn: &Connection) -> Result<SqliteWalStats, SqliteStorageError> {
+    const SELECT_FILE_SQL: &str = r#"
+        SELECT file FROM pragma_database_list
+        WHERE name == 'main';
+    "#;
+
+    let db_file: Option<String> = conn
+        .query_one(SELECT_FILE_SQL, (), |row| row.get(0).map_err(From::from))?
+        .filter(|file: &String| !file.is_empty());
+    let file_size = match db_file {
+        Some(db_file) => match std::fs::metadata(format!("{}-wal", db_file)) {
+            Ok(metadata) => metadata.len(),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => 0,
+            Err(err) => return Err(err.into()),
+        },
+        None => 0,
+    };
+
+    let autocheckpoint_frames: i64 =
+        conn.pragma_query_value(None, "wal_autocheckpoint", |row| row.get(0))?;
+
+    Ok(SqliteWalStats {
+        file_size,
+        autocheckpoint_frames: u64::try_from(autocheckpoint_frames).unwrap_or_default(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::clock::SystemClock;
+    use crate::fixtures::{PostBuilder, TempDatabaseFile};
+
+    use super::*;
+
+    #[test]
+    fn test_checkpoint_not_wal() {
+        let conn = Connection::open_in_memory().unwrap();
+        assert!(checkpoint(&conn, SqliteCheckpointMode::Passive)
+            .unwrap()
+            .is_none());
+        assert_eq!(get_wal_stats(&conn).unwrap().file_size, 0);
+    }
+
+    #[test]
+    fn test_checkpoint_truncate() {
+        let db_file = TempDatabaseFile::new();
+        let conn = Connection::open(db_file.path()).unwrap();
+        conn.pragma_update(None, "journal_mode", "WAL").unwrap();
+        conn.pragma_update(None, "wal_autocheckpoint", 0).unwrap();
+        crate::storage::sqlite::init_db_schema(&conn, &SystemClock).unwrap();
+
+        for i in 0..50 {
+            let post = PostBuilder::new(format!("slug{}", i)).build();
+            crate::storage::sqlite::post::insert_post(&conn, &post, &[]).unwrap();
+        }
+
+        let stats = get_wal_stats(&conn).unwrap();
+        assert!(stats.file_size > 0);
+        assert_eq!(stats.autocheckpoint_frames, 0);
+
+        let result = checkpoint(&conn, SqliteCheckpointMode::Truncate)
+            .unwrap()
+            .unwrap();
+        assert!(!result.busy);
+        assert_eq!(result.log_frames, result.checkpointed_frames);
+
+        assert_eq!(get_wal_stats(&conn).unwrap().file_size, 0);
+    }
+}
@@ -54,11 +54,19 @@ where
             return Err(SynchronizeStorageError::DiverseHistory);
         }
     } else if let Some(to_latest_commit) = to_latest_commit.as_ref() {
-        if to_latest_commit.id != from_commits[0].id {
-            return Err(SynchronizeStorageError::DiverseHistory);
-        } else {
-            // The first commit within `from_commits` is actually present in the destication storage.
-            from_commits.remove(0);
+        // Commits created by a single mutation share their timestamp, so the destination's latest commit is not
+        // necessarily the first one within `from_commits`.
+        match from_commits
+            .iter()
+            .position(|commit| commit.id == to_latest_commit.id)
+        {
+            Some(pos) => {
+                // The commits up to and including the destination's latest commit are already present there.
+                from_commits.drain(..=pos);
+            }
+            None => {
+                return Err(SynchronizeStorageError::DiverseHistory);
+            }
         }
     }
 
@@ -101,6 +109,7 @@ where
     S: ?Sized + Storage,
 {
     let mut added_post_slugs = HashSet::new();
+    let mut updated_meta_post_slugs = HashSet::new();
     let mut deleted_post_slugs = HashSet::new();
     let mut added_resource_ids = HashSet::new();
     let mut deleted_resource_ids = HashSet::new();
@@ -123,19 +132,32 @@ where
                     deleted_resource_ids.insert(payload.id);
                 }
             }
+            CommitPayload::UpdatePostMeta(payload) => {
+                updated_meta_post_slugs.insert(payload.slug.clone());
+            }
         }
     }
 
     let mut delta = Delta::new();
 
     for slug in &added_post_slugs {
-        if let Some(post_with_resources) = storage.get_post_with_resources(slug).await? {
-            delta.added_posts.push(post_with_resources);
+        if let Some((post, post_resources)) = storage.get_post_with_resources(slug).await? {
+            let private_resource_ids = storage.get_private_resource_ids(slug).await?;
+            delta
+                .added_posts
+                .push((post, post_resources, private_resource_ids));
         }
     }
 
+    // The metadata of added posts is sent as well, since posts added by the delta replace the destination's posts
+    // together with their metadata.
+    for slug in added_post_slugs.union(&updated_meta_post_slugs) {
+        let post_meta = storage.get_all_post_meta(slug).await?;
+        delta.post_meta.push((slug.clone(), post_meta));
+    }
+
     for id in &added_resource_ids {
-        if let Some(resource) = storage.get_resource(id).await? {
+        if let Some(resource) = storage.get_resource(id, false).await? {
             delta.added_resources.push(resource);
         }
     }
@@ -146,3 +168,124 @@ where
     delta.commits = commits;
     Ok(delta)
 }
+
+#[cfg(test)]
+mod tests {
+    use rusqlite::Connection;
+
+    use crate::clock::MockClock;
+    use crate::fixtures::{PostBuilder, ResourceBuilder};
+    use crate::storage::sqlite::SqliteStorage;
+
+    use super::*;
+
+    fn create_storage(clock: &MockClock) -> SqliteStorage {
+        let conn = Connection::open_in_memory().unwrap();
+        SqliteStorage::new_with_clock(conn, clock.clone()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_synchronize_private_resource() {
+        let clock = MockClock::new(1000);
+        let storage_from = create_storage(&clock);
+        let storage_to = create_storage(&clock);
+
+        let post = PostBuilder::new("slug").build();
+        let public_resource = ResourceBuilder::new("public").build();
+        let private_resource = ResourceBuilder::new("private").build();
+        storage_from
+            .insert_post(&post, &[public_resource.clone(), private_resource.clone()])
+            .await
+            .unwrap();
+        clock.advance(1);
+        assert!(storage_from
+            .set_resource_public("slug", &private_resource.id, false)
+            .await
+            .unwrap());
+        clock.advance(1);
+        synchronize_storage(&storage_from, &storage_to)
+            .await
+            .unwrap();
+
+        assert!(storage_to
+            .get_resource(&public_resource.id, true)
+            .await
+            .unwrap()
+            .is_some());
+        assert!(storage_to
+            .get_resource(&private_resource.id, true)
+            .await
+            .unwrap()
+            .is_none());
+        assert!(storage_to
+            .get_resource(&private_resource.id, false)
+            .await
+            .unwrap()
+            .is_some());
+
+        // Making the resource public again is replicated as well.
+        clock.advance(1);
+        storage_from
+            .set_resource_public("slug", &private_resource.id, true)
+            .await
+            .unwrap();
+        clock.advance(1);
+        synchronize_storage(&storage_from, &storage_to)
+            .await
+            .unwrap();
+        assert!(storage_to
+            .get_resource(&private_resource.id, true)
+            .await
+            .unwrap()
+            .is_some());
+    }
+
+    #[tokio::test]
+    async fn test_synchronize_post_meta() {
+        let clock = MockClock::new(1000);
+        let storage_from = create_storage(&clock);
+        let storage_to = create_storage(&clock);
+
+        let post = PostBuilder::new("slug").build();
+        storage_from.insert_post(&post, &[]).await.unwrap();
+        clock.advance(1);
+        storage_from
+            .set_post_meta("slug", "views", "10")
+            .await
+            .unwrap();
+        clock.advance(1);
+        synchronize_storage(&storage_from, &storage_to)
+            .await
+            .unwrap();
+        assert_eq!(
+            storage_to.get_post_meta("slug", "views").await.unwrap(),
+            Some(String::from("10"))
+        );
+
+        clock.advance(1);
+        storage_from
+            .set_post_meta("slug", "views", "11")
+            .await
+            .unwrap();
+        clock.advance(1);
+        synchronize_storage(&storage_from, &storage_to)
+            .await
+            .unwrap();
+        assert_eq!(
+            storage_to.get_post_meta("slug", "views").await.unwrap(),
+            Some(String::from("11"))
+        );
+
+        clock.advance(1);
+        storage_from.delete_post("slug").await.unwrap();
+        clock.advance(1);
+        synchronize_storage(&storage_from, &storage_to)
+            .await
+            .unwrap();
+        assert!(storage_to
+            .get_all_post_meta("slug")
+            .await
+            .unwrap()
+            .is_empty());
+    }
+}
@@ -0,0 +1,153 @@
+use std::fmt::{Debug, Display, Formatter};
+
+use serde::de::Error as _;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use time::{OffsetDateTime, PrimitiveDateTime, UtcOffset};
+use time_tz::{Offset, TimeZone as _, Tz};
+
+/// A time zone of the IANA time zone database, such as `Europe/Berlin`.
+///
+/// Unlike a fixed [`UtcOffset`], a time zone follows the daylight saving time rules of its region, so the offset from
+/// UTC depends on the time at which it is taken. Time zones are serialized as their IANA names.
+#[derive(Clone, Copy)]
+pub struct TimeZone {
+    tz: &'static Tz,
+}
+
+impl TimeZone {
+    /// Get the UTC time zone.
+    pub fn utc() -> Self {
+        Self::from_name("UTC").unwrap()
+    }
+
+    /// Get the time zone with the given IANA name.
+    ///
+    /// Returns `None` if there is no time zone with the given name.
+    pub fn from_name(name: &str) -> Option<Self> {
+        time_tz::timezones::get_by_name(name).map(|tz| Self { tz })
+    }
+
+    /// Get the IANA name of the time zone.
+    pub fn name(&self) -> &'static str {
+        self.tz.name()
+    }
+
+    /// Get the offset from UTC in effect in the time zone at the given Unix timestamp.
+    pub fn utc_offset_at(&self, timestamp: i64) -> UtcOffset {
+        let time = OffsetDateTime::from_unix_timestamp(timestamp).unwrap();
+        self.tz.get_offset_utc(&time).to_utc()
+    }
+
+    /// Get the date and time in the time zone at the given Unix timestamp.
+    pub fn local_time(&self, timestamp: i64) -> PrimitiveDateTime {
+        let time = OffsetDateTime::from_unix_timestamp(timestamp).unwrap();
+        let time = time.to_offset(self.utc_offset_at(timestamp));
+        PrimitiveDateTime::new(time.date(), time.time())
+    }
+
+    /// Get the Unix timestamp of the given date and time in the time zone.
+    ///
+    /// A local time skipped by a transition to daylight saving time is taken with the offset in effect before the
+    /// transition, and a local time repeated by a transition from daylight saving time maps to its later occurrence.
+    pub fn unix_timestamp(&self, local_time: PrimitiveDateTime) -> i64 {
+        let as_utc = local_time.assume_utc().unix_timestamp();
+        let guess = as_utc - i64::from(self.utc_offset_at(as_utc).whole_seconds());
+        as_utc - i64::from(self.utc_offset_at(guess).whole_seconds())
+    }
+}
+
+impl Debug for TimeZone {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("TimeZone").field(&self.name()).finish()
+    }
+}
+
+impl Display for TimeZone {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl Default for TimeZone {
+    fn default() -> Self {
+        Self::utc()
+    }
+}
+
+impl PartialEq for TimeZone {
+    fn eq(&self, other: &Self) -> bool {
+        self.name() == other.name()
+    }
+}
+
+impl Eq for TimeZone {}
+
+impl Serialize for TimeZone {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(self.name())
+    }
+}
+
+impl<'de> Deserialize<'de> for TimeZone {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let name = String::deserialize(deserializer)?;
+        Self::from_name(&name)
+            .ok_or_else(|| D::Error::custom(format!("unknown time zone: {}", name)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use time::{Date, Month, Time};
+
+    use super::*;
+
+    fn local(year: i32, month: Month, day: u8, hour: u8, minute: u8) -> PrimitiveDateTime {
+        PrimitiveDateTime::new(
+            Date::from_calendar_date(year, month, day).unwrap(),
+            Time::from_hms(hour, minute, 0).unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_from_name() {
+        assert_eq!(TimeZone::from_name("UTC"), Some(TimeZone::utc()));
+        assert_eq!(
+            TimeZone::from_name("Europe/Berlin").unwrap().name(),
+            "Europe/Berlin"
+        );
+        assert_eq!(TimeZone::from_name("Mars/Olympus_Mons"), None);
+    }
+
+    #[test]
+    fn test_daylight_saving_time() {
+        let berlin = TimeZone::from_name("Europe/Berlin").unwrap();
+
+        // 2024-01-15 12:00 UTC and 2024-07-15 12:00 UTC.
+        assert_eq!(berlin.utc_offset_at(1705320000).whole_hours(), 1);
+        assert_eq!(berlin.utc_offset_at(1721044800).whole_hours(), 2);
+
+        // 2024-03-31 22:30 UTC is already April in Berlin, which observes daylight saving time by then.
+        let april_first = local(2024, Month::April, 1, 0, 30);
+        assert_eq!(berlin.local_time(1711924200), april_first);
+        assert_eq!(berlin.unix_timestamp(april_first), 1711924200);
+
+        let new_year = local(2024, Month::January, 1, 0, 0);
+        assert_eq!(berlin.unix_timestamp(new_year), 1704063600);
+    }
+
+    #[test]
+    fn test_serde() {
+        let berlin = TimeZone::from_name("Europe/Berlin").unwrap();
+        let json = serde_json::to_string(&berlin).unwrap();
+        assert_eq!(json, "\"Europe/Berlin\"");
+        assert_eq!(serde_json::from_str::<TimeZone>(&json).unwrap(), berlin);
+        assert!(serde_json::from_str::<TimeZone>("\"Nowhere\"").is_err());
+    }
+}
@@ -49,6 +49,81 @@ impl DocumentNode {
             child.visit_mut(visitor);
         }
     }
+
+    /// Render the document tree rooted at this document node into plain text.
+    ///
+    /// The text of block-level nodes is put on separate lines. Images and dividers carry no text and are left out.
+    pub fn to_plain_text(&self) -> String {
+        let mut text = String::new();
+        self.render_plain_text(&mut text);
+
+        let text_len = text.trim_end().len();
+        text.truncate(text_len);
+        text
+    }
+
+    /// Count the words in the plain text of the document tree rooted at this document node.
+    ///
+    /// Words are the whitespace-separated runs of the text rendered by `to_plain_text`.
+    pub fn word_count(&self) -> usize {
+        self.to_plain_text().split_whitespace().count()
+    }
+
+    fn render_plain_text(&self, output: &mut String) {
+        match &self.tag {
+            DocumentNodeTag::Code { code, .. } => output.push_str(code),
+            DocumentNodeTag::Equation { expr, .. } => output.push_str(expr),
+            DocumentNodeTag::InlineText { text } => output.push_str(text),
+            DocumentNodeTag::InlineCode { code } => output.push_str(code),
+            DocumentNodeTag::InlineEquation { expr } => output.push_str(expr),
+            _ => {}
+        }
+
+        for child in &self.children {
+            child.render_plain_text(output);
+        }
+
+        match &self.tag {
+            DocumentNodeTag::Paragraph
+            | DocumentNodeTag::Heading { .. }
+            | DocumentNodeTag::Callout { .. }
+            | DocumentNodeTag::Quote
+            | DocumentNodeTag::ListItem
+            | DocumentNodeTag::Code { .. }
+            | DocumentNodeTag::Equation { .. }
+            | DocumentNodeTag::TableRow => {
+                if output.ends_with('\t') {
+                    output.pop();
+                }
+                if !output.is_empty() && !output.ends_with('\n') {
+                    output.push('\n');
+                }
+            }
+            DocumentNodeTag::TableCell => output.push('\t'),
+            _ => {}
+        }
+    }
+
+    /// Get the UUIDs of all embedded resources referenced within the document tree rooted at this document node.
+    pub fn embedded_resources(&self) -> Vec<String> {
+        struct Visitor(Vec<String>);
+
+        impl DocumentNodeVisitor for Visitor {
+            fn visit(&mut self, node: &DocumentNode) {
+                if let DocumentNodeTag::Image {
+                    link: DocumentResourceLink::Embedded { uuid },
+                    ..
+                } = &node.tag
+                {
+                    self.0.push(uuid.clone());
+                }
+            }
+        }
+
+        let mut visitor = Visitor(Vec::new());
+        self.visit(&mut visitor);
+        visitor.0
+    }
 }
 
 /// A document tree node's tag.
@@ -43,7 +43,6 @@ where
         .iter()
         .map(crate::blog::schema::create_post_from_notion_page)
         .collect::<Result<_, _>>()
-        .map_err(NotionBlogError::from)
 }
 
 /// Get the content of the specified post from the corresponding Notion page.
@@ -112,6 +111,9 @@ pub async fn extract_notion_resources(
     Ok(resources)
 }
 
+/// The source recorded on posts synced from Notion.
+pub const POST_SOURCE: &str = "notion";
+
 /// A post published via Notion.
 #[derive(Clone, Debug)]
 pub struct NotionPost {
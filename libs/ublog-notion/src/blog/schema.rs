@@ -1,6 +1,8 @@
+use std::collections::HashSet;
+
 use time::format_description::well_known::Iso8601;
 use time::Date;
-use ublog_data::models::Post;
+use ublog_data::models::{Post, PostKind};
 use ublog_doc::DocumentNode;
 
 use crate::api::models::{Database, Page, PropertyValue};
@@ -8,7 +10,7 @@ use crate::api::{
     NotionApi, QueryDatabaseFilter, QueryDatabaseParams, QueryDatabasePropertyFilter,
     QueryDatabaseSort,
 };
-use crate::blog::{InvalidSchemaError, NotionBlogError, NotionPost};
+use crate::blog::{InvalidSchemaError, NotionBlogError, NotionPost, POST_SOURCE};
 
 /// Validate posts database schema.
 pub async fn validate_posts_db_schema<T>(
@@ -39,12 +41,12 @@ pub fn get_query_posts_db_params() -> QueryDatabaseParams {
 /// Create a [`Post`] object in the blog posts namespace from the corresponding Notion page.
 pub fn create_post_from_notion_page(page: &Page) -> Result<NotionPost, NotionBlogError> {
     let title = TITLE_PROPERTY.get_str_value(page);
-    let slug = SLUG_PROPERTY.get_str_value(page);
+    let slug = sanitize_slug(&SLUG_PROPERTY.get_str_value(page));
     let author = AUTHOR_PROPERTY.get_str_value(page);
     let create_timestamp = CREATE_DATE_PROPERTY.get_timestamp_value(page);
     let update_timestamp = UPDATE_DATE_PROPERTY.get_timestamp_value(page);
     let category = CATEGORY_PROPERTY.get_str_value(page);
-    let tags = TAGS_PROPERTY.get_str_list_value(page);
+    let tags = normalize_tags(TAGS_PROPERTY.get_str_list_value(page));
     let is_special = SPECIAL_PROPERTY.get_checkbox_value(page);
 
     let post = NotionPost {
@@ -58,12 +60,54 @@ pub fn create_post_from_notion_page(page: &Page) -> Result<NotionPost, NotionBlo
             category,
             tags,
             is_special,
+            kind: PostKind::Article,
+            link_url: None,
+            canonical_url: None,
+            source: Some(String::from(POST_SOURCE)),
+            source_version: Some(update_timestamp),
+            word_count: 0,
             content: DocumentNode::new_empty(),
         },
     };
     Ok(post)
 }
 
+/// Sanitize a slug entered in Notion into one that posts accept.
+///
+/// Runs of whitespace become single hyphens, and characters that cannot appear in slugs are removed.
+fn sanitize_slug(slug: &str) -> String {
+    let sanitized = slug
+        .split_whitespace()
+        .map(|word| {
+            word.chars()
+                .filter(|ch| !ch.is_control() && !"/?#%".contains(*ch))
+                .collect::<String>()
+        })
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join("-");
+
+    if sanitized != slug {
+        spdlog::warn!("Slug \"{}\" is sanitized into \"{}\"", slug, sanitized);
+    }
+
+    sanitized
+}
+
+/// Trim the given tags entered in Notion, and drop the empty tags and the tags that are the same as an earlier tag.
+///
+/// Tags that are equal after [`normalize_tag`](ublog_data::models::normalize_tag) are the same tag, which keeps the
+/// spelling of its first occurrence.
+fn normalize_tags(tags: Vec<String>) -> Vec<String> {
+    let mut normalized_tags = HashSet::new();
+    tags.into_iter()
+        .map(|tag| String::from(tag.trim()))
+        .filter(|tag| {
+            !tag.is_empty() && normalized_tags.insert(ublog_data::models::normalize_tag(tag))
+        })
+        .collect()
+}
+
 fn validate_posts_db_schema_on(db: &Database) -> Result<(), InvalidSchemaError> {
     for prop_desc in SCHEMA_PROPERTIES {
         match db.properties.get(prop_desc.name) {
@@ -194,3 +238,128 @@ impl NotionPropertyTypes {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::api::models::{
+        DatePropertyValue, RichText, RichTextAnnotations, RichTextVariants, SelectPropertyValue,
+        TextRichText,
+    };
+
+    use super::*;
+
+    fn create_rich_text(text: &str) -> Vec<RichText> {
+        vec![RichText {
+            plain_text: String::from(text),
+            href: None,
+            annotations: RichTextAnnotations {
+                bold: false,
+                italic: false,
+                strikethrough: false,
+                underline: false,
+                code: false,
+                color: String::from("default"),
+            },
+            variants: RichTextVariants::Text {
+                text: TextRichText {
+                    content: String::from(text),
+                    link: None,
+                },
+            },
+        }]
+    }
+
+    fn create_select(name: &str) -> SelectPropertyValue {
+        SelectPropertyValue {
+            id: String::from(name),
+            name: String::from(name),
+            color: String::from("default"),
+        }
+    }
+
+    fn create_date(date: &str) -> PropertyValue {
+        PropertyValue::Date {
+            date: DatePropertyValue {
+                start: String::from(date),
+                end: None,
+                time_zone: None,
+            },
+        }
+    }
+
+    fn create_page(slug: &str, tags: &[&str]) -> Page {
+        let properties = HashMap::from([
+            (
+                String::from(TITLE_PROPERTY.name),
+                PropertyValue::Title {
+                    title: create_rich_text("Title"),
+                },
+            ),
+            (
+                String::from(SLUG_PROPERTY.name),
+                PropertyValue::RichText {
+                    rich_text: create_rich_text(slug),
+                },
+            ),
+            (
+                String::from(AUTHOR_PROPERTY.name),
+                PropertyValue::RichText {
+                    rich_text: create_rich_text("msr"),
+                },
+            ),
+            (
+                String::from(CREATE_DATE_PROPERTY.name),
+                create_date("2024-01-01"),
+            ),
+            (
+                String::from(UPDATE_DATE_PROPERTY.name),
+                create_date("2024-01-02"),
+            ),
+            (
+                String::from(CATEGORY_PROPERTY.name),
+                PropertyValue::Select {
+                    select: create_select("category"),
+                },
+            ),
+            (
+                String::from(TAGS_PROPERTY.name),
+                PropertyValue::MultiSelect {
+                    multi_select: tags.iter().map(|tag| create_select(tag)).collect(),
+                },
+            ),
+            (
+                String::from(SPECIAL_PROPERTY.name),
+                PropertyValue::Checkbox { checkbox: false },
+            ),
+        ]);
+
+        Page {
+            id: String::from("page"),
+            created_time: String::from("2024-01-01T00:00:00.000Z"),
+            last_edited_time: String::from("2024-01-02T00:00:00.000Z"),
+            archived: false,
+            properties,
+            url: String::from("https://www.notion.so/page"),
+        }
+    }
+
+    #[test]
+    fn test_create_post_normalizes_tags() {
+        let page = create_page("post", &["Rust", "rust ", "Async", " ", "RUST"]);
+        let post = create_post_from_notion_page(&page).unwrap().post;
+
+        assert_eq!(post.tags, ["Rust", "Async"]);
+        assert!(post.validate().is_empty());
+    }
+
+    #[test]
+    fn test_create_post_sanitizes_slug() {
+        let page = create_page(" hello  world/?#% ", &[]);
+        let post = create_post_from_notion_page(&page).unwrap().post;
+
+        assert_eq!(post.slug, "hello-world");
+        assert!(post.validate().is_empty());
+    }
+}
@@ -13,7 +13,7 @@ impl HtmlNode {
         match self {
             Self::Text(text) => {
                 let escaped = html_escape::encode_text(text);
-                output.push_str(&*escaped);
+                output.push_str(&escaped);
             }
             Self::Element(element) => {
                 element.to_html_str(output);
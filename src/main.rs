@@ -1,5 +1,6 @@
 mod notion;
 mod server;
+mod storage;
 mod utils;
 
 use std::error::Error;
@@ -38,6 +39,7 @@ fn main_impl() -> Result<(), Box<dyn Error>> {
         match args {
             UblogArgs::FetchNotion(args) => crate::notion::fetch_notion(&args).await,
             UblogArgs::Serve(args) => crate::server::serve(&args).await,
+            UblogArgs::Storage(args) => crate::storage::storage_report(&args).await,
         }
     })
 }
@@ -52,6 +54,7 @@ fn main_impl() -> Result<(), Box<dyn Error>> {
 enum UblogArgs {
     FetchNotion(FetchNotionArgs),
     Serve(ServerArgs),
+    Storage(StorageArgs),
 }
 
 #[derive(Debug, StructOpt)]
@@ -68,6 +71,19 @@ struct FetchNotionArgs {
     /// Target Notion database ID.
     notion_database_id: String,
 
+    /// Only report the posts that would be created, updated or conflicted, without writing to the database.
+    #[structopt(long)]
+    dry_run: bool,
+
+    /// Overwrite the local edits of the post with the given slug by its version in Notion. May be given more than once.
+    #[structopt(long, value_name = "slug")]
+    force_remote: Vec<String>,
+
+    /// Keep the local edits of the post with the given slug and skip its version in Notion. May be given more than
+    /// once.
+    #[structopt(long, value_name = "slug")]
+    keep_local: Vec<String>,
+
     /// Enable debug output.
     #[structopt(long)]
     debug: bool,
@@ -99,4 +115,19 @@ struct ServerArgs {
     /// Path to the log file directory.
     #[structopt(short, long, default_value = "logs")]
     logs_dir: PathBuf,
+
+    /// Maximum number of seconds to wait for a graceful shutdown before exiting forcibly.
+    #[structopt(long, default_value = "30")]
+    shutdown_timeout: u64,
+}
+
+#[derive(Debug, StructOpt)]
+#[structopt(
+    name = "storage",
+    about = "Report the space taken by the database and its resources"
+)]
+struct StorageArgs {
+    /// Path to the ublog database.
+    #[structopt(short, long, default_value = "ublog.db")]
+    database: PathBuf,
 }
@@ -1,7 +1,8 @@
+use std::collections::HashMap;
 use std::error::Error;
 
 use ublog_data::db::Database;
-use ublog_data::models::Resource;
+use ublog_data::models::{Post, Resource};
 use ublog_data::storage::sqlite::SqliteStorage;
 use ublog_data::storage::Storage;
 use ublog_notion::api::NotionApi;
@@ -15,6 +16,11 @@ pub(crate) async fn fetch_notion(args: &FetchNotionArgs) -> Result<(), Box<dyn E
         crate::utils::logging::init_basic_logger(args.debug)
     );
 
+    let resolutions = fallible_step!(
+        "parse conflict resolutions",
+        get_conflict_resolutions(&args.force_remote, &args.keep_local)
+    );
+
     let notion_api = NotionApi::new(&args.token);
 
     let db_storage = fallible_step!(
@@ -32,16 +38,40 @@ pub(crate) async fn fetch_notion(args: &FetchNotionArgs) -> Result<(), Box<dyn E
         posts.len()
     );
 
-    let diff_posts = filter_diff_posts(posts, &db).await?;
+    let diff_posts = filter_diff_posts(posts, &db, &resolutions).await?;
     let new_posts = diff_posts.iter().filter(|p| p.is_new()).count();
     let updated_posts = diff_posts.iter().filter(|p| p.is_updated()).count();
+    let conflicted_posts = diff_posts.iter().filter(|p| p.is_conflict()).count();
     spdlog::info!(
-        "{} diff posts found: {} new, {} updated",
+        "{} diff posts found: {} new, {} updated, {} conflicted",
         diff_posts.len(),
         new_posts,
-        updated_posts
+        updated_posts,
+        conflicted_posts
     );
 
+    if args.dry_run {
+        for p in &diff_posts {
+            let action = match p {
+                DiffPost::New(_) => "create",
+                DiffPost::Updated(_) => "update",
+                DiffPost::Conflict(_) => "flag conflict on",
+            };
+            spdlog::info!(
+                "Would {} post: {} - {}",
+                action,
+                p.post().post.slug,
+                p.post().notion_page_id
+            );
+        }
+
+        return Ok(());
+    }
+
+    // Posts edited locally since they were last synced are reported rather than overwritten.
+    let (conflicts, diff_posts): (Vec<_>, Vec<_>) =
+        diff_posts.into_iter().partition(DiffPost::is_conflict);
+
     futures::future::join_all(
         diff_posts
             .into_iter()
@@ -51,25 +81,60 @@ pub(crate) async fn fetch_notion(args: &FetchNotionArgs) -> Result<(), Box<dyn E
     .into_iter()
     .collect::<Result<Vec<_>, _>>()?;
 
+    if !conflicts.is_empty() {
+        for p in &conflicts {
+            spdlog::warn!(
+                "Post {} was edited locally since it was last synced. Pass --force-remote {} or --keep-local {} to resolve the conflict.",
+                p.post().post.slug,
+                p.post().post.slug,
+                p.post().post.slug
+            );
+        }
+        return Err(format!("{} posts conflict with local edits", conflicts.len()).into());
+    }
+
     Ok(())
 }
 
+/// How to resolve a conflict between a post edited locally and a newer version of it in Notion.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum ConflictResolution {
+    ForceRemote,
+    KeepLocal,
+}
+
+fn get_conflict_resolutions(
+    force_remote: &[String],
+    keep_local: &[String],
+) -> Result<HashMap<String, ConflictResolution>, String> {
+    let mut resolutions = HashMap::new();
+    for slug in force_remote {
+        resolutions.insert(slug.clone(), ConflictResolution::ForceRemote);
+    }
+    for slug in keep_local {
+        if resolutions.contains_key(slug) {
+            return Err(format!(
+                "post {} cannot both be forced to its remote version and keep its local edits",
+                slug
+            ));
+        }
+        resolutions.insert(slug.clone(), ConflictResolution::KeepLocal);
+    }
+
+    Ok(resolutions)
+}
+
 async fn filter_diff_posts<S>(
     posts: Vec<NotionPost>,
     db: &Database<S>,
+    resolutions: &HashMap<String, ConflictResolution>,
 ) -> Result<Vec<DiffPost>, Box<dyn Error>>
 where
     S: Storage,
 {
     let task = futures::future::join_all(posts.into_iter().map(|p| async {
         match db.get_post(&p.post.slug).await {
-            Ok(Some(post)) => {
-                if p.post.update_timestamp > post.update_timestamp {
-                    Ok(Some(DiffPost::Updated(p)))
-                } else {
-                    Ok(None)
-                }
-            }
+            Ok(Some(post)) => Ok(diff_existing_post(p, &post, resolutions)),
             Ok(None) => Ok(Some(DiffPost::New(p))), // db.get_post returns None indicating the post does not exist
             Err(err) => Err(err),
         }
@@ -86,10 +151,38 @@ where
     Ok(filtered_posts)
 }
 
+fn diff_existing_post(
+    remote: NotionPost,
+    local: &Post,
+    resolutions: &HashMap<String, ConflictResolution>,
+) -> Option<DiffPost> {
+    // Posts synced before their source versions were recorded have not been edited locally as far as we can tell.
+    let synced_version = local.source_version.unwrap_or(local.update_timestamp);
+    if remote.post.update_timestamp <= synced_version {
+        return None;
+    }
+
+    if local.update_timestamp <= synced_version {
+        return Some(DiffPost::Updated(remote));
+    }
+
+    match resolutions.get(&remote.post.slug) {
+        Some(ConflictResolution::ForceRemote) => Some(DiffPost::Updated(remote)),
+        Some(ConflictResolution::KeepLocal) => {
+            spdlog::info!("Keeping local edits of post: {}", remote.post.slug);
+            None
+        }
+        None => Some(DiffPost::Conflict(remote)),
+    }
+}
+
 #[derive(Debug)]
 enum DiffPost {
     New(NotionPost),
     Updated(NotionPost),
+
+    /// The post has a newer version in Notion but has been edited locally since it was last synced.
+    Conflict(NotionPost),
 }
 
 impl DiffPost {
@@ -101,10 +194,15 @@ impl DiffPost {
         matches!(self, Self::Updated(_))
     }
 
+    fn is_conflict(&self) -> bool {
+        matches!(self, Self::Conflict(_))
+    }
+
     fn post(&self) -> &NotionPost {
         match self {
             Self::New(p) => p,
             Self::Updated(p) => p,
+            Self::Conflict(p) => p,
         }
     }
 
@@ -112,6 +210,7 @@ impl DiffPost {
         match self {
             Self::New(p) => p,
             Self::Updated(p) => p,
+            Self::Conflict(p) => p,
         }
     }
 }
@@ -137,6 +236,7 @@ where
     match &post {
         DiffPost::New(p) => insert_post(p, &resources, db).await,
         DiffPost::Updated(p) => update_post(p, &resources, db).await,
+        DiffPost::Conflict(_) => unreachable!("conflicted posts are never applied"),
     }
 }
 
@@ -191,3 +291,93 @@ where
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use ublog_data::fixtures::PostBuilder;
+    use ublog_notion::blog::POST_SOURCE;
+
+    use super::*;
+
+    fn create_notion_post(update_timestamp: i64) -> NotionPost {
+        NotionPost {
+            notion_page_id: String::from("page"),
+            post: PostBuilder::new("post")
+                .update_timestamp(update_timestamp)
+                .source(POST_SOURCE, update_timestamp)
+                .build(),
+        }
+    }
+
+    /// Sync version 100 of the post from Notion, then edit it locally at 150.
+    async fn create_locally_edited_db() -> Database<SqliteStorage> {
+        let db = Database::new(SqliteStorage::new_memory().unwrap());
+        db.insert_post(&create_notion_post(100).post, &[])
+            .await
+            .unwrap();
+
+        let mut post = db.get_post("post").await.unwrap().unwrap();
+        post.title = String::from("edited");
+        post.update_timestamp = 150;
+        db.update_post(&post, &[]).await.unwrap();
+
+        db
+    }
+
+    async fn diff(
+        db: &Database<SqliteStorage>,
+        remote_update_timestamp: i64,
+        resolutions: &HashMap<String, ConflictResolution>,
+    ) -> Vec<DiffPost> {
+        filter_diff_posts(
+            vec![create_notion_post(remote_update_timestamp)],
+            db,
+            resolutions,
+        )
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_sync_unedited_post() {
+        let db = Database::new(SqliteStorage::new_memory().unwrap());
+        let diff_posts = diff(&db, 100, &HashMap::new()).await;
+        assert!(diff_posts[0].is_new());
+
+        db.insert_post(&create_notion_post(100).post, &[])
+            .await
+            .unwrap();
+        assert!(diff(&db, 100, &HashMap::new()).await.is_empty());
+
+        let diff_posts = diff(&db, 200, &HashMap::new()).await;
+        assert!(diff_posts[0].is_updated());
+    }
+
+    #[tokio::test]
+    async fn test_sync_locally_edited_post() {
+        let db = create_locally_edited_db().await;
+
+        // The local edits are kept as long as the post is not changed in Notion.
+        assert!(diff(&db, 100, &HashMap::new()).await.is_empty());
+
+        let diff_posts = diff(&db, 120, &HashMap::new()).await;
+        assert_eq!(diff_posts.len(), 1);
+        assert!(diff_posts[0].is_conflict());
+    }
+
+    #[tokio::test]
+    async fn test_resolve_conflict() {
+        let db = create_locally_edited_db().await;
+
+        let resolutions = get_conflict_resolutions(&[String::from("post")], &[]).unwrap();
+        let diff_posts = diff(&db, 120, &resolutions).await;
+        assert!(diff_posts[0].is_updated());
+
+        let resolutions = get_conflict_resolutions(&[], &[String::from("post")]).unwrap();
+        assert!(diff(&db, 120, &resolutions).await.is_empty());
+
+        assert!(
+            get_conflict_resolutions(&[String::from("post")], &[String::from("post")]).is_err()
+        );
+    }
+}
@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use ublog_data::time_zone::TimeZone;
 
 /// Provide information about the served site.
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -10,4 +11,26 @@ pub(crate) struct SiteConfig {
     pub(crate) url: String,
     pub(crate) copyright: String,
     pub(crate) post_url_template: String,
+
+    /// The site's time zone, given by its IANA name such as `Europe/Berlin`.
+    ///
+    /// The time zone is used to group posts by day and month, following its daylight saving time rules.
+    #[serde(default)]
+    pub(crate) time_zone: TimeZone,
+}
+
+impl SiteConfig {
+    /// Create a site config for tests, whose posts are served under `https://example.com/posts/`.
+    #[cfg(test)]
+    pub(crate) fn new_test() -> Self {
+        Self {
+            title: String::from("Blog"),
+            owner: String::from("msr"),
+            owner_email: String::from("msr@example.com"),
+            url: String::from("https://example.com"),
+            copyright: String::from("msr"),
+            post_url_template: String::from("https://example.com/posts/${slug}"),
+            time_zone: TimeZone::utc(),
+        }
+    }
 }
@@ -3,10 +3,13 @@ use std::sync::Arc;
 
 use rss::{
     CategoryBuilder as RssCategoryBuilder, Channel as RssChannel,
-    ChannelBuilder as RssChannelBuilder, ItemBuilder as RssItemBuilder,
+    ChannelBuilder as RssChannelBuilder, GuidBuilder as RssGuidBuilder, Item as RssItem,
+    ItemBuilder as RssItemBuilder,
 };
-use time::format_description::well_known::Rfc2822;
+use serde::Serialize;
+use time::format_description::well_known::{Rfc2822, Rfc3339};
 use time::OffsetDateTime;
+use ublog_data::clock::Clock;
 use ublog_data::models::Post;
 use ublog_data::storage::Pagination;
 
@@ -26,36 +29,216 @@ pub(super) async fn compute_rss(ctx: Arc<ServerContext>) -> Result<RssChannel, B
         .title(ctx.site.title.clone())
         .link(ctx.site.url.clone())
         .copyright(ctx.site.copyright.clone())
-        .last_build_date(OffsetDateTime::now_utc().format(&Rfc2822).unwrap())
+        .last_build_date(
+            OffsetDateTime::from_unix_timestamp(ctx.clock.now_unix_timestamp())
+                .unwrap()
+                .format(&Rfc2822)
+                .unwrap(),
+        )
         .generator(String::from("ublog"));
 
     for p in &posts.objects {
-        let item = RssItemBuilder::default()
-            .title(p.title.clone())
-            .link(create_post_url(&ctx.site, p))
-            .author(ctx.site.owner.clone())
-            .category(
-                RssCategoryBuilder::default()
-                    .name(p.category.clone())
-                    .build(),
-            )
-            .pub_date(
-                OffsetDateTime::from_unix_timestamp(p.update_timestamp)
-                    .unwrap()
-                    .format(&Rfc2822)
-                    .unwrap(),
-            )
-            .build();
-
-        channel_builder.item(item);
+        channel_builder.item(create_rss_item(&ctx.site, p));
     }
 
     let channel = channel_builder.build();
     Ok(channel)
 }
 
+fn create_rss_item(site: &SiteConfig, post: &Post) -> RssItem {
+    // Link posts link out to the page they point to, and syndicated posts link to their originals. Either way, items are
+    // identified by their local URLs.
+    let post_url = create_post_url(site, post);
+    let link = post
+        .link_url
+        .clone()
+        .or_else(|| post.canonical_url.clone())
+        .unwrap_or_else(|| post_url.clone());
+
+    RssItemBuilder::default()
+        .title(post.display_title())
+        .link(link)
+        .guid(
+            RssGuidBuilder::default()
+                .value(post_url)
+                .permalink(true)
+                .build(),
+        )
+        .author(site.owner.clone())
+        .category(
+            RssCategoryBuilder::default()
+                .name(post.category.clone())
+                .build(),
+        )
+        .pub_date(
+            OffsetDateTime::from_unix_timestamp(post.update_timestamp)
+                .unwrap()
+                .format(&Rfc2822)
+                .unwrap(),
+        )
+        .build()
+}
+
+/// A JSON Feed 1.1 document.
+#[derive(Clone, Debug, Serialize)]
+pub(super) struct JsonFeed {
+    version: &'static str,
+    title: String,
+    home_page_url: String,
+    authors: Vec<JsonFeedAuthor>,
+    items: Vec<JsonFeedItem>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+struct JsonFeedAuthor {
+    name: String,
+}
+
+#[derive(Clone, Debug, Serialize)]
+struct JsonFeedItem {
+    id: String,
+    url: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    external_url: Option<String>,
+    title: String,
+    content_text: String,
+    date_published: String,
+    date_modified: String,
+    tags: Vec<String>,
+}
+
+pub(super) async fn compute_json_feed(ctx: Arc<ServerContext>) -> Result<JsonFeed, Box<dyn Error>> {
+    let pagination = Pagination::from_page_and_size(1, JSON_FEED_POSTS);
+    let posts = ctx.db.get_posts(false, &pagination).await.map_err(|err| {
+        spdlog::error!("Get posts list from database for JSON Feed failed: {}", err);
+        Box::<dyn Error>::from(err)
+    })?;
+
+    // Posts in a list come without their content, which the feed items carry.
+    let slugs: Vec<_> = posts.objects.into_iter().map(|p| p.slug).collect();
+    let posts = ctx
+        .db
+        .get_posts_by_slugs(&slugs, true)
+        .await
+        .map_err(|err| {
+            spdlog::error!("Get posts from database for JSON Feed failed: {}", err);
+            Box::<dyn Error>::from(err)
+        })?;
+
+    let items = posts
+        .posts
+        .iter()
+        .map(|p| create_json_feed_item(&ctx.site, p))
+        .collect();
+
+    Ok(JsonFeed {
+        version: JSON_FEED_VERSION,
+        title: ctx.site.title.clone(),
+        home_page_url: ctx.site.url.clone(),
+        authors: vec![JsonFeedAuthor {
+            name: ctx.site.owner.clone(),
+        }],
+        items,
+    })
+}
+
+fn create_json_feed_item(site: &SiteConfig, post: &Post) -> JsonFeedItem {
+    // Syndicated posts point at their originals, while their IDs stay local.
+    JsonFeedItem {
+        id: post.slug.clone(),
+        url: post
+            .canonical_url
+            .clone()
+            .unwrap_or_else(|| create_post_url(site, post)),
+        external_url: post.link_url.clone(),
+        title: post.display_title(),
+        content_text: post.content.to_plain_text(),
+        date_published: format_rfc3339(post.create_timestamp),
+        date_modified: format_rfc3339(post.update_timestamp),
+        tags: post.tags.clone(),
+    }
+}
+
+fn format_rfc3339(timestamp: i64) -> String {
+    OffsetDateTime::from_unix_timestamp(timestamp)
+        .unwrap()
+        .format(&Rfc3339)
+        .unwrap()
+}
+
 fn create_post_url(site: &SiteConfig, post: &Post) -> String {
     site.post_url_template.replace("${slug}", &post.slug)
 }
 
 const RSS_POSTS: usize = 50;
+
+const JSON_FEED_POSTS: usize = 50;
+const JSON_FEED_VERSION: &str = "https://jsonfeed.org/version/1.1";
+
+#[cfg(test)]
+mod tests {
+    use ublog_data::clock::FixedClock;
+    use ublog_data::fixtures::PostBuilder;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_compute_json_feed() {
+        let ctx = Arc::new(ServerContext::new_test(Arc::new(FixedClock::new(0))));
+        for i in 0..3 {
+            let post = PostBuilder::new(format!("post{}", i)).timestamp(i).build();
+            ctx.db.insert_post(&post, &[]).await.unwrap();
+        }
+        let special_post = PostBuilder::new("about").special(true).build();
+        ctx.db.insert_post(&special_post, &[]).await.unwrap();
+
+        let feed = compute_json_feed(ctx).await.unwrap();
+        let feed: serde_json::Value =
+            serde_json::from_slice(&serde_json::to_vec(&feed).unwrap()).unwrap();
+        assert_eq!(feed["version"], "https://jsonfeed.org/version/1.1");
+        assert_eq!(feed["title"], "Blog");
+
+        let items = feed["items"].as_array().unwrap();
+        assert_eq!(items.len(), 3);
+        assert_eq!(items[0]["id"], "post2");
+        assert_eq!(items[0]["url"], "https://example.com/posts/post2");
+        assert_eq!(items[0]["date_published"], "1970-01-01T00:00:02Z");
+    }
+
+    #[test]
+    fn test_feed_items() {
+        let site = SiteConfig::new_test();
+        let post = PostBuilder::new("local").build();
+
+        let item = create_rss_item(&site, &post);
+        assert_eq!(item.link(), Some("https://example.com/posts/local"));
+        assert_eq!(
+            item.guid().unwrap().value(),
+            "https://example.com/posts/local"
+        );
+
+        let item = create_json_feed_item(&site, &post);
+        assert_eq!(item.id, "local");
+        assert_eq!(item.url, "https://example.com/posts/local");
+    }
+
+    #[test]
+    fn test_feed_items_canonical_url() {
+        let site = SiteConfig::new_test();
+        let post = PostBuilder::new("syndicated")
+            .canonical_url("https://elsewhere.example.org/original")
+            .build();
+
+        let item = create_rss_item(&site, &post);
+        assert_eq!(item.link(), Some("https://elsewhere.example.org/original"));
+        assert_eq!(
+            item.guid().unwrap().value(),
+            "https://example.com/posts/syndicated"
+        );
+
+        let item = create_json_feed_item(&site, &post);
+        assert_eq!(item.id, "syndicated");
+        assert_eq!(item.url, "https://elsewhere.example.org/original");
+        assert_eq!(item.external_url, None);
+    }
+}
@@ -0,0 +1,246 @@
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::response::{IntoResponse, Response};
+use axum::{Extension, Json};
+use hyper::StatusCode;
+use serde::Serialize;
+use ublog_data::clock::Clock;
+
+use crate::server::ServerContext;
+
+/// Run the background worker of the server.
+///
+/// The worker first warms the server up. Afterwards, it wakes up periodically to recompute the feed caches once they
+/// expire, so that requests rarely wait for a feed to be computed. The worker beats its heartbeat after each round of
+/// work.
+pub(super) async fn run_worker(ctx: Arc<ServerContext>) {
+    warmup(ctx.clone()).await;
+
+    loop {
+        tokio::time::sleep(WORKER_INTERVAL).await;
+        prime_feed_caches(&ctx).await;
+        ctx.worker_heartbeat.beat(&*ctx.clock);
+    }
+}
+
+/// Warm up the server so that the first requests after a deploy are not slow.
+///
+/// The feed caches are primed and sqlite's statement cache is populated by running the queries behind them. The server
+/// reports itself as ready once the warmup finishes, even if some of its steps fail.
+pub(super) async fn warmup(ctx: Arc<ServerContext>) {
+    spdlog::info!("Warming up server");
+
+    prime_feed_caches(&ctx).await;
+
+    ctx.ready.store(true, Ordering::Release);
+    ctx.worker_heartbeat.beat(&*ctx.clock);
+    spdlog::info!("Server warmup finished");
+}
+
+async fn prime_feed_caches(ctx: &Arc<ServerContext>) {
+    if let Err(err) = ctx
+        .rss_cache
+        .get(|| crate::server::feed::compute_rss(ctx.clone()))
+        .await
+    {
+        spdlog::warn!("Prime RSS cache failed: {}", err);
+    }
+
+    if let Err(err) = ctx
+        .json_feed_cache
+        .get(|| crate::server::feed::compute_json_feed(ctx.clone()))
+        .await
+    {
+        spdlog::warn!("Prime JSON feed cache failed: {}", err);
+    }
+}
+
+/// The interval between two rounds of work of the background worker.
+const WORKER_INTERVAL: Duration = Duration::from_secs(60);
+
+/// The age after which the heartbeat of the background worker is considered stale, in seconds.
+///
+/// A worker whose last heartbeat is older than this has missed several rounds of work and is considered stuck.
+const WORKER_HEARTBEAT_TIMEOUT: i64 = 5 * WORKER_INTERVAL.as_secs() as i64;
+
+/// The time at which the background worker last finished a round of work.
+#[derive(Debug)]
+pub(super) struct Heartbeat {
+    timestamp: AtomicI64,
+}
+
+impl Heartbeat {
+    /// Create a new `Heartbeat` that has not beaten yet.
+    pub(super) fn new() -> Self {
+        Self {
+            timestamp: AtomicI64::new(NO_HEARTBEAT),
+        }
+    }
+
+    /// Record a heartbeat at the current time of the given clock.
+    pub(super) fn beat(&self, clock: &dyn Clock) {
+        self.timestamp
+            .store(clock.now_unix_timestamp(), Ordering::Release);
+    }
+
+    /// Get the age of the last heartbeat in seconds at the current time of the given clock.
+    ///
+    /// Returns `None` if there has been no heartbeat yet.
+    fn age(&self, clock: &dyn Clock) -> Option<i64> {
+        match self.timestamp.load(Ordering::Acquire) {
+            NO_HEARTBEAT => None,
+            timestamp => Some(clock.now_unix_timestamp() - timestamp),
+        }
+    }
+}
+
+impl Default for Heartbeat {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+const NO_HEARTBEAT: i64 = i64::MIN;
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct HealthReport {
+    status: HealthStatus,
+    components: BTreeMap<&'static str, ComponentHealth>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ComponentHealth {
+    status: HealthStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    message: Option<String>,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+enum HealthStatus {
+    Up,
+    Down,
+}
+
+impl ComponentHealth {
+    fn up(message: Option<String>) -> Self {
+        Self {
+            status: HealthStatus::Up,
+            message,
+        }
+    }
+
+    fn down(message: String) -> Self {
+        Self {
+            status: HealthStatus::Down,
+            message: Some(message),
+        }
+    }
+}
+
+/// Report the health of the server and of each of its components.
+///
+/// Responds with 200 if every component is up, and with 503 otherwise.
+pub(super) async fn get_health(Extension(ctx): Extension<Arc<ServerContext>>) -> Response {
+    let mut components = BTreeMap::new();
+
+    let warmup = if ctx.ready.load(Ordering::Acquire) {
+        ComponentHealth::up(None)
+    } else {
+        ComponentHealth::down(String::from("warmup in progress"))
+    };
+    components.insert("warmup", warmup);
+
+    let worker = match ctx.worker_heartbeat.age(&*ctx.clock) {
+        Some(age) if age <= WORKER_HEARTBEAT_TIMEOUT => {
+            ComponentHealth::up(Some(format!("last heartbeat {} seconds ago", age)))
+        }
+        Some(age) => ComponentHealth::down(format!("last heartbeat {} seconds ago", age)),
+        None => ComponentHealth::down(String::from("no heartbeat yet")),
+    };
+    components.insert("worker", worker);
+
+    // Reading the applied schema migrations is a trivial query that proves the database is reachable.
+    let database = match ctx.db.schema_info().await {
+        Ok(migrations) => {
+            let version = migrations.last().map(|(version, _)| *version).unwrap_or(0);
+            ComponentHealth::up(Some(format!("schema version {}", version)))
+        }
+        Err(err) => {
+            spdlog::error!("Health check of database failed: {}", err);
+            ComponentHealth::down(err.to_string())
+        }
+    };
+    components.insert("database", database);
+
+    let all_up = components
+        .values()
+        .all(|component| component.status == HealthStatus::Up);
+    let (status_code, status) = if all_up {
+        (StatusCode::OK, HealthStatus::Up)
+    } else {
+        (StatusCode::SERVICE_UNAVAILABLE, HealthStatus::Down)
+    };
+
+    (status_code, Json(HealthReport { status, components })).into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use ublog_data::clock::MockClock;
+
+    use super::*;
+
+    async fn request_health(ctx: &Arc<ServerContext>) -> (StatusCode, serde_json::Value) {
+        let response = get_health(Extension(ctx.clone())).await;
+        let status = response.status();
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        (status, serde_json::from_slice(&body).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_ready_after_warmup() {
+        let ctx = Arc::new(ServerContext::new_test(Arc::new(MockClock::new(1000))));
+
+        let (status, report) = request_health(&ctx).await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(report["status"], "down");
+        assert_eq!(report["components"]["warmup"]["status"], "down");
+        assert_eq!(report["components"]["database"]["status"], "up");
+
+        warmup(ctx.clone()).await;
+
+        let (status, report) = request_health(&ctx).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(report["status"], "up");
+        assert_eq!(report["components"]["warmup"]["status"], "up");
+        assert_eq!(report["components"]["worker"]["status"], "up");
+    }
+
+    #[tokio::test]
+    async fn test_stale_worker_heartbeat() {
+        let clock = MockClock::new(1000);
+        let ctx = Arc::new(ServerContext::new_test(Arc::new(clock.clone())));
+        warmup(ctx.clone()).await;
+
+        clock.advance(WORKER_HEARTBEAT_TIMEOUT);
+        let (status, _) = request_health(&ctx).await;
+        assert_eq!(status, StatusCode::OK);
+
+        clock.advance(1);
+        let (status, report) = request_health(&ctx).await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(report["components"]["worker"]["status"], "down");
+        assert_eq!(report["components"]["warmup"]["status"], "up");
+        assert_eq!(report["components"]["database"]["status"], "up");
+
+        ctx.worker_heartbeat.beat(&clock);
+        let (status, _) = request_health(&ctx).await;
+        assert_eq!(status, StatusCode::OK);
+    }
+}
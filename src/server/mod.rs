@@ -1,19 +1,27 @@
 pub(crate) mod config;
 mod feed;
+mod health;
 mod router;
+mod shutdown;
 
 use std::error::Error;
 use std::net::{IpAddr, SocketAddr};
 use std::path::Path;
+use std::sync::atomic::AtomicBool;
 use std::sync::Arc;
+use std::time::Duration;
 
 use axum::Server;
 use hyper::server::conn::AddrIncoming;
 use rss::Channel as RssChannel;
+use ublog_data::clock::{Clock, SystemClock};
 use ublog_data::db::Database;
-use ublog_data::storage::sqlite::SqliteStorage;
+use ublog_data::storage::sqlite::{SqliteCheckpointMode, SqliteOpenOptions, SqliteStorage};
 
 use crate::server::config::SiteConfig;
+use crate::server::feed::JsonFeed;
+use crate::server::health::Heartbeat;
+use crate::server::shutdown::Shutdown;
 use crate::utils::cache::Cache;
 use crate::{fallible_step, ServerArgs};
 
@@ -25,17 +33,49 @@ pub(crate) async fn serve(args: &ServerArgs) -> Result<(), Box<dyn Error>> {
 
     let site = fallible_step!("load site config", load_site_config(&args.site).await);
 
+    let clock: Arc<dyn Clock> = Arc::new(SystemClock);
     let storage = fallible_step!(
         "initialize database storage",
-        SqliteStorage::new_file(&args.database)
+        SqliteStorage::new_file_with_clock(
+            &args.database,
+            &SqliteOpenOptions::default(),
+            clock.clone()
+        )
     );
+    if let Err(err) = storage.warm_statement_cache() {
+        spdlog::warn!("Warm up statement cache failed: {}", err);
+    }
 
     let ctx = ServerContext {
         site,
         db: Database::new(storage),
-        rss_cache: Cache::new(RSS_CACHE_EXPIRE),
+        rss_cache: Cache::new(RSS_CACHE_EXPIRE, clock.clone()),
+        json_feed_cache: Cache::new(RSS_CACHE_EXPIRE, clock.clone()),
+        clock,
+        ready: AtomicBool::new(false),
+        worker_heartbeat: Heartbeat::new(),
     };
-    let router = crate::server::router::create_router(Arc::new(ctx));
+    let ctx = Arc::new(ctx);
+    let router = crate::server::router::create_router(ctx.clone());
+    tokio::spawn(crate::server::health::run_worker(ctx.clone()));
+
+    let mut shutdown = Shutdown::new(Duration::from_secs(args.shutdown_timeout));
+    let db_ctx = ctx.clone();
+    shutdown.register("database", move || async move {
+        if let Err(err) = db_ctx.db.storage().optimize() {
+            spdlog::error!("Optimize database failed: {}", err);
+        }
+    });
+    // Checkpointing last moves everything written before, including by the optimization, into the database file.
+    shutdown.register("write-ahead log", move || async move {
+        match ctx.db.storage().checkpoint(SqliteCheckpointMode::Truncate) {
+            Ok(Some(checkpoint)) if checkpoint.busy => {
+                spdlog::warn!("Checkpoint write-ahead log incomplete: database is busy");
+            }
+            Ok(_) => {}
+            Err(err) => spdlog::error!("Checkpoint write-ahead log failed: {}", err),
+        }
+    });
 
     let addr: IpAddr = fallible_step!("parse server address", args.addr.parse());
     let server_addr = SocketAddr::new(addr, args.port);
@@ -46,9 +86,12 @@ pub(crate) async fn serve(args: &ServerArgs) -> Result<(), Box<dyn Error>> {
         "run server",
         Server::builder(acceptor)
             .serve(router.into_make_service())
+            .with_graceful_shutdown(shutdown.signal())
             .await
     );
 
+    shutdown.flush().await;
+
     Ok(())
 }
 
@@ -57,7 +100,8 @@ where
     P: AsRef<Path>,
 {
     let config_json = fallible_step!("read site config", tokio::fs::read_to_string(path).await);
-    let config = fallible_step!("parse site config", serde_json::from_str(&config_json));
+    let config: SiteConfig =
+        fallible_step!("parse site config", serde_json::from_str(&config_json));
     Ok(config)
 }
 
@@ -66,7 +110,34 @@ struct ServerContext {
     site: SiteConfig,
     db: Database<SqliteStorage>,
     rss_cache: Cache<RssChannel>,
+    json_feed_cache: Cache<JsonFeed>,
+
+    /// The clock from which the server reads the current time.
+    clock: Arc<dyn Clock>,
+
+    /// Whether the server has finished warming up.
+    ready: AtomicBool,
+
+    /// The heartbeat of the background worker.
+    worker_heartbeat: Heartbeat,
+}
+
+#[cfg(test)]
+impl ServerContext {
+    /// Create a context for tests over an empty in-memory database that reads the current time from the given clock.
+    fn new_test(clock: Arc<dyn Clock>) -> Self {
+        let storage = SqliteStorage::new_memory_with_clock(clock.clone()).unwrap();
+        Self {
+            site: SiteConfig::new_test(),
+            db: Database::new(storage),
+            rss_cache: Cache::new(RSS_CACHE_EXPIRE, clock.clone()),
+            json_feed_cache: Cache::new(RSS_CACHE_EXPIRE, clock.clone()),
+            clock,
+            ready: AtomicBool::new(false),
+            worker_heartbeat: Heartbeat::new(),
+        }
+    }
 }
 
-// RSS cache expire time is 10 minutes.
+// RSS and JSON Feed cache expire time is 10 minutes.
 const RSS_CACHE_EXPIRE: u64 = 600;
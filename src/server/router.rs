@@ -7,12 +7,17 @@ use axum::{Extension, Json, Router};
 use http::{HeaderMap, HeaderValue};
 use hyper::StatusCode;
 use rss::Channel as RssChannel;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use tower_http::cors::{Any, CorsLayer};
-use ublog_data::models::{Post, Resource};
-use ublog_data::storage::{PaginatedList, Pagination};
+use ublog_data::models::{Post, PostKind, Resource};
+use ublog_data::storage::sqlite::SqliteStorageError;
+use ublog_data::storage::{
+    ArchiveEntry, PaginatedList, Pagination, PostsBySlugs, SlugSuggestion, MAX_PAGE_SIZE,
+};
+use ublog_doc::DocumentNode;
 use uuid::Uuid;
 
+use crate::server::feed::JsonFeed;
 use crate::server::ServerContext;
 
 /// Create a router for the server.
@@ -20,8 +25,12 @@ pub(super) fn create_router(ctx: Arc<ServerContext>) -> Router {
     Router::new()
         .route("/api/posts", get(get_posts))
         .route("/api/posts/:slug", get(get_post))
+        .route("/api/archive", get(get_archive_index))
+        .route("/api/archive/:year/:month", get(get_archive_posts))
         .route("/api/resources/:id", get(get_resource))
         .route("/api/rss", get(get_rss))
+        .route("/api/feed.json", get(get_json_feed))
+        .route("/healthz", get(crate::server::health::get_health))
         .layer(CorsLayer::new().allow_methods(Any).allow_origin(Any))
         .layer(Extension(ctx))
 }
@@ -34,44 +43,314 @@ struct PaginationParams {
     items: Option<usize>,
 }
 
+#[derive(Clone, Debug, Deserialize)]
+struct SlugsParams {
+    #[serde(default)]
+    slugs: Option<String>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+struct KindParams {
+    #[serde(default)]
+    kind: Option<PostKind>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+struct FieldsParams {
+    #[serde(default)]
+    fields: Option<String>,
+}
+
+impl FieldsParams {
+    /// Parse the comma-separated list of post fields requested by the client.
+    ///
+    /// Returns `None` if the client does not select fields. Field names are those of the serialized `Post`; the
+    /// `content` field is only accepted if `allow_content` is set.
+    fn to_post_fields(&self, allow_content: bool) -> Result<Option<Vec<String>>, ErrorResponse> {
+        let fields = match &self.fields {
+            Some(fields) => fields,
+            None => return Ok(None),
+        };
+
+        let known_fields = get_post_field_names();
+        let mut selected_fields = Vec::new();
+        for field in fields.split(',').filter(|field| !field.is_empty()) {
+            let is_known = known_fields.iter().any(|known| known == field);
+            if !is_known || (!allow_content && field == POST_CONTENT_FIELD) {
+                spdlog::warn!("Invalid post field from client: {}", field);
+                let message = format!("unknown field: {}", field);
+                return Err(ErrorResponse::new(StatusCode::BAD_REQUEST, message));
+            }
+
+            selected_fields.push(String::from(field));
+        }
+
+        Ok(Some(selected_fields))
+    }
+}
+
+const POST_CONTENT_FIELD: &str = "content";
+
+/// Get the names of the fields of a serialized `Post`.
+fn get_post_field_names() -> Vec<String> {
+    let post = Post {
+        title: String::new(),
+        slug: String::new(),
+        author: String::new(),
+        create_timestamp: 0,
+        update_timestamp: 0,
+        category: String::new(),
+        tags: Vec::new(),
+        is_special: false,
+        kind: PostKind::Article,
+        link_url: None,
+        canonical_url: None,
+        source: None,
+        source_version: None,
+        word_count: 0,
+        content: DocumentNode::new_empty(),
+    };
+
+    match serde_json::to_value(&post).unwrap() {
+        serde_json::Value::Object(post) => post.into_iter().map(|(key, _)| key).collect(),
+        _ => unreachable!(),
+    }
+}
+
+/// Serialize the given value and keep only the selected fields of the posts within it.
+///
+/// `posts_key` names the field of the serialized value that holds the list of posts. If `posts_key` is `None`, the
+/// serialized value itself is a post.
+fn select_post_fields<T>(value: &T, posts_key: Option<&str>, fields: &[String]) -> Response
+where
+    T: Serialize,
+{
+    let retain_fields = |post: &mut serde_json::Value| {
+        if let Some(post) = post.as_object_mut() {
+            post.retain(|key, _| fields.iter().any(|field| field == key));
+        }
+    };
+
+    let mut value = serde_json::to_value(value).unwrap();
+    match posts_key {
+        Some(posts_key) => {
+            if let Some(posts) = value[posts_key].as_array_mut() {
+                posts.iter_mut().for_each(retain_fields);
+            }
+        }
+        None => retain_fields(&mut value),
+    }
+
+    Json(value).into_response()
+}
+
 const DEFAULT_PAGE: usize = 1;
 const DEFAULT_ITEMS_PER_PAGE: usize = 20;
 
+impl PaginationParams {
+    fn to_pagination(&self) -> Result<Pagination, StatusCode> {
+        let page = self.page.unwrap_or(DEFAULT_PAGE);
+        let items = self.items.unwrap_or(DEFAULT_ITEMS_PER_PAGE);
+        if page == 0 || items == 0 || items > MAX_PAGE_SIZE {
+            spdlog::warn!(
+                "Invalid pagination from client: page {}, items {}",
+                page,
+                items
+            );
+            return Err(StatusCode::BAD_REQUEST);
+        }
+
+        Ok(Pagination::from_page_and_size(page, items))
+    }
+}
+
 async fn get_posts(
     Extension(ctx): Extension<Arc<ServerContext>>,
     Query(pagination): Query<PaginationParams>,
+    Query(slugs): Query<SlugsParams>,
+    Query(kind): Query<KindParams>,
+    Query(fields): Query<FieldsParams>,
+) -> Result<Response, ErrorResponse> {
+    let fields = fields.to_post_fields(false)?;
+
+    if let Some(slugs) = slugs.slugs {
+        let posts = get_posts_by_slugs(&ctx, &slugs)
+            .await
+            .map_err(ErrorResponse::from_status)?;
+        return Ok(match fields {
+            Some(fields) => select_post_fields(&posts.0, Some("posts"), &fields),
+            None => posts.into_response(),
+        });
+    }
+
+    let posts = get_posts_list(&ctx, &pagination, kind.kind)
+        .await
+        .map_err(ErrorResponse::from_status)?;
+    Ok(match fields {
+        Some(fields) => select_post_fields(&posts.0, Some("objects"), &fields),
+        None => posts.into_response(),
+    })
+}
+
+async fn get_posts_list(
+    ctx: &ServerContext,
+    pagination: &PaginationParams,
+    kind: Option<PostKind>,
 ) -> Result<Json<PaginatedList<Post>>, StatusCode> {
-    let page = pagination.page.unwrap_or(DEFAULT_PAGE);
-    let items = pagination.items.unwrap_or(DEFAULT_ITEMS_PER_PAGE);
-    let pagination = Pagination::from_page_and_size(page, items);
+    let pagination = pagination.to_pagination()?;
+    let page = pagination.page();
+    let items = pagination.page_size();
+
+    let posts = match kind {
+        Some(kind) => ctx.db.get_posts_of_kind(false, kind, &pagination).await,
+        None => ctx.db.get_posts(false, &pagination).await,
+    };
+    posts.map(Json).map_err(|err| {
+        spdlog::error!(
+            "Get posts list from database failed: {} (page {}, items {})",
+            err,
+            page,
+            items
+        );
+        StatusCode::INTERNAL_SERVER_ERROR
+    })
+}
+
+async fn get_posts_by_slugs(
+    ctx: &ServerContext,
+    slugs: &str,
+) -> Result<Json<PostsBySlugs>, StatusCode> {
+    let slugs: Vec<String> = slugs
+        .split(',')
+        .filter(|slug| !slug.is_empty())
+        .map(String::from)
+        .collect();
 
     ctx.db
-        .get_posts(false, &pagination)
+        .get_posts_by_slugs(&slugs, false)
         .await
         .map(Json)
+        .map_err(|err| match err {
+            SqliteStorageError::TooManySlugs(_) => {
+                spdlog::warn!("Invalid posts selection from client: {}", err);
+                StatusCode::BAD_REQUEST
+            }
+            _ => {
+                spdlog::error!("Get posts by slugs from database failed: {}", err);
+                StatusCode::INTERNAL_SERVER_ERROR
+            }
+        })
+}
+
+async fn get_post(
+    Extension(ctx): Extension<Arc<ServerContext>>,
+    Path((slug,)): Path<(String,)>,
+    Query(fields): Query<FieldsParams>,
+) -> Result<Response, ErrorResponse> {
+    let fields = fields.to_post_fields(true)?;
+
+    // Selecting the post by its slug allows skipping the content column when the content is not requested.
+    let with_content = selects_content(fields.as_deref());
+    let post = ctx
+        .db
+        .get_posts_by_slugs(std::slice::from_ref(&slug), with_content)
+        .await
         .map_err(|err| {
+            spdlog::error!("Get post from database failed: {} (slug {})", err, slug);
+            ErrorResponse::from_status(StatusCode::INTERNAL_SERVER_ERROR)
+        })?
+        .posts
+        .pop();
+    let post = match post {
+        Some(post) => post,
+        None => return Err(post_not_found(&ctx, &slug).await),
+    };
+
+    Ok(match fields {
+        Some(fields) => select_post_fields(&post, None, &fields),
+        None => Json(post).into_response(),
+    })
+}
+
+/// Determine whether the given selection of post fields includes the content.
+///
+/// Not selecting fields selects all of them, including the content.
+fn selects_content(fields: Option<&[String]>) -> bool {
+    fields.is_none_or(|fields| fields.iter().any(|field| field == POST_CONTENT_FIELD))
+}
+
+const MAX_SLUG_SUGGESTIONS: usize = 5;
+
+/// Build the response to a request for a post that does not exist, suggesting posts with similar slugs.
+async fn post_not_found(ctx: &ServerContext, slug: &str) -> ErrorResponse {
+    // Failing to find suggestions should not turn a 404 into a 500.
+    let suggestions = ctx
+        .db
+        .get_similar_slugs(slug, MAX_SLUG_SUGGESTIONS)
+        .await
+        .unwrap_or_else(|err| {
             spdlog::error!(
-                "Get posts list from database failed: {} (page {}, items {})",
+                "Get similar slugs from database failed: {} (slug {})",
                 err,
-                page,
-                items
+                slug
             );
-            StatusCode::INTERNAL_SERVER_ERROR
-        })
+            Vec::new()
+        });
+
+    ErrorResponse::with_suggestions(
+        StatusCode::NOT_FOUND,
+        format!("post not found: {}", slug),
+        suggestions,
+    )
 }
 
-async fn get_post(
+async fn get_archive_index(
     Extension(ctx): Extension<Arc<ServerContext>>,
-    Path((slug,)): Path<(String,)>,
-) -> Result<Json<Post>, StatusCode> {
+) -> Result<Json<Vec<ArchiveEntry>>, StatusCode> {
     ctx.db
-        .get_post(&slug)
+        .get_archive_index(false, ctx.site.time_zone)
         .await
+        .map(Json)
         .map_err(|err| {
-            spdlog::error!("Get post from database failed: {} (slug {})", err, slug);
+            spdlog::error!("Get archive index from database failed: {}", err);
             StatusCode::INTERNAL_SERVER_ERROR
         })
-        .and_then(|post| post.ok_or(StatusCode::NOT_FOUND).map(Json))
+}
+
+async fn get_archive_posts(
+    Extension(ctx): Extension<Arc<ServerContext>>,
+    Path((year, month)): Path<(i32, u8)>,
+    Query(pagination): Query<PaginationParams>,
+) -> Result<Json<PaginatedList<Post>>, StatusCode> {
+    if !(1..=12).contains(&month) {
+        spdlog::warn!("Invalid archive month from client: {}-{}", year, month);
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let pagination = pagination.to_pagination()?;
+    let page = pagination.page();
+    let items = pagination.page_size();
+    ctx.db
+        .get_archive_posts(false, year, month, ctx.site.time_zone, &pagination)
+        .await
+        .map(Json)
+        .map_err(|err| match err {
+            SqliteStorageError::InvalidMonth(..) => {
+                spdlog::warn!("Invalid archive month from client: {}", err);
+                StatusCode::BAD_REQUEST
+            }
+            _ => {
+                spdlog::error!(
+                    "Get archive posts from database failed: {} (month {}-{}, page {}, items {})",
+                    err,
+                    year,
+                    month,
+                    page,
+                    items
+                );
+                StatusCode::INTERNAL_SERVER_ERROR
+            }
+        })
 }
 
 async fn get_resource(
@@ -84,7 +363,7 @@ async fn get_resource(
     })?;
 
     ctx.db
-        .get_resource(&id)
+        .get_resource(&id, true)
         .await
         .map_err(|err| {
             spdlog::error!("Get resource from database failed: {} (id {})", err, id);
@@ -103,6 +382,72 @@ async fn get_rss(
         .map(|channel| WithContentType::from(&*channel))
 }
 
+async fn get_json_feed(
+    Extension(ctx): Extension<Arc<ServerContext>>,
+) -> Result<WithContentType<Vec<u8>>, StatusCode> {
+    ctx.json_feed_cache
+        .get(|| crate::server::feed::compute_json_feed(ctx.clone()))
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+        .map(|feed| WithContentType::from(&*feed))
+}
+
+/// An error response with a status code and a plain text message, or a JSON body when posts are suggested.
+#[derive(Clone, Debug)]
+struct ErrorResponse {
+    status: StatusCode,
+    message: String,
+    suggestions: Option<Vec<SlugSuggestion>>,
+}
+
+impl ErrorResponse {
+    fn new(status: StatusCode, message: String) -> Self {
+        Self {
+            status,
+            message,
+            suggestions: None,
+        }
+    }
+
+    fn from_status(status: StatusCode) -> Self {
+        Self::new(status, String::new())
+    }
+
+    /// Create an error response whose body is a JSON object holding the message and the suggested posts.
+    fn with_suggestions(
+        status: StatusCode,
+        message: String,
+        suggestions: Vec<SlugSuggestion>,
+    ) -> Self {
+        Self {
+            status,
+            message,
+            suggestions: Some(suggestions),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
+struct SuggestionsBody {
+    message: String,
+    suggestions: Vec<SlugSuggestion>,
+}
+
+impl IntoResponse for ErrorResponse {
+    fn into_response(self) -> Response {
+        match self.suggestions {
+            Some(suggestions) => {
+                let body = SuggestionsBody {
+                    message: self.message,
+                    suggestions,
+                };
+                (self.status, Json(body)).into_response()
+            }
+            None => (self.status, self.message).into_response(),
+        }
+    }
+}
+
 #[derive(Clone, Debug)]
 struct WithContentType<T> {
     content_type: String,
@@ -130,6 +475,15 @@ impl<'a> From<&'a RssChannel> for WithContentType<Vec<u8>> {
     }
 }
 
+impl<'a> From<&'a JsonFeed> for WithContentType<Vec<u8>> {
+    fn from(feed: &'a JsonFeed) -> Self {
+        Self {
+            content_type: String::from(JSON_FEED_CONTENT_TYPE),
+            other: serde_json::to_vec(feed).unwrap(),
+        }
+    }
+}
+
 impl<T> IntoResponse for WithContentType<T>
 where
     T: IntoResponse,
@@ -146,3 +500,149 @@ where
 }
 
 const RSS_CONTENT_TYPE: &str = "application/rss+xml";
+const JSON_FEED_CONTENT_TYPE: &str = "application/feed+json";
+
+#[cfg(test)]
+mod tests {
+    use ublog_data::clock::FixedClock;
+    use ublog_data::fixtures::PostBuilder;
+    use ublog_doc::DocumentNodeTag;
+
+    use super::*;
+
+    async fn create_context() -> Arc<ServerContext> {
+        let ctx = ServerContext::new_test(Arc::new(FixedClock::new(0)));
+
+        let mut content = DocumentNode::new_empty();
+        content
+            .children
+            .push(DocumentNode::new(DocumentNodeTag::InlineText {
+                text: String::from("hello"),
+            }));
+        let post = PostBuilder::new("post")
+            .tags(["rust"])
+            .content(content)
+            .build();
+        ctx.db.insert_post(&post, &[]).await.unwrap();
+
+        Arc::new(ctx)
+    }
+
+    async fn into_json(
+        response: Result<Response, ErrorResponse>,
+    ) -> (StatusCode, serde_json::Value) {
+        let response = response.unwrap_or_else(IntoResponse::into_response);
+        let status = response.status();
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        let value = serde_json::from_slice(&body).unwrap_or(serde_json::Value::Null);
+        (status, value)
+    }
+
+    async fn request_post(
+        ctx: &Arc<ServerContext>,
+        fields: Option<&str>,
+    ) -> (StatusCode, serde_json::Value) {
+        let fields = FieldsParams {
+            fields: fields.map(String::from),
+        };
+        let response = get_post(
+            Extension(ctx.clone()),
+            Path((String::from("post"),)),
+            Query(fields),
+        )
+        .await;
+        into_json(response).await
+    }
+
+    async fn request_posts(
+        ctx: &Arc<ServerContext>,
+        fields: Option<&str>,
+    ) -> (StatusCode, serde_json::Value) {
+        let response = get_posts(
+            Extension(ctx.clone()),
+            Query(PaginationParams {
+                page: None,
+                items: None,
+            }),
+            Query(SlugsParams { slugs: None }),
+            Query(KindParams { kind: None }),
+            Query(FieldsParams {
+                fields: fields.map(String::from),
+            }),
+        )
+        .await;
+        into_json(response).await
+    }
+
+    fn field_names(post: &serde_json::Value) -> Vec<&str> {
+        post.as_object()
+            .unwrap()
+            .keys()
+            .map(String::as_str)
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_get_post_all_fields() {
+        let ctx = create_context().await;
+
+        let (status, post) = request_post(&ctx, None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(field_names(&post).len(), get_post_field_names().len());
+        assert_eq!(post["content"]["children"].as_array().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_get_post_select_fields() {
+        let ctx = create_context().await;
+
+        let (status, post) = request_post(&ctx, Some("slug,tags")).await;
+        assert_eq!(status, StatusCode::OK);
+        let mut fields = field_names(&post);
+        fields.sort_unstable();
+        assert_eq!(fields, ["slug", "tags"]);
+        assert_eq!(post["tags"], serde_json::json!(["rust"]));
+
+        let (status, post) = request_post(&ctx, Some("title,content")).await;
+        assert_eq!(status, StatusCode::OK);
+        let mut fields = field_names(&post);
+        fields.sort_unstable();
+        assert_eq!(fields, ["content", "title"]);
+        assert_eq!(post["content"]["children"].as_array().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_get_post_unknown_field() {
+        let ctx = create_context().await;
+
+        let (status, _) = request_post(&ctx, Some("title,password")).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_get_posts_select_fields() {
+        let ctx = create_context().await;
+
+        let (status, posts) = request_posts(&ctx, Some("slug,title")).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(posts["totalCount"], 1);
+        let mut fields = field_names(&posts["objects"][0]);
+        fields.sort_unstable();
+        assert_eq!(fields, ["slug", "title"]);
+
+        // Posts in lists are selected without their content.
+        let (status, _) = request_posts(&ctx, Some("slug,content")).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+
+    #[test]
+    fn test_selects_content() {
+        assert!(selects_content(None));
+        assert!(selects_content(Some(&[String::from("content")])));
+        assert!(!selects_content(Some(&[
+            String::from("title"),
+            String::from("slug")
+        ])));
+        assert!(!selects_content(Some(&[])));
+    }
+}
@@ -0,0 +1,185 @@
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use futures::future::{BoxFuture, FutureExt};
+use tokio::task::JoinHandle;
+
+type FlushCallback = Box<dyn FnOnce() -> BoxFuture<'static, ()> + Send>;
+
+type TimeoutAction = Arc<dyn Fn() + Send + Sync>;
+
+/// Coordinate the graceful shutdown of the server.
+///
+/// Components that hold pending state register flush callbacks on the coordinator. When a shutdown signal is received,
+/// the server stops accepting new requests and drains in-flight ones, after which the registered callbacks are run in
+/// registration order. If the whole procedure does not finish within the configured deadline, the process is forcibly
+/// terminated.
+pub(super) struct Shutdown {
+    deadline: Duration,
+    callbacks: Vec<(String, FlushCallback)>,
+    on_timeout: TimeoutAction,
+    deadline_timer: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl Shutdown {
+    /// Create a new `Shutdown` coordinator with the given deadline.
+    pub(super) fn new(deadline: Duration) -> Self {
+        Self::with_timeout_action(deadline, Arc::new(|| std::process::exit(1)))
+    }
+
+    /// Create a new `Shutdown` coordinator with the given deadline, which runs the given action instead of terminating
+    /// the process when the deadline is exceeded.
+    fn with_timeout_action(deadline: Duration, on_timeout: TimeoutAction) -> Self {
+        Self {
+            deadline,
+            callbacks: Vec::new(),
+            on_timeout,
+            deadline_timer: Mutex::new(None),
+        }
+    }
+
+    /// Register a callback that flushes pending state of the named component during shutdown.
+    pub(super) fn register<N, F, R>(&mut self, name: N, callback: F)
+    where
+        N: Into<String>,
+        F: FnOnce() -> R + Send + 'static,
+        R: Future<Output = ()> + Send + 'static,
+    {
+        self.callbacks
+            .push((name.into(), Box::new(move || callback().boxed())));
+    }
+
+    /// Wait for a shutdown signal.
+    ///
+    /// Once the signal is received, the deadline timer starts running.
+    pub(super) async fn signal(&self) {
+        self.start_on(wait_for_signal()).await;
+    }
+
+    /// Wait for the given shutdown trigger, and then start the deadline timer.
+    async fn start_on<F>(&self, trigger: F)
+    where
+        F: Future<Output = ()>,
+    {
+        trigger.await;
+        spdlog::info!("Shutdown signal received, stopping HTTP server");
+
+        let deadline = self.deadline;
+        let on_timeout = self.on_timeout.clone();
+        let timer = tokio::spawn(async move {
+            tokio::time::sleep(deadline).await;
+            spdlog::error!(
+                "Graceful shutdown did not finish within {:?}, exiting",
+                deadline
+            );
+            on_timeout();
+        });
+        *self.deadline_timer.lock().unwrap() = Some(timer);
+    }
+
+    /// Run all registered flush callbacks, and then stop the deadline timer.
+    pub(super) async fn flush(self) {
+        for (name, callback) in self.callbacks {
+            spdlog::info!("Flushing {} before shutdown", name);
+            callback().await;
+        }
+
+        if let Some(timer) = self.deadline_timer.into_inner().unwrap() {
+            timer.abort();
+        }
+
+        spdlog::info!("Graceful shutdown finished");
+    }
+}
+
+#[cfg(unix)]
+async fn wait_for_signal() {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    let mut terminate = match signal(SignalKind::terminate()) {
+        Ok(terminate) => terminate,
+        Err(err) => {
+            spdlog::error!("Install SIGTERM handler failed: {}", err);
+            let _ = tokio::signal::ctrl_c().await;
+            return;
+        }
+    };
+
+    tokio::select! {
+        _ = tokio::signal::ctrl_c() => {}
+        _ = terminate.recv() => {}
+    }
+}
+
+#[cfg(not(unix))]
+async fn wait_for_signal() {
+    let _ = tokio::signal::ctrl_c().await;
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    use super::*;
+
+    fn create_shutdown(deadline: Duration) -> (Shutdown, Arc<AtomicBool>) {
+        let timed_out = Arc::new(AtomicBool::new(false));
+        let on_timeout = {
+            let timed_out = timed_out.clone();
+            Arc::new(move || timed_out.store(true, Ordering::SeqCst))
+        };
+        (
+            Shutdown::with_timeout_action(deadline, on_timeout),
+            timed_out,
+        )
+    }
+
+    #[tokio::test]
+    async fn test_flush_in_registration_order() {
+        let (mut shutdown, timed_out) = create_shutdown(Duration::from_millis(200));
+
+        let flushed = Arc::new(Mutex::new(Vec::new()));
+        for name in ["views", "deliveries", "database"] {
+            let flushed = flushed.clone();
+            shutdown.register(name, move || async move {
+                // Later callbacks finish first unless the coordinator waits for each callback in turn.
+                if name == "views" {
+                    tokio::time::sleep(Duration::from_millis(20)).await;
+                }
+                flushed.lock().unwrap().push(name);
+            });
+        }
+
+        shutdown.start_on(async {}).await;
+        shutdown.flush().await;
+        assert_eq!(
+            *flushed.lock().unwrap(),
+            ["views", "deliveries", "database"]
+        );
+
+        // The deadline timer stops once the flush finishes.
+        tokio::time::sleep(Duration::from_millis(400)).await;
+        assert!(!timed_out.load(Ordering::SeqCst));
+    }
+
+    #[tokio::test]
+    async fn test_deadline_exceeded() {
+        let (mut shutdown, timed_out) = create_shutdown(Duration::from_millis(50));
+        shutdown.register("stuck", || tokio::time::sleep(Duration::from_secs(3600)));
+
+        shutdown.start_on(async {}).await;
+        let flush = tokio::spawn(shutdown.flush());
+
+        for _ in 0..100 {
+            if timed_out.load(Ordering::SeqCst) {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert!(timed_out.load(Ordering::SeqCst));
+        assert!(!flush.is_finished());
+
+        flush.abort();
+    }
+}
@@ -0,0 +1,90 @@
+use std::error::Error;
+
+use ublog_data::storage::sqlite::{ResourceUsage, SqliteStorage};
+
+use crate::{fallible_step, StorageArgs};
+
+pub(crate) async fn storage_report(args: &StorageArgs) -> Result<(), Box<dyn Error>> {
+    let db_storage = fallible_step!(
+        "initialize database storage",
+        SqliteStorage::new_file(&args.database)
+    );
+    let report = fallible_step!("generate storage report", db_storage.storage_report());
+
+    println!(
+        "Database: {} ({} free pages of {})",
+        format_size(report.file_size),
+        report.freelist_pages,
+        format_size(report.page_size)
+    );
+    println!(
+        "Resources: {} in total, {}",
+        report.resources_count,
+        format_size(report.resources_bytes)
+    );
+
+    for post in &report.posts {
+        if post.resources.is_empty() {
+            continue;
+        }
+
+        println!();
+        println!(
+            "{}: {} resources, {}",
+            post.post_slug,
+            post.resources.len(),
+            format_size(post.resources_bytes)
+        );
+        for res in &post.resources {
+            print_resource_usage(res);
+        }
+    }
+
+    if !report.unattached_resources.is_empty() {
+        println!();
+        println!(
+            "Not attached to any post: {} resources",
+            report.unattached_resources.len()
+        );
+        for res in &report.unattached_resources {
+            print_resource_usage(res);
+        }
+    }
+
+    Ok(())
+}
+
+fn print_resource_usage(res: &ResourceUsage) {
+    let unreferenced_mark = if res.is_referenced {
+        ""
+    } else {
+        " (unreferenced)"
+    };
+    println!(
+        "    {:>10}  {} {}{}",
+        format_size(res.size),
+        res.id.as_hyphenated(),
+        res.name,
+        unreferenced_mark
+    );
+}
+
+fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = UNITS[0];
+    for next_unit in &UNITS[1..] {
+        if size < 1024.0 {
+            break;
+        }
+        size /= 1024.0;
+        unit = next_unit;
+    }
+
+    format!("{:.1} {}", size, unit)
+}
@@ -1,37 +1,41 @@
 use std::future::Future;
 use std::ops::Deref;
-use std::time::SystemTime;
+use std::sync::Arc;
 
 use tokio::sync::{Mutex, MutexGuard};
+use ublog_data::clock::Clock;
 
 #[derive(Debug)]
 pub(crate) struct Cache<T> {
     expire_secs: u64,
-    value: Mutex<Option<(SystemTime, T)>>,
+    clock: Arc<dyn Clock>,
+    value: Mutex<Option<(i64, T)>>,
 }
 
 impl<T> Cache<T> {
-    pub(crate) fn new(expire_secs: u64) -> Self {
+    pub(crate) fn new(expire_secs: u64, clock: Arc<dyn Clock>) -> Self {
         Self {
             expire_secs,
+            clock,
             value: Mutex::new(None),
         }
     }
 
-    pub(crate) async fn get<F, R, E>(&self, value_factory: F) -> Result<CachedValue<T>, E>
+    pub(crate) async fn get<F, R, E>(&self, value_factory: F) -> Result<CachedValue<'_, T>, E>
     where
         F: FnOnce() -> R,
         R: Future<Output = Result<T, E>>,
     {
         let mut lock = self.value.lock().await;
         if let Some((t, _)) = &*lock {
-            if t.elapsed().unwrap().as_secs() < self.expire_secs {
+            let elapsed_secs = self.clock.now_unix_timestamp() - *t;
+            if elapsed_secs < self.expire_secs as i64 {
                 return Ok(CachedValue { guard: lock });
             }
         }
 
         let value = value_factory().await?;
-        *lock = Some((SystemTime::now(), value));
+        *lock = Some((self.clock.now_unix_timestamp(), value));
 
         Ok(CachedValue { guard: lock })
     }
@@ -39,11 +43,11 @@ impl<T> Cache<T> {
 
 #[derive(Debug)]
 pub(crate) struct CachedValue<'a, T> {
-    guard: MutexGuard<'a, Option<(SystemTime, T)>>,
+    guard: MutexGuard<'a, Option<(i64, T)>>,
 }
 
 impl<'a, T> CachedValue<'a, T> {
-    fn cache_pair(&self) -> &(SystemTime, T) {
+    fn cache_pair(&self) -> &(i64, T) {
         (*self.guard).as_ref().unwrap()
     }
 }
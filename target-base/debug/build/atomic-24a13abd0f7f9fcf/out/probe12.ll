@@ -0,0 +1,20 @@
+; ModuleID = 'probe12.9ec0ad598aaa48b2-cgu.0'
+source_filename = "probe12.9ec0ad598aaa48b2-cgu.0"
+target datalayout = "e-m:e-p270:32:32-p271:32:32-p272:64:64-i64:64-i128:128-f80:128-n8:16:32:64-S128"
+target triple = "x86_64-unknown-linux-gnu"
+
+; probe12::probe
+; Function Attrs: nonlazybind uwtable
+define void @_ZN7probe125probe17h0d893fc1a7db2ea4E() unnamed_addr #0 {
+start:
+  ret void
+}
+
+attributes #0 = { nonlazybind uwtable "probe-stack"="inline-asm" "target-cpu"="x86-64" }
+
+!llvm.module.flags = !{!0, !1}
+!llvm.ident = !{!2}
+
+!0 = !{i32 8, !"PIC Level", i32 2}
+!1 = !{i32 2, !"RtLibUseGOT", i32 1}
+!2 = !{!"rustc version 1.95.0 (59807616e 2026-04-14)"}
@@ -0,0 +1,93 @@
+import assert from "node:assert/strict";
+import { test } from "node:test";
+
+import { renderJsonLd } from "./jsonLd";
+import { DocumentNode, Post } from "./model";
+
+const SITE_URL = "https://example.com";
+
+function createPost(content: DocumentNode): Post {
+  return {
+    title: "title",
+    slug: "post",
+    author: "msr",
+    createTimestamp: 0,
+    updateTimestamp: 0,
+    category: "",
+    tags: [],
+    isSpecial: false,
+    canonicalUrl: null,
+    wordCount: 0,
+    content,
+  };
+}
+
+function createRoot(...children: DocumentNode[]): DocumentNode {
+  return { tag: { type: "root" }, children };
+}
+
+function createParagraph(text: string): DocumentNode {
+  return {
+    tag: { type: "paragraph" },
+    children: [{ tag: { type: "inlineText", text }, children: [] }],
+  };
+}
+
+test("renders a post", () => {
+  const post: Post = {
+    ...createPost(
+      createRoot(createParagraph("three words here"), {
+        tag: { type: "image", link: { type: "embedded", uuid: "1234" }, caption: null },
+        children: [],
+      })
+    ),
+    title: "Ending </script>",
+    updateTimestamp: 86400,
+    category: "rust",
+    tags: ["async", "tokio"],
+    wordCount: 3,
+  };
+
+  assert.equal(
+    renderJsonLd(post, SITE_URL),
+    '{"@context":"https://schema.org","@type":"BlogPosting",' +
+      '"mainEntityOfPage":{"@type":"WebPage","@id":"https://example.com/posts/post"},' +
+      '"headline":"Ending \\u003c/script>","image":"https://example.com/api/resources/1234",' +
+      '"datePublished":"1970-01-01T00:00:00.000Z","dateModified":"1970-01-02T00:00:00.000Z",' +
+      '"author":{"@type":"Person","name":"msr"},"articleSection":"rust","keywords":"async, tokio","wordCount":3}'
+  );
+});
+
+test("renders a syndicated post without images", () => {
+  const post: Post = {
+    ...createPost(createRoot(createParagraph(""))),
+    canonicalUrl: "https://elsewhere.example.org/original",
+  };
+
+  assert.equal(
+    renderJsonLd(post, SITE_URL),
+    '{"@context":"https://schema.org","@type":"BlogPosting",' +
+      '"mainEntityOfPage":{"@type":"WebPage","@id":"https://elsewhere.example.org/original"},' +
+      '"headline":"title","datePublished":"1970-01-01T00:00:00.000Z","dateModified":"1970-01-01T00:00:00.000Z",' +
+      '"author":{"@type":"Person","name":"msr"},"wordCount":0}'
+  );
+});
+
+test("takes the first image of the post", () => {
+  const post = createPost(
+    createRoot(
+      createParagraph("text"),
+      {
+        tag: { type: "image", link: { type: "external", url: "https://cdn.example.org/a.png" }, caption: null },
+        children: [],
+      },
+      {
+        tag: { type: "image", link: { type: "embedded", uuid: "5678" }, caption: null },
+        children: [],
+      }
+    )
+  );
+
+  const jsonLd = JSON.parse(renderJsonLd(post, SITE_URL));
+  assert.equal(jsonLd.image, "https://cdn.example.org/a.png");
+});
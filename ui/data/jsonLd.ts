@@ -0,0 +1,58 @@
+import { DocumentNode, Post } from "./model";
+
+/**
+ * Render the schema.org `BlogPosting` structured data of the given post as JSON that is safe to embed in a `<script>`
+ * element.
+ *
+ * `siteUrl` is the public URL of the site, against which the URLs of the post page and of its embedded images are
+ * resolved.
+ */
+export function renderJsonLd(post: Post, siteUrl: string): string {
+  const postUrl = new URL(`/posts/${encodeURIComponent(post.slug)}`, siteUrl).toString();
+  const imageUrl = getFirstImageUrl(post.content, siteUrl);
+
+  const jsonLd = {
+    "@context": "https://schema.org",
+    "@type": "BlogPosting",
+    mainEntityOfPage: {
+      "@type": "WebPage",
+      "@id": post.canonicalUrl ?? postUrl,
+    },
+    headline: post.title,
+    image: imageUrl ?? undefined,
+    datePublished: new Date(post.createTimestamp * 1000).toISOString(),
+    dateModified: new Date(post.updateTimestamp * 1000).toISOString(),
+    author: {
+      "@type": "Person",
+      name: post.author,
+    },
+    articleSection: post.category || undefined,
+    keywords: post.tags.length > 0 ? post.tags.join(", ") : undefined,
+    wordCount: post.wordCount,
+  };
+
+  // Escaping "<" keeps a "</script>" within any value from closing the script element.
+  return JSON.stringify(jsonLd).replace(/</g, "\\u003c");
+}
+
+function getFirstImageUrl(root: DocumentNode, siteUrl: string): string | null {
+  if (root.tag.type === "image") {
+    const link = root.tag.link;
+    switch (link.type) {
+      case "external":
+        return link.url;
+
+      case "embedded":
+        return new URL(`/api/resources/${link.uuid}`, siteUrl).toString();
+    }
+  }
+
+  for (const child of root.children) {
+    const url = getFirstImageUrl(child, siteUrl);
+    if (url !== null) {
+      return url;
+    }
+  }
+
+  return null;
+}
@@ -12,6 +12,8 @@ export interface Post {
   category: string;
   tags: string[];
   isSpecial: boolean;
+  canonicalUrl: string | null;
+  wordCount: number;
   content: DocumentNode;
 }
 
@@ -1,4 +1,6 @@
 import { GetStaticPathsResult, GetStaticPropsContext, GetStaticPropsResult } from "next";
+import getConfig from "next/config";
+import Head from "next/head";
 import { useRouter } from "next/router";
 import { useRef } from "react";
 
@@ -6,6 +8,7 @@ import Document from "../../components/Document";
 import Loading from "../../components/Loading";
 import { TocContext, TocEntry, TocNav, TocScrollSpy } from "../../components/Toc";
 import { getPost } from "../../data/api";
+import { renderJsonLd } from "../../data/jsonLd";
 import { DocumentNode, Post } from "../../data/model";
 
 interface PostPageProps {
@@ -32,8 +35,16 @@ export default function PostPage({ post }: PostPageProps): JSX.Element {
   }
 
   const tocInfo = getDocumentTocInfo(post.content);
+  const { publicRuntimeConfig } = getConfig();
   return (
     <div className="dark:text-gray-200 selection:bg-gray-700 selection:text-white dark:selection:bg-gray-200 dark:selection:text-black">
+      <Head>
+        {post.canonicalUrl && <link rel="canonical" href={post.canonicalUrl} />}
+        <script
+          type="application/ld+json"
+          dangerouslySetInnerHTML={{ __html: renderJsonLd(post, publicRuntimeConfig.dataServerUrl) }}
+        />
+      </Head>
       <header>
         <h1 className="font-bold text-4xl mb-8">{post.title}</h1>
         <div className="flex items-center text-slate-600 dark:text-slate-400 text-sm my-2">